@@ -0,0 +1,20 @@
+/// Application and window that had focus when a capture was triggered
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveWindow {
+    pub app: String,
+    pub title: Option<String>,
+}
+
+/// The window that currently has focus, if the platform reports one
+pub fn current() -> Option<ActiveWindow> {
+    let window = active_win_pos_rs::get_active_window().ok()?;
+    let app = window.app_name.trim();
+    if app.is_empty() {
+        return None;
+    }
+    let title = window.title.trim();
+    Some(ActiveWindow {
+        app: app.to_string(),
+        title: (!title.is_empty()).then(|| title.to_string()),
+    })
+}
@@ -0,0 +1,167 @@
+use crate::models::{CitationSpan, Snippet, SnippetSource};
+
+/// A `[n]` marker found in an answer, with its span in UTF-16 code units so the
+/// frontend can slice the answer string directly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CitationMarker {
+    pub number: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+fn utf16_offset(text: &str, byte_index: usize) -> usize {
+    text[..byte_index].encode_utf16().count()
+}
+
+/// Find citation markers such as `[1]`, `[2][3]` or `[1, 3]` in `answer`.
+/// Numbers outside `1..=source_count` and markdown links (`[1](...)`) are ignored.
+/// A grouped marker like `[1, 3]` yields one entry per number, all sharing its span.
+pub fn parse_citations(answer: &str, source_count: usize) -> Vec<CitationMarker> {
+    let mut markers = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(open) = answer[search_from..].find('[').map(|i| search_from + i) {
+        let Some(close) = answer[open..].find(']').map(|i| open + i) else {
+            break;
+        };
+        search_from = open + 1;
+
+        let inner = &answer[open + 1..close];
+        if answer[close + 1..].starts_with('(') {
+            continue;
+        }
+        let numbers: Option<Vec<usize>> = inner
+            .split(',')
+            .map(|part| part.trim().parse::<usize>().ok())
+            .collect();
+        let Some(numbers) = numbers else {
+            continue;
+        };
+        if numbers.is_empty() || numbers.iter().any(|n| *n == 0 || *n > source_count) {
+            continue;
+        }
+
+        let start = utf16_offset(answer, open);
+        let end = utf16_offset(answer, close + 1);
+        markers.extend(
+            numbers
+                .into_iter()
+                .map(|number| CitationMarker { number, start, end }),
+        );
+        search_from = close + 1;
+    }
+
+    markers
+}
+
+/// Sources actually cited in `answer`, in citation-number order. `hits` are the
+/// snippets in the order they were numbered in the prompt; uncited ones are dropped.
+pub fn cited_sources(answer: &str, hits: &[(Snippet, f64)]) -> Vec<SnippetSource> {
+    let markers = parse_citations(answer, hits.len());
+
+    hits.iter()
+        .enumerate()
+        .filter_map(|(index, (snippet, score))| {
+            let citation = index + 1;
+            let positions: Vec<CitationSpan> = markers
+                .iter()
+                .filter(|m| m.number == citation)
+                .map(|m| CitationSpan {
+                    start: m.start,
+                    end: m.end,
+                })
+                .collect();
+            if positions.is_empty() {
+                return None;
+            }
+            Some(SnippetSource {
+                id: snippet.id.clone(),
+                title: snippet.title.clone(),
+                score: *score,
+                citation,
+                positions,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_and_adjacent_citations() {
+        let markers = parse_citations("Restart the daemon [1]. See also [2][3].", 3);
+
+        let numbers: Vec<usize> = markers.iter().map(|m| m.number).collect();
+        assert_eq!(numbers, vec![1, 2, 3]);
+        assert_eq!((markers[0].start, markers[0].end), (19, 22));
+        assert_eq!((markers[1].start, markers[1].end), (33, 36));
+    }
+
+    #[test]
+    fn test_parse_grouped_citation_shares_span() {
+        let markers = parse_citations("Both apply [1, 2].", 2);
+
+        assert_eq!(markers.len(), 2);
+        assert_eq!(markers[0].start, markers[1].start);
+        assert_eq!(markers[0].end, markers[1].end);
+    }
+
+    #[test]
+    fn test_parse_ignores_out_of_range_links_and_text() {
+        let answer = "Use arr[0] or [4], not [docs](http://x) or [1](http://y). Done [2]";
+        let markers = parse_citations(answer, 3);
+
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].number, 2);
+    }
+
+    #[test]
+    fn test_positions_are_utf16_offsets() {
+        // "é" is two UTF-8 bytes but one UTF-16 unit; "😀" is four bytes and two units
+        let markers = parse_citations("é😀 [1]", 1);
+
+        assert_eq!((markers[0].start, markers[0].end), (4, 7));
+    }
+
+    #[test]
+    fn test_cited_sources_drops_uncited() {
+        // Given
+        let hit = |id: &str| {
+            let snippet = Snippet {
+                id: id.to_string(),
+                title: id.to_uppercase(),
+                problem: String::new(),
+                solution: None,
+                summary: None,
+                code: None,
+                code_language: None,
+                reference_url: None,
+                tags: vec![],
+                files: vec![],
+                created_at: String::new(),
+                updated_at: String::new(),
+                is_favorite: false,
+                is_deleted: false,
+                deleted_at: None,
+                last_accessed_at: None,
+                source_app: None,
+                captured_at: None,
+                status: Default::default(),
+            };
+            (snippet, 0.5)
+        };
+        let hits = vec![hit("a"), hit("b"), hit("c")];
+
+        // When
+        let sources = cited_sources("Use the fix from [3], as noted in [3] and [1].", &hits);
+
+        // Then
+        let cited: Vec<(&str, usize, usize)> = sources
+            .iter()
+            .map(|s| (s.id.as_str(), s.citation, s.positions.len()))
+            .collect();
+        assert_eq!(cited, vec![("a", 1, 1), ("c", 3, 2)]);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::models::Snippet;
+
+/// Rough characters-per-token ratio used to estimate prompt size without a tokenizer
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Smallest remaining budget worth spending on a truncated snippet
+const MIN_SECTION_TOKENS: usize = 48;
+
+const TRUNCATION_MARKER: &str = "… (truncated)";
+
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Cut `text` to about `max_tokens`, preferring a line break near the end of the kept part
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    if estimate_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+    let max_chars = (max_tokens * CHARS_PER_TOKEN).saturating_sub(TRUNCATION_MARKER.len());
+    let kept: String = text.chars().take(max_chars).collect();
+    let kept = match kept.rfind('\n') {
+        Some(newline) if newline >= kept.len() / 2 => &kept[..newline],
+        _ => kept.as_str(),
+    };
+    format!("{}\n{TRUNCATION_MARKER}", kept.trim_end())
+}
+
+fn render_header(number: usize, snippet: &Snippet, score: f64) -> String {
+    let mut header = format!(
+        "### [{number}] {} (Relevance: {:.0}%)\n**Problem:** {}\n",
+        snippet.title,
+        score * 100.0,
+        snippet.problem
+    );
+    if let Some(summary) = snippet.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        header.push_str(&format!("**Summary:** {summary}\n"));
+    }
+    header
+}
+
+fn render_code(label: &str, language: &str, content: &str) -> String {
+    format!("**{label}:**\n```{language}\n{content}\n```\n")
+}
+
+/// Render one snippet within `budget` tokens. The header is always kept; the solution
+/// comes next because it is the fix the user wants to recall, then the code files in order.
+fn render_snippet(number: usize, snippet: &Snippet, score: f64, budget: usize) -> String {
+    let mut section = render_header(number, snippet, score);
+    let mut remaining = budget.saturating_sub(estimate_tokens(&section));
+
+    if let Some(solution) = snippet.solution.as_deref().filter(|s| !s.trim().is_empty()) {
+        let overhead = estimate_tokens("**Solution:** \n");
+        if remaining > overhead {
+            let text = truncate_to_tokens(solution, remaining - overhead);
+            let part = format!("**Solution:** {text}\n");
+            remaining = remaining.saturating_sub(estimate_tokens(&part));
+            section.push_str(&part);
+        }
+    }
+
+    for file in &snippet.files {
+        let label = file.filename.as_deref().unwrap_or("Code");
+        let language = file.language.as_deref().unwrap_or("");
+        let overhead = estimate_tokens(&render_code(label, language, ""));
+        if remaining <= overhead {
+            break;
+        }
+        let content = truncate_to_tokens(&file.content, remaining - overhead);
+        let part = render_code(label, language, &content);
+        remaining = remaining.saturating_sub(estimate_tokens(&part));
+        section.push_str(&part);
+    }
+
+    section
+}
+
+/// Pack the retrieved snippets into about `max_tokens` tokens. `hits` must be ordered
+/// best first; the n-th hit is labelled `[n]` so the answer can cite it. Snippets that
+/// fit are included whole; the first one that does not is truncated to the space left,
+/// and the rest are dropped.
+pub fn build_context(hits: &[(Snippet, f64)], max_tokens: usize) -> String {
+    let mut parts = Vec::new();
+    let mut remaining = max_tokens;
+    for (index, (snippet, score)) in hits.iter().enumerate() {
+        let number = index + 1;
+        let full = render_snippet(number, snippet, *score, usize::MAX);
+        let cost = estimate_tokens(&full);
+        if cost <= remaining {
+            remaining -= cost;
+            parts.push(full);
+            continue;
+        }
+        if remaining >= MIN_SECTION_TOKENS {
+            parts.push(render_snippet(number, snippet, *score, remaining));
+        }
+        break;
+    }
+
+    parts.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SnippetFile;
+
+    fn make_snippet(id: &str, solution: &str, code: &str) -> Snippet {
+        Snippet {
+            id: id.to_string(),
+            title: format!("Snippet {id}"),
+            problem: "Build fails".to_string(),
+            solution: Some(solution.to_string()),
+            summary: None,
+            code: Some(code.to_string()),
+            code_language: Some("rust".to_string()),
+            reference_url: None,
+            tags: vec![],
+            files: vec![SnippetFile {
+                id: format!("{id}-f"),
+                snippet_id: id.to_string(),
+                filename: Some("main.rs".to_string()),
+                language: Some("rust".to_string()),
+                content: code.to_string(),
+                position: 0,
+            }],
+            created_at: "2026-02-09".to_string(),
+            updated_at: "2026-02-09".to_string(),
+            is_favorite: false,
+            is_deleted: false,
+            deleted_at: None,
+            last_accessed_at: None,
+            source_app: None,
+            captured_at: None,
+            status: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_truncate_to_tokens() {
+        assert_eq!(truncate_to_tokens("short", 10), "short");
+
+        let text = "line one\n".repeat(50);
+        let cut = truncate_to_tokens(&text, 20);
+        assert!(estimate_tokens(&cut) <= 21);
+        assert!(cut.ends_with(TRUNCATION_MARKER));
+        assert!(cut.starts_with("line one\nline one"));
+    }
+
+    #[test]
+    fn test_build_context_includes_solution_and_full_code() {
+        // Given
+        let code = "fn main() {\n".to_string() + &"    step();\n".repeat(40) + "}";
+        let hits = vec![(make_snippet("a", "Pin the toolchain", &code), 0.9)];
+
+        // When
+        let context = build_context(&hits, 2000);
+
+        // Then
+        assert!(context.contains("**Solution:** Pin the toolchain"));
+        assert!(context.contains(&code));
+        assert!(!context.contains(TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_build_context_numbers_hits_and_truncates() {
+        // Given
+        let code = "x\n".repeat(400);
+        let hits = vec![
+            (make_snippet("high", "High fix", &code), 0.8),
+            (make_snippet("mid", "Mid fix", &code), 0.6),
+            (make_snippet("low", "Low fix", &code), 0.4),
+        ];
+
+        // When
+        let context = build_context(&hits, 300);
+
+        // Then
+        assert!(context.starts_with("### [1] Snippet high"));
+        assert!(context.contains("### [2] Snippet mid"));
+        assert!(!context.contains("Snippet low"));
+        assert!(context.contains(TRUNCATION_MARKER));
+        assert!(estimate_tokens(&context) <= 310);
+    }
+}
@@ -0,0 +1,212 @@
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use super::ollama::OllamaClient;
+use crate::models::{DiagnosticStep, OllamaDiagnostics};
+
+/// Host and port the base URL points at
+pub fn parse_target(base_url: &str) -> Result<(String, u16), String> {
+    let url = reqwest::Url::parse(base_url).map_err(|e| format!("Invalid URL: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Unsupported scheme '{}'", url.scheme()));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| "URL has no host".to_string())?
+        .trim_matches(['[', ']'])
+        .to_string();
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| "URL has no port".to_string())?;
+    Ok((host, port))
+}
+
+fn step(name: &str, started: Instant, result: &Result<String, String>) -> DiagnosticStep {
+    let (ok, detail) = match result {
+        Ok(detail) => (true, detail.clone()),
+        Err(error) => (false, error.clone()),
+    };
+    DiagnosticStep {
+        name: name.to_string(),
+        ok,
+        detail,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+async fn resolve(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| format!("Cannot resolve '{host}': {e}"))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!("'{host}' resolved to no addresses"));
+    }
+    Ok(addrs)
+}
+
+async fn connect(addrs: &[SocketAddr], client: &OllamaClient) -> Result<SocketAddr, String> {
+    let mut errors = vec![];
+    for addr in addrs {
+        let attempt = tokio::time::timeout(
+            client.config().connect_timeout,
+            tokio::net::TcpStream::connect(addr),
+        )
+        .await;
+        match attempt {
+            Ok(Ok(_)) => return Ok(*addr),
+            Ok(Err(e)) => errors.push(format!("{addr}: {e}")),
+            Err(_) => errors.push(format!("{addr}: timed out")),
+        }
+    }
+    Err(format!("Cannot connect to {}", errors.join("; ")))
+}
+
+/// Walk through URL parsing, DNS, TCP connect and an `/api/version` request,
+/// stopping at the first step that fails
+pub async fn diagnose(client: &OllamaClient) -> OllamaDiagnostics {
+    let mut report = OllamaDiagnostics {
+        base_url: client.base_url().to_string(),
+        reachable: false,
+        version: None,
+        steps: vec![],
+    };
+
+    let started = Instant::now();
+    let target = parse_target(client.base_url());
+    let summary = target
+        .as_ref()
+        .map(|(host, port)| format!("{host}:{port}"))
+        .map_err(Clone::clone);
+    report.steps.push(step("url", started, &summary));
+    let Ok((host, port)) = target else {
+        return report;
+    };
+
+    let started = Instant::now();
+    let addrs = resolve(&host, port).await;
+    let summary = addrs.as_ref().map_err(Clone::clone).map(|addrs| {
+        addrs
+            .iter()
+            .map(|a| a.ip().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    report.steps.push(step("dns", started, &summary));
+    let Ok(addrs) = addrs else {
+        return report;
+    };
+
+    let started = Instant::now();
+    let connected = connect(&addrs, client)
+        .await
+        .map(|addr| format!("Connected to {addr}"));
+    report.steps.push(step("connect", started, &connected));
+    if connected.is_err() {
+        return report;
+    }
+
+    let started = Instant::now();
+    let version = client.version().await;
+    let summary = version
+        .as_ref()
+        .map(|v| format!("Ollama {v}"))
+        .map_err(Clone::clone);
+    report.steps.push(step("http", started, &summary));
+    report.reachable = version.is_ok();
+    report.version = version.ok();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::ollama::OllamaHttp;
+    use crate::models::Settings;
+
+    fn client_for(base_url: String) -> OllamaClient {
+        let settings = Settings {
+            ollama_base_url: base_url,
+            ..Settings::default()
+        };
+        OllamaHttp::default().client(&settings)
+    }
+
+    #[test]
+    fn test_parse_target() {
+        assert_eq!(
+            parse_target("http://localhost:11434").unwrap(),
+            ("localhost".to_string(), 11434)
+        );
+        assert_eq!(
+            parse_target("https://ollama.example.com").unwrap(),
+            ("ollama.example.com".to_string(), 443)
+        );
+        assert_eq!(
+            parse_target("http://[::1]:11434").unwrap(),
+            ("::1".to_string(), 11434)
+        );
+        assert!(parse_target("localhost:11434").is_err());
+        assert!(parse_target("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_reports_version() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Given: a server answering /api/version
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            // The connect step opens and drops one connection before the HTTP request
+            for _ in 0..2 {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = [0u8; 1024];
+                if socket.read(&mut request).await.unwrap_or(0) == 0 {
+                    continue;
+                }
+                let body = r#"{"version":"0.6.2"}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        // When
+        let report = diagnose(&client_for(format!("http://{addr}"))).await;
+
+        // Then
+        let names: Vec<&str> = report.steps.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["url", "dns", "connect", "http"]);
+        assert!(report.reachable);
+        assert_eq!(report.version.as_deref(), Some("0.6.2"));
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_stops_at_failed_connect() {
+        // Given: a port with nothing listening
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        // When
+        let report = diagnose(&client_for(format!("http://{addr}"))).await;
+
+        // Then
+        let last = report.steps.last().unwrap();
+        assert_eq!(last.name, "connect");
+        assert!(!last.ok);
+        assert!(!report.reachable);
+        assert_eq!(report.steps.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_rejects_bad_url() {
+        let report = diagnose(&client_for("localhost:11434".to_string())).await;
+
+        assert_eq!(report.steps.len(), 1);
+        assert!(!report.steps[0].ok);
+    }
+}
@@ -1,55 +1,200 @@
+use tokio::task::JoinSet;
+
+use super::ollama::OllamaClient;
 use crate::db::Database;
-use crate::models::Snippet;
+use crate::models::{BulkItemResult, Settings, Snippet};
 
-/// Prepare text for embedding by combining snippet fields.
+/// Prepare text for embedding by combining snippet fields and all of its files.
 /// Title is repeated for higher weight.
 pub fn prepare_text(snippet: &Snippet) -> String {
-    format!(
+    let mut text = format!(
         "{title} {title} {problem} {solution}",
         title = snippet.title,
         problem = snippet.problem,
         solution = snippet.solution.as_deref().unwrap_or("")
-    )
-    .trim()
-    .to_string()
+    );
+
+    for file in &snippet.files {
+        if let Some(ref filename) = file.filename {
+            text.push(' ');
+            text.push_str(filename);
+        }
+        text.push(' ');
+        text.push_str(&file.content);
+    }
+
+    text.trim().to_string()
 }
 
-/// Save an embedding vector to the database
-pub fn save_embedding(
-    db: &Database,
-    snippet_id: &str,
-    embedding: &[f32],
+/// Characters of each archived reference page added to an embedding, so a long page
+/// cannot drown out the snippet itself
+const REFERENCE_EMBED_CHARS: usize = 2000;
+
+/// Text embedded for a stored snippet: [`prepare_text`], followed by the start of each
+/// archived reference page when the `embed_references` setting is on
+pub fn document_text(db: &Database, snippet: &Snippet) -> String {
+    let mut text = prepare_text(snippet);
+    if !db.settings().get_or_default().embed_references {
+        return text;
+    }
+    for reference in db.references().for_snippet(&snippet.id).unwrap_or_default() {
+        if let Some(title) = &reference.title {
+            text.push(' ');
+            text.push_str(title);
+        }
+        text.push(' ');
+        text.extend(reference.content.chars().take(REFERENCE_EMBED_CHARS));
+    }
+    text.trim_end().to_string()
+}
+
+/// How bulk embedding splits its work
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatchOptions {
+    /// Texts sent in one `/api/embed` request
+    pub batch_size: usize,
+    /// Requests in flight at the same time
+    pub concurrency: usize,
+}
+
+impl BatchOptions {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            batch_size: settings.embedding_batch_size.max(1) as usize,
+            concurrency: settings.embedding_concurrency.max(1) as usize,
+        }
+    }
+}
+
+/// Embed `texts` in batches, running up to `concurrency` requests at once. Returns one
+/// result per text in input order; a failed request fails every text in its batch.
+/// `on_progress` receives the number of texts finished so far.
+pub async fn embed_texts(
+    ollama: &OllamaClient,
     model: &str,
-) -> Result<(), String> {
-    let embedding_bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
-
-    db.with_connection(|conn| {
-        conn.execute(
-            "INSERT OR REPLACE INTO embeddings (snippet_id, embedding, embedding_model, created_at)
-             VALUES (?1, ?2, ?3, datetime('now'))",
-            rusqlite::params![snippet_id, embedding_bytes, model],
-        )?;
-        Ok(())
-    })
-    .map_err(|e| format!("Failed to save embedding: {}", e))
+    texts: &[String],
+    options: BatchOptions,
+    mut on_progress: impl FnMut(usize),
+) -> Vec<Result<Vec<f32>, String>> {
+    let batches: Vec<Vec<String>> = texts
+        .chunks(options.batch_size.max(1))
+        .map(|chunk| chunk.to_vec())
+        .collect();
+    let mut outcomes: Vec<Option<Result<Vec<Vec<f32>>, String>>> = vec![None; batches.len()];
+
+    let mut tasks = JoinSet::new();
+    let mut next = 0;
+    let mut done = 0;
+    while next < batches.len() || !tasks.is_empty() {
+        while next < batches.len() && tasks.len() < options.concurrency.max(1) {
+            let ollama = ollama.clone();
+            let model = model.to_string();
+            let batch = batches[next].clone();
+            let index = next;
+            tasks.spawn(async move { (index, ollama.create_embeddings(&batch, &model).await) });
+            next += 1;
+        }
+        if let Some(Ok((index, outcome))) = tasks.join_next().await {
+            done += batches[index].len();
+            outcomes[index] = Some(outcome);
+            on_progress(done);
+        }
+    }
+
+    batches
+        .iter()
+        .zip(outcomes)
+        .flat_map(|(batch, outcome)| {
+            let results: Vec<Result<Vec<f32>, String>> = match outcome {
+                Some(Ok(vectors)) => vectors.into_iter().map(Ok).collect(),
+                Some(Err(e)) => vec![Err(e); batch.len()],
+                None => vec![Err("Embedding task failed".to_string()); batch.len()],
+            };
+            results
+        })
+        .collect()
 }
 
 /// Generate and save embedding for a snippet (best-effort: silently skips if Ollama unavailable)
 pub async fn embed_snippet(
     db: &Database,
+    ollama: &OllamaClient,
     snippet: &Snippet,
     embedding_model: &str,
-    base_url: &str,
 ) -> Result<(), String> {
-    let text = prepare_text(snippet);
-    let embedding = super::ollama::create_embedding(&text, embedding_model, base_url).await?;
-    save_embedding(db, &snippet.id, &embedding, embedding_model)
+    let text = document_text(db, snippet);
+    let embedding = ollama.create_embedding(&text, embedding_model).await?;
+    db.embeddings()
+        .save_one(&snippet.id, &embedding, embedding_model)
+        .map_err(String::from)
+}
+
+/// Embed and save many snippets using batched requests. Returns one result per snippet
+/// in input order; snippets that no longer exist are reported as failures.
+pub async fn embed_snippets(
+    db: &Database,
+    ollama: &OllamaClient,
+    ids: &[String],
+    embedding_model: &str,
+    options: BatchOptions,
+    on_progress: impl FnMut(usize),
+) -> Vec<BulkItemResult> {
+    let mut found = vec![];
+    let mut texts = vec![];
+    let mut results: Vec<BulkItemResult> = Vec::with_capacity(ids.len());
+    for id in ids {
+        match db.snippets().get(id) {
+            Ok(snippet) => {
+                found.push(results.len());
+                texts.push(document_text(db, &snippet));
+                results.push(BulkItemResult {
+                    id: id.clone(),
+                    success: true,
+                    changed: true,
+                    error: None,
+                });
+            }
+            Err(e) => results.push(BulkItemResult {
+                id: id.clone(),
+                success: false,
+                changed: false,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+
+    let vectors = embed_texts(ollama, embedding_model, &texts, options, on_progress).await;
+    let mut embedded = vec![];
+    for (index, vector) in found.into_iter().zip(vectors) {
+        match vector {
+            Ok(vector) => embedded.push((index, vector)),
+            Err(e) => {
+                results[index].success = false;
+                results[index].changed = false;
+                results[index].error = Some(e);
+            }
+        }
+    }
+
+    let rows: Vec<(String, Vec<f32>)> = embedded
+        .iter()
+        .map(|(index, vector)| (results[*index].id.clone(), vector.clone()))
+        .collect();
+    if let Err(e) = db.embeddings().save(&rows, embedding_model) {
+        let e = e.to_string();
+        for (index, _) in &embedded {
+            results[*index].success = false;
+            results[*index].changed = false;
+            results[*index].error = Some(e.clone());
+        }
+    }
+    results
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Snippet;
+    use crate::models::{Snippet, SnippetFile};
 
     fn make_snippet(title: &str, problem: &str, solution: Option<&str>) -> Snippet {
         Snippet {
@@ -57,16 +202,21 @@ mod tests {
             title: title.to_string(),
             problem: problem.to_string(),
             solution: solution.map(String::from),
+            summary: None,
             code: None,
             code_language: None,
             reference_url: None,
             tags: vec![],
+            files: vec![],
             created_at: "2026-02-09".to_string(),
             updated_at: "2026-02-09".to_string(),
             is_favorite: false,
             is_deleted: false,
             deleted_at: None,
             last_accessed_at: None,
+            source_app: None,
+            captured_at: None,
+            status: Default::default(),
         }
     }
 
@@ -97,86 +247,171 @@ mod tests {
     }
 
     #[test]
-    fn test_save_and_read_embedding() {
-        let db = Database::new_in_memory().unwrap();
+    fn test_prepare_text_includes_all_files() {
+        let mut snippet = make_snippet("Deploy", "Service won't boot", None);
+        snippet.files = vec![
+            SnippetFile {
+                id: "f1".to_string(),
+                snippet_id: "test-id".to_string(),
+                filename: Some("Dockerfile".to_string()),
+                language: Some("dockerfile".to_string()),
+                content: "FROM rust:1".to_string(),
+                position: 0,
+            },
+            SnippetFile {
+                id: "f2".to_string(),
+                snippet_id: "test-id".to_string(),
+                filename: None,
+                language: Some("bash".to_string()),
+                content: "docker compose up".to_string(),
+                position: 1,
+            },
+        ];
+        let text = prepare_text(&snippet);
 
-        // Insert a snippet first
+        assert!(text.contains("Dockerfile FROM rust:1"));
+        assert!(text.contains("docker compose up"));
+    }
+
+    #[test]
+    fn test_document_text_adds_references_only_when_enabled() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
         db.with_connection(|conn| {
-            conn.execute(
-                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Test', 'Problem')",
-                [],
-            )?;
-            Ok(())
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Prune', 'Disk full');",
+            )
         })
         .unwrap();
+        db.references()
+            .save(
+                "s1",
+                "https://a.test/prune",
+                Some("Docker docs"),
+                &"x".repeat(5000),
+            )
+            .unwrap();
+        let snippet = db.snippets().get("s1").unwrap();
 
-        // Save embedding
-        let embedding = vec![0.1_f32, 0.2, 0.3, 0.4];
-        save_embedding(&db, "s1", &embedding, "test-model").unwrap();
-
-        // Verify it was saved
-        let (stored_model, stored_blob): (String, Vec<u8>) = db
-            .with_connection(|conn| {
-                conn.query_row(
-                    "SELECT embedding_model, embedding FROM embeddings WHERE snippet_id = 's1'",
-                    [],
-                    |row| Ok((row.get(0)?, row.get(1)?)),
-                )
-            })
+        // When
+        let without = document_text(&db, &snippet);
+        db.with_connection(|conn| conn.execute_batch("UPDATE settings SET embed_references = 1;"))
             .unwrap();
+        let with = document_text(&db, &snippet);
 
-        assert_eq!(stored_model, "test-model");
+        // Then
+        assert_eq!(without, prepare_text(&snippet));
+        assert!(with.starts_with(&without));
+        assert!(with.contains("Docker docs"));
+        assert_eq!(with.matches('x').count(), REFERENCE_EMBED_CHARS);
+    }
 
-        // Decode stored blob back to f32 vector
-        let decoded: Vec<f32> = stored_blob
-            .chunks_exact(4)
-            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
-            .collect();
+    /// Fake `/api/embed` that answers every request with `[text length]` per input text
+    async fn serve_embeddings(requests: usize) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
-        assert_eq!(decoded, embedding);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for _ in 0..requests {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut raw = vec![];
+                    let mut buf = [0u8; 4096];
+                    let body = loop {
+                        let n = socket.read(&mut buf).await.unwrap();
+                        raw.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&raw).to_string();
+                        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                            let length: usize = head
+                                .lines()
+                                .find_map(|l| {
+                                    l.to_lowercase()
+                                        .strip_prefix("content-length: ")
+                                        .map(String::from)
+                                })
+                                .and_then(|v| v.trim().parse().ok())
+                                .unwrap_or(0);
+                            if body.len() >= length {
+                                break body.to_string();
+                            }
+                        }
+                    };
+                    let request: serde_json::Value = serde_json::from_str(&body).unwrap();
+                    let vectors: Vec<Vec<f32>> = request["input"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .map(|t| vec![t.as_str().unwrap().len() as f32])
+                        .collect();
+                    let body = serde_json::json!({ "embeddings": vectors }).to_string();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{addr}")
     }
 
-    #[test]
-    fn test_save_embedding_replaces_existing() {
-        let db = Database::new_in_memory().unwrap();
+    #[tokio::test]
+    async fn test_embed_texts_keeps_input_order_across_batches() {
+        // Given
+        let settings = Settings {
+            ollama_base_url: serve_embeddings(3).await,
+            ..Settings::default()
+        };
+        let ollama = crate::ai::ollama::OllamaHttp::default().client(&settings);
+        let texts: Vec<String> = ["a", "bb", "ccc", "dddd", "eeeee"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let options = BatchOptions {
+            batch_size: 2,
+            concurrency: 3,
+        };
+        let mut progress = vec![];
 
-        db.with_connection(|conn| {
-            conn.execute(
-                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Test', 'Problem')",
-                [],
-            )?;
-            Ok(())
+        // When
+        let results = embed_texts(&ollama, "nomic-embed-text", &texts, options, |done| {
+            progress.push(done)
         })
-        .unwrap();
+        .await;
 
-        // Save first embedding
-        save_embedding(&db, "s1", &[1.0, 2.0], "model-v1").unwrap();
-
-        // Replace with new embedding
-        save_embedding(&db, "s1", &[3.0, 4.0], "model-v2").unwrap();
-
-        // Should have only one row
-        let count: i32 = db
-            .with_connection(|conn| {
-                conn.query_row(
-                    "SELECT COUNT(*) FROM embeddings WHERE snippet_id = 's1'",
-                    [],
-                    |row| row.get(0),
-                )
-            })
-            .unwrap();
-        assert_eq!(count, 1);
-
-        // Should be the new one
-        let model: String = db
-            .with_connection(|conn| {
-                conn.query_row(
-                    "SELECT embedding_model FROM embeddings WHERE snippet_id = 's1'",
-                    [],
-                    |row| row.get(0),
-                )
-            })
-            .unwrap();
-        assert_eq!(model, "model-v2");
+        // Then
+        let lengths: Vec<f32> = results.into_iter().map(|r| r.unwrap()[0]).collect();
+        assert_eq!(lengths, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(progress.len(), 3);
+        assert_eq!(progress.last(), Some(&5));
+    }
+
+    #[tokio::test]
+    async fn test_embed_texts_fails_whole_batch_on_error() {
+        // Given: nothing listening
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let settings = Settings {
+            ollama_base_url: format!("http://{}", listener.local_addr().unwrap()),
+            ollama_max_retries: 0,
+            ..Settings::default()
+        };
+        drop(listener);
+        let ollama = crate::ai::ollama::OllamaHttp::default().client(&settings);
+        let texts = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        // When
+        let results = embed_texts(
+            &ollama,
+            "nomic-embed-text",
+            &texts,
+            BatchOptions::from_settings(&settings),
+            |_| {},
+        )
+        .await;
+
+        // Then
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| r.is_err()));
     }
 }
@@ -0,0 +1,65 @@
+use super::ollama::{GenerateOptions, OllamaClient};
+use super::prompts;
+use crate::commands::prompt::render_prompt;
+use crate::db::Database;
+use crate::language;
+use crate::models::{AiFeature, Settings};
+
+/// Longest code excerpt sent to the LLM for language detection
+const MAX_PROMPT_CODE_CHARS: usize = 2000;
+
+/// Ask the LLM to name the language of a code block; only registry languages are accepted
+pub async fn detect_with_llm(
+    db: &Database,
+    ollama: &OllamaClient,
+    content: &str,
+    settings: &Settings,
+) -> Option<&'static str> {
+    let excerpt: String = content.chars().take(MAX_PROMPT_CODE_CHARS).collect();
+    let prompt = render_prompt(
+        db,
+        prompts::DETECT_LANGUAGE,
+        &[("code", format!("```\n{excerpt}\n```"))],
+    );
+
+    let answer = ollama
+        .generate(
+            &prompt,
+            settings.model_for(AiFeature::Language),
+            &GenerateOptions::from_settings(settings),
+        )
+        .await
+        .ok()?;
+    let word = answer
+        .split_whitespace()
+        .next()?
+        .trim_matches(|c: char| c == '`' || c == '.' || c == '"' || c == '\'');
+    language::normalize(word)
+}
+
+/// Language to store for a code block: the given value in canonical form, otherwise a
+/// heuristic guess, otherwise the LLM's answer when enabled in settings
+pub async fn resolve(
+    db: &Database,
+    ollama: &OllamaClient,
+    settings: &Settings,
+    given: Option<&str>,
+    content: &str,
+    filename: Option<&str>,
+) -> Option<String> {
+    if let Some(given) = given.and_then(language::canonicalize) {
+        return Some(given);
+    }
+    if content.trim().is_empty() && filename.is_none() {
+        return None;
+    }
+    if let Some(detected) = language::detect(content, filename) {
+        return Some(detected.to_string());
+    }
+    if settings.detect_language_with_llm && !content.trim().is_empty() {
+        return detect_with_llm(db, ollama, content, settings)
+            .await
+            .map(String::from);
+    }
+    None
+}
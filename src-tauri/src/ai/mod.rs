@@ -1,2 +1,10 @@
+pub mod citation;
+pub mod context;
+pub mod diagnostics;
 pub mod embedding;
+pub mod language;
 pub mod ollama;
+pub mod prompts;
+pub mod summary;
+pub mod tags;
+pub mod vector;
@@ -1,10 +1,21 @@
-use reqwest::Client;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 
+use crate::models::{ModelDetails, Settings};
+
+/// Longest wait between two retries
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Texts an embedding request may take per request timeout
+const EMBED_TEXTS_PER_TIMEOUT: usize = 16;
+
 #[derive(Serialize)]
-struct EmbeddingRequest {
-    model: String,
-    input: String,
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
 }
 
 #[derive(Deserialize)]
@@ -22,49 +33,76 @@ struct OllamaModel {
     name: String,
 }
 
-/// Check if Ollama is running and reachable
-pub async fn check_connection(base_url: &str) -> Result<bool, String> {
-    let client = Client::new();
-
-    match client
-        .get(format!("{}/api/tags", base_url))
-        .timeout(std::time::Duration::from_secs(3))
-        .send()
-        .await
-    {
-        Ok(response) => Ok(response.status().is_success()),
-        Err(_) => Ok(false),
-    }
+#[derive(Deserialize)]
+struct VersionResponse {
+    version: String,
 }
 
-/// List available Ollama models
-pub async fn list_models(base_url: &str) -> Result<Vec<String>, String> {
-    let client = Client::new();
-
-    let response = client
-        .get(format!("{}/api/tags", base_url))
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama connection failed: {}", e))?;
+/// Sampling and runtime options sent with generation requests. Unset fields are
+/// omitted so Ollama falls back to the model's own defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GenerateOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+    /// Sent next to the options rather than inside them
+    #[serde(skip)]
+    pub keep_alive: Option<String>,
+}
 
-    if !response.status().is_success() {
-        return Err(format!("Ollama returned status: {}", response.status()));
+impl GenerateOptions {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            temperature: settings.temperature,
+            top_p: settings.top_p,
+            seed: settings.seed,
+            num_ctx: settings.num_ctx,
+            num_predict: settings.num_predict,
+            keep_alive: settings.keep_alive.clone(),
+        }
     }
 
-    let tags_response: OllamaTagsResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
-
-    Ok(tags_response.models.into_iter().map(|m| m.name).collect())
+    fn has_model_options(&self) -> bool {
+        self.temperature.is_some()
+            || self.top_p.is_some()
+            || self.seed.is_some()
+            || self.num_ctx.is_some()
+            || self.num_predict.is_some()
+    }
 }
 
 #[derive(Serialize)]
-struct GenerateRequest {
+struct GenerateRequest<'a> {
     model: String,
     prompt: String,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<&'a GenerateOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<&'a str>,
+    /// `"json"` or a JSON schema the response must follow
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'a serde_json::Value>,
+}
+
+impl<'a> GenerateRequest<'a> {
+    fn new(prompt: &str, model: &str, options: &'a GenerateOptions) -> Self {
+        Self {
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            stream: false,
+            options: options.has_model_options().then_some(options),
+            keep_alive: options.keep_alive.as_deref(),
+            format: None,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -72,81 +110,660 @@ struct GenerateResponse {
     response: String,
 }
 
-/// Generate text using Ollama LLM
-pub async fn generate(prompt: &str, model: &str, base_url: &str) -> Result<String, String> {
-    let client = Client::new();
+#[derive(Serialize)]
+struct ModelRequest<'a> {
+    model: &'a str,
+}
 
-    let response = client
-        .post(format!("{}/api/generate", base_url))
-        .json(&GenerateRequest {
-            model: model.to_string(),
-            prompt: prompt.to_string(),
-            stream: false,
+#[derive(Serialize)]
+struct PullRequest<'a> {
+    model: &'a str,
+    stream: bool,
+}
+
+/// One line of the `/api/pull` progress stream
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PullStatus {
+    #[serde(default)]
+    pub status: String,
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Take the complete lines out of `buffer` and parse them as pull statuses.
+/// A trailing partial line stays in the buffer for the next chunk; bytes are only
+/// decoded once their line is complete, so a character split across chunks survives.
+pub fn drain_pull_statuses(buffer: &mut Vec<u8>) -> Result<Vec<PullStatus>, String> {
+    let Some(last_newline) = buffer.iter().rposition(|&b| b == b'\n') else {
+        return Ok(vec![]);
+    };
+    let complete: Vec<u8> = buffer.drain(..=last_newline).collect();
+    String::from_utf8_lossy(&complete)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| format!("Failed to parse pull progress: {}", e))
         })
-        .timeout(std::time::Duration::from_secs(120))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama generation failed: {}", e))?;
+        .collect()
+}
 
-    if !response.status().is_success() {
-        return Err(format!(
-            "Ollama generation returned status: {}",
-            response.status()
-        ));
+/// Read the `/api/show` response into the fields the app cares about
+pub fn parse_model_details(name: &str, body: &serde_json::Value) -> ModelDetails {
+    let details = &body["details"];
+    let text = |value: &serde_json::Value| value.as_str().map(String::from);
+    let info_value = |suffix: &str| {
+        body["model_info"].as_object().and_then(|info| {
+            info.iter()
+                .find(|(key, _)| key.ends_with(suffix))
+                .and_then(|(_, value)| value.as_u64())
+        })
+    };
+
+    ModelDetails {
+        name: name.to_string(),
+        family: text(&details["family"]),
+        parameter_size: text(&details["parameter_size"]),
+        quantization_level: text(&details["quantization_level"]),
+        parameters: text(&body["parameters"]),
+        context_length: info_value(".context_length"),
+        embedding_length: info_value(".embedding_length"),
+        capabilities: body["capabilities"]
+            .as_array()
+            .map(|items| items.iter().filter_map(text).collect())
+            .unwrap_or_default(),
     }
+}
 
-    let gen_response: GenerateResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse generation response: {}", e))?;
+/// Timeouts and retry policy for Ollama requests
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfig {
+    pub connect_timeout: Duration,
+    /// Limit for short requests: listing, inspecting and deleting models, embeddings
+    pub request_timeout: Duration,
+    /// Limit for text generation, which can take much longer
+    pub generate_timeout: Duration,
+    /// Extra attempts after a connection error or a 429/502/503/504 response
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further one
+    pub retry_backoff: Duration,
+}
 
-    Ok(gen_response.response)
+impl ClientConfig {
+    pub fn from_settings(settings: &Settings) -> Self {
+        let secs = |value: i32| Duration::from_secs(value.max(1) as u64);
+        Self {
+            connect_timeout: secs(settings.ollama_connect_timeout_secs),
+            request_timeout: secs(settings.ollama_request_timeout_secs),
+            generate_timeout: secs(settings.ollama_generate_timeout_secs),
+            max_retries: settings.ollama_max_retries.max(0) as u32,
+            retry_backoff: Duration::from_millis(250),
+        }
+    }
 }
 
-/// Create an embedding vector for the given text
-pub async fn create_embedding(text: &str, model: &str, base_url: &str) -> Result<Vec<f32>, String> {
-    let client = Client::new();
+/// Wait before retry number `attempt` (0-based): `base` doubled per attempt, capped
+pub fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_DELAY)
+}
 
-    let response = client
-        .post(format!("{}/api/embed", base_url))
-        .json(&EmbeddingRequest {
-            model: model.to_string(),
-            input: text.to_string(),
-        })
-        .timeout(std::time::Duration::from_secs(30))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama embedding request failed: {}", e))?;
+/// Statuses Ollama returns while it is busy or restarting
+pub fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Long-lived HTTP client shared by all Ollama calls, kept in Tauri state so
+/// connections are pooled. It is rebuilt only when the connect timeout changes.
+#[derive(Default)]
+pub struct OllamaHttp {
+    cached: Mutex<Option<(Duration, Client)>>,
+}
+
+impl OllamaHttp {
+    /// Client for the Ollama server and timeouts configured in `settings`
+    pub fn client(&self, settings: &Settings) -> OllamaClient {
+        let config = ClientConfig::from_settings(settings);
+        let mut cached = self.cached.lock().unwrap();
+        let http = match cached.as_ref() {
+            Some((timeout, client)) if *timeout == config.connect_timeout => client.clone(),
+            _ => {
+                let client = Client::builder()
+                    .connect_timeout(config.connect_timeout)
+                    .build()
+                    .unwrap_or_default();
+                *cached = Some((config.connect_timeout, client.clone()));
+                client
+            }
+        };
+
+        OllamaClient {
+            http,
+            base_url: settings.ollama_base_url.trim_end_matches('/').to_string(),
+            config,
+        }
+    }
+}
+
+/// Handle for one Ollama server; cheap to clone
+#[derive(Debug, Clone)]
+pub struct OllamaClient {
+    http: Client,
+    base_url: String,
+    config: ClientConfig,
+}
+
+impl OllamaClient {
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Send the request built by `build`, retrying connection errors and transient
+    /// statuses with exponential backoff
+    async fn send(&self, build: impl Fn() -> RequestBuilder) -> Result<Response, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            let retryable = attempt < self.config.max_retries;
+            match build().send().await {
+                Ok(response) if retryable && is_transient_status(response.status()) => {}
+                Err(e) if retryable && e.is_connect() => {}
+                result => return result,
+            }
+            tokio::time::sleep(backoff_delay(self.config.retry_backoff, attempt)).await;
+            attempt += 1;
+        }
+    }
 
-    if !response.status().is_success() {
-        return Err(format!(
-            "Ollama embedding returned status: {}",
-            response.status()
-        ));
+    /// Check if Ollama is running and reachable
+    pub async fn check_connection(&self) -> Result<bool, String> {
+        match self
+            .http
+            .get(self.url("/api/tags"))
+            .timeout(self.config.request_timeout)
+            .send()
+            .await
+        {
+            Ok(response) => Ok(response.status().is_success()),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Version reported by the Ollama server
+    pub async fn version(&self) -> Result<String, String> {
+        let response = self
+            .http
+            .get(self.url("/api/version"))
+            .timeout(self.config.request_timeout)
+            .send()
+            .await
+            .map_err(|e| format!("Ollama connection failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Ollama returned status: {}", response.status()));
+        }
+
+        let version: VersionResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Ollama version: {}", e))?;
+        Ok(version.version)
     }
 
-    let emb_response: EmbeddingResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse embedding response: {}", e))?;
+    /// List available Ollama models
+    pub async fn list_models(&self) -> Result<Vec<String>, String> {
+        let response = self
+            .send(|| {
+                self.http
+                    .get(self.url("/api/tags"))
+                    .timeout(self.config.request_timeout)
+            })
+            .await
+            .map_err(|e| format!("Ollama connection failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Ollama returned status: {}", response.status()));
+        }
 
-    emb_response
-        .embeddings
-        .into_iter()
-        .next()
-        .ok_or_else(|| "No embedding returned".to_string())
+        let tags_response: OllamaTagsResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+
+        Ok(tags_response.models.into_iter().map(|m| m.name).collect())
+    }
+
+    /// Generate text using Ollama LLM
+    pub async fn generate(
+        &self,
+        prompt: &str,
+        model: &str,
+        options: &GenerateOptions,
+    ) -> Result<String, String> {
+        self.send_generate(&GenerateRequest::new(prompt, model, options))
+            .await
+    }
+
+    /// Generate a JSON response constrained to `schema` (Ollama structured outputs)
+    pub async fn generate_json(
+        &self,
+        prompt: &str,
+        model: &str,
+        options: &GenerateOptions,
+        schema: &serde_json::Value,
+    ) -> Result<String, String> {
+        let request = GenerateRequest {
+            format: Some(schema),
+            ..GenerateRequest::new(prompt, model, options)
+        };
+        self.send_generate(&request).await
+    }
+
+    async fn send_generate(&self, request: &GenerateRequest<'_>) -> Result<String, String> {
+        let response = self
+            .send(|| {
+                self.http
+                    .post(self.url("/api/generate"))
+                    .json(request)
+                    .timeout(self.config.generate_timeout)
+            })
+            .await
+            .map_err(|e| format!("Ollama generation failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!(
+                "Ollama generation returned status: {}",
+                response.status()
+            ));
+        }
+
+        let gen_response: GenerateResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse generation response: {}", e))?;
+
+        Ok(gen_response.response)
+    }
+
+    /// Create an embedding vector for the given text
+    pub async fn create_embedding(&self, text: &str, model: &str) -> Result<Vec<f32>, String> {
+        self.create_embeddings(&[text.to_string()], model)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No embedding returned".to_string())
+    }
+
+    /// Embed several texts in one request; vectors are returned in input order
+    pub async fn create_embeddings(
+        &self,
+        texts: &[String],
+        model: &str,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        if texts.is_empty() {
+            return Ok(vec![]);
+        }
+        let request = EmbeddingRequest {
+            model,
+            input: texts,
+        };
+        // Allow one request timeout per started group of texts
+        let groups = texts.len().div_ceil(EMBED_TEXTS_PER_TIMEOUT) as u32;
+        let timeout = self.config.request_timeout.saturating_mul(groups);
+        let response = self
+            .send(|| {
+                self.http
+                    .post(self.url("/api/embed"))
+                    .json(&request)
+                    .timeout(timeout)
+            })
+            .await
+            .map_err(|e| format!("Ollama embedding request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!(
+                "Ollama embedding returned status: {}",
+                response.status()
+            ));
+        }
+
+        let emb_response: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse embedding response: {}", e))?;
+
+        if emb_response.embeddings.len() != texts.len() {
+            return Err(format!(
+                "Ollama returned {} embeddings for {} texts",
+                emb_response.embeddings.len(),
+                texts.len()
+            ));
+        }
+        Ok(emb_response.embeddings)
+    }
+
+    /// Download a model, reporting each progress update to `on_progress`
+    pub async fn pull_model<F>(&self, name: &str, mut on_progress: F) -> Result<(), String>
+    where
+        F: FnMut(&PullStatus),
+    {
+        // Downloads can take a long time, so only the connection is bounded
+        let mut response = self
+            .send(|| {
+                self.http.post(self.url("/api/pull")).json(&PullRequest {
+                    model: name,
+                    stream: true,
+                })
+            })
+            .await
+            .map_err(|e| format!("Ollama pull failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!(
+                "Ollama pull returned status: {}",
+                response.status()
+            ));
+        }
+
+        let mut buffer = Vec::new();
+        let mut succeeded = false;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Ollama pull interrupted: {}", e))?
+        {
+            buffer.extend_from_slice(&chunk);
+            for status in drain_pull_statuses(&mut buffer)? {
+                if let Some(error) = status.error {
+                    return Err(format!("Ollama pull failed: {}", error));
+                }
+                succeeded |= status.status == "success";
+                on_progress(&status);
+            }
+        }
+
+        if succeeded {
+            Ok(())
+        } else {
+            Err("Ollama pull ended before completing".to_string())
+        }
+    }
+
+    /// Remove a model from the Ollama server
+    pub async fn delete_model(&self, name: &str) -> Result<(), String> {
+        let response = self
+            .send(|| {
+                self.http
+                    .delete(self.url("/api/delete"))
+                    .json(&ModelRequest { model: name })
+                    .timeout(self.config.request_timeout)
+            })
+            .await
+            .map_err(|e| format!("Ollama delete failed: {}", e))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(format!("Model '{}' is not installed", name));
+        }
+        if !response.status().is_success() {
+            return Err(format!(
+                "Ollama delete returned status: {}",
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Inspect an installed model
+    pub async fn show_model(&self, name: &str) -> Result<ModelDetails, String> {
+        let response = self
+            .send(|| {
+                self.http
+                    .post(self.url("/api/show"))
+                    .json(&ModelRequest { model: name })
+                    .timeout(self.config.request_timeout)
+            })
+            .await
+            .map_err(|e| format!("Ollama show failed: {}", e))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(format!("Model '{}' is not installed", name));
+        }
+        if !response.status().is_success() {
+            return Err(format!(
+                "Ollama show returned status: {}",
+                response.status()
+            ));
+        }
+
+        let body: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse model details: {}", e))?;
+        Ok(parse_model_details(name, &body))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+    fn default_client() -> OllamaClient {
+        OllamaHttp::default().client(&Settings::default())
+    }
+
+    /// Serve one canned HTTP response per connection, in order, on a local port
+    async fn serve(responses: Vec<&'static str>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = [0u8; 4096];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    fn client_for(base_url: String, max_retries: i32) -> OllamaClient {
+        let settings = Settings {
+            ollama_base_url: base_url,
+            ollama_max_retries: max_retries,
+            ..Settings::default()
+        };
+        OllamaHttp::default().client(&settings)
+    }
+
+    const UNAVAILABLE: &str =
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const TAGS: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 37\r\nConnection: close\r\n\r\n{\"models\":[{\"name\":\"llama3:latest\"}]}";
+
+    #[test]
+    fn test_backoff_delay_doubles_up_to_cap() {
+        let base = Duration::from_millis(250);
+
+        assert_eq!(backoff_delay(base, 0), Duration::from_millis(250));
+        assert_eq!(backoff_delay(base, 2), Duration::from_secs(1));
+        assert_eq!(backoff_delay(base, 10), MAX_RETRY_DELAY);
+        assert_eq!(backoff_delay(base, 40), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_transient_statuses() {
+        assert!(is_transient_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_transient_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_transient_status(StatusCode::NOT_FOUND));
+        assert!(!is_transient_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn test_client_uses_configured_timeouts() {
+        let settings = Settings {
+            ollama_base_url: "http://gpu-box:11434/".to_string(),
+            ollama_generate_timeout_secs: 600,
+            ..Settings::default()
+        };
+
+        let client = OllamaHttp::default().client(&settings);
+
+        assert_eq!(client.base_url(), "http://gpu-box:11434");
+        assert_eq!(client.config().generate_timeout, Duration::from_secs(600));
+    }
+
+    #[tokio::test]
+    async fn test_transient_status_is_retried() {
+        // Given
+        let base_url = serve(vec![UNAVAILABLE, TAGS]).await;
+
+        // When
+        let models = client_for(base_url, 2).list_models().await;
+
+        // Then
+        assert_eq!(models.unwrap(), vec!["llama3:latest"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_embeddings_checks_count() {
+        const ONE_VECTOR: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 26\r\nConnection: close\r\n\r\n{\"embeddings\":[[0.1,0.2]]}";
+        let base_url = serve(vec![ONE_VECTOR]).await;
+
+        let result = client_for(base_url, 0)
+            .create_embeddings(&["a".to_string(), "b".to_string()], "nomic-embed-text")
+            .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            "Ollama returned 1 embeddings for 2 texts"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retries_stop_after_limit() {
+        let base_url = serve(vec![UNAVAILABLE, TAGS]).await;
+
+        let result = client_for(base_url, 0).list_models().await;
+
+        assert!(result.unwrap_err().contains("503"));
+    }
+
+    #[test]
+    fn test_drain_pull_statuses_keeps_partial_line() {
+        let mut buffer = concat!(
+            "{\"status\":\"pulling manifest\"}\n",
+            "{\"status\":\"downloading\",\"digest\":\"sha256:ab\",\"total\":100,\"completed\":40}\n",
+            "{\"status\":\"succ"
+        )
+        .as_bytes()
+        .to_vec();
+
+        let statuses = drain_pull_statuses(&mut buffer).unwrap();
+
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[1].total, Some(100));
+        assert_eq!(statuses[1].completed, Some(40));
+        assert_eq!(buffer, b"{\"status\":\"succ");
+
+        buffer.extend_from_slice(b"ess\"}\n");
+        let statuses = drain_pull_statuses(&mut buffer).unwrap();
+        assert_eq!(statuses[0].status, "success");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_drain_pull_statuses_keeps_character_split_across_chunks() {
+        // Given - "é" is two bytes, and the chunk boundary falls between them
+        let line = "{\"status\":\"téléchargement\"}\n".as_bytes();
+        let split = line.iter().position(|&b| b == 0xC3).unwrap() + 1;
+        let mut buffer = line[..split].to_vec();
+
+        // When
+        let first = drain_pull_statuses(&mut buffer).unwrap();
+        buffer.extend_from_slice(&line[split..]);
+        let second = drain_pull_statuses(&mut buffer).unwrap();
+
+        // Then
+        assert!(first.is_empty());
+        assert_eq!(second[0].status, "téléchargement");
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_parse_model_details() {
+        let body = serde_json::json!({
+            "parameters": "num_ctx 4096\nstop \"<|im_end|>\"",
+            "details": {
+                "family": "qwen2",
+                "parameter_size": "7.6B",
+                "quantization_level": "Q4_K_M"
+            },
+            "model_info": {
+                "general.architecture": "qwen2",
+                "qwen2.context_length": 32768,
+                "qwen2.embedding_length": 3584
+            },
+            "capabilities": ["completion", "tools"]
+        });
+
+        let details = parse_model_details("qwen2.5-coder:7b", &body);
+
+        assert_eq!(details.family.as_deref(), Some("qwen2"));
+        assert_eq!(details.parameter_size.as_deref(), Some("7.6B"));
+        assert_eq!(details.context_length, Some(32768));
+        assert_eq!(details.embedding_length, Some(3584));
+        assert_eq!(details.capabilities, vec!["completion", "tools"]);
+        assert!(details.parameters.unwrap().contains("num_ctx 4096"));
+    }
+
+    #[test]
+    fn test_generate_request_omits_unset_options() {
+        let options = GenerateOptions::default();
+        let json = serde_json::to_value(GenerateRequest::new("hi", "llama3", &options)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({ "model": "llama3", "prompt": "hi", "stream": false })
+        );
+    }
+
+    #[test]
+    fn test_generate_request_includes_options_and_keep_alive() {
+        let options = GenerateOptions {
+            temperature: Some(0.2),
+            seed: Some(42),
+            num_predict: Some(256),
+            keep_alive: Some("10m".to_string()),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(GenerateRequest::new("hi", "llama3", &options)).unwrap();
+
+        assert_eq!(
+            json["options"],
+            serde_json::json!({ "temperature": 0.2, "seed": 42, "num_predict": 256 })
+        );
+        assert_eq!(json["keep_alive"], "10m");
+    }
 
     #[tokio::test]
     async fn test_check_connection() {
         // This test will pass whether Ollama is running or not
-        let result = check_connection(DEFAULT_OLLAMA_BASE_URL).await;
+        let result = default_client().check_connection().await;
         assert!(result.is_ok());
         // result is Ok(true) if running, Ok(false) if not
     }
@@ -154,41 +771,36 @@ mod tests {
     #[tokio::test]
     async fn test_list_models_when_ollama_not_running() {
         // If Ollama is not running, this should return an error
-        let connected = check_connection(DEFAULT_OLLAMA_BASE_URL)
-            .await
-            .unwrap_or(false);
+        let connected = default_client().check_connection().await.unwrap_or(false);
         if !connected {
-            let result = list_models(DEFAULT_OLLAMA_BASE_URL).await;
+            let result = default_client().list_models().await;
             assert!(result.is_err());
         }
     }
 
     #[tokio::test]
     async fn test_list_models_when_ollama_running() {
-        let connected = check_connection(DEFAULT_OLLAMA_BASE_URL)
-            .await
-            .unwrap_or(false);
+        let connected = default_client().check_connection().await.unwrap_or(false);
         if !connected {
             eprintln!("Skipping test: Ollama not running");
             return;
         }
 
-        let models = list_models(DEFAULT_OLLAMA_BASE_URL).await.unwrap();
+        let models = default_client().list_models().await.unwrap();
         assert!(!models.is_empty());
     }
 
     #[tokio::test]
     async fn test_create_embedding() {
-        let connected = check_connection(DEFAULT_OLLAMA_BASE_URL)
-            .await
-            .unwrap_or(false);
+        let connected = default_client().check_connection().await.unwrap_or(false);
         if !connected {
             eprintln!("Skipping test: Ollama not running");
             return;
         }
 
         let text = "Docker container networking issue";
-        let embedding = create_embedding(text, "nomic-embed-text", DEFAULT_OLLAMA_BASE_URL)
+        let embedding = default_client()
+            .create_embedding(text, "nomic-embed-text")
             .await
             .unwrap();
 
@@ -199,38 +811,27 @@ mod tests {
 
     #[tokio::test]
     async fn test_similar_texts_have_higher_similarity() {
-        let connected = check_connection(DEFAULT_OLLAMA_BASE_URL)
-            .await
-            .unwrap_or(false);
+        let connected = default_client().check_connection().await.unwrap_or(false);
         if !connected {
             eprintln!("Skipping test: Ollama not running");
             return;
         }
 
-        let emb1 = create_embedding(
-            "How to fix Docker network issue",
-            "nomic-embed-text",
-            DEFAULT_OLLAMA_BASE_URL,
-        )
-        .await
-        .unwrap();
-        let emb2 = create_embedding(
-            "Docker container networking problem",
-            "nomic-embed-text",
-            DEFAULT_OLLAMA_BASE_URL,
-        )
-        .await
-        .unwrap();
-        let emb3 = create_embedding(
-            "Best pizza recipes for dinner",
-            "nomic-embed-text",
-            DEFAULT_OLLAMA_BASE_URL,
-        )
-        .await
-        .unwrap();
+        let emb1 = default_client()
+            .create_embedding("How to fix Docker network issue", "nomic-embed-text")
+            .await
+            .unwrap();
+        let emb2 = default_client()
+            .create_embedding("Docker container networking problem", "nomic-embed-text")
+            .await
+            .unwrap();
+        let emb3 = default_client()
+            .create_embedding("Best pizza recipes for dinner", "nomic-embed-text")
+            .await
+            .unwrap();
 
-        let sim_1_2 = cosine_similarity(&emb1, &emb2);
-        let sim_1_3 = cosine_similarity(&emb1, &emb3);
+        let sim_1_2 = crate::ai::vector::cosine(&emb1, &emb2);
+        let sim_1_3 = crate::ai::vector::cosine(&emb1, &emb3);
 
         // Similar texts should have higher cosine similarity
         assert!(
@@ -240,14 +841,4 @@ mod tests {
             sim_1_3
         );
     }
-
-    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-        let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-        let mag_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-        let mag_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
-        if mag_a == 0.0 || mag_b == 0.0 {
-            return 0.0;
-        }
-        dot / (mag_a * mag_b)
-    }
 }
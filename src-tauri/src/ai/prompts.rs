@@ -0,0 +1,370 @@
+use crate::models::Snippet;
+use crate::placeholders;
+
+pub const GENERATE_SOLUTION: &str = "generate_solution";
+pub const SUGGEST_TAGS: &str = "suggest_tags";
+pub const CHAT: &str = "chat";
+pub const GENERATE_TITLE: &str = "generate_title";
+pub const SUMMARIZE_SNIPPET: &str = "summarize_snippet";
+pub const DETECT_LANGUAGE: &str = "detect_language";
+
+/// Longest excerpt of each code file included in prompts
+const MAX_FILE_CHARS: usize = 1500;
+
+pub struct PromptVariable {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub required: bool,
+}
+
+/// A prompt used by an AI feature, with the variables it may reference
+pub struct PromptDefinition {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub variables: &'static [PromptVariable],
+    pub default_template: &'static str,
+}
+
+const SNIPPET_VARIABLES: &[PromptVariable] = &[
+    PromptVariable {
+        name: "snippet",
+        description: "Problem, solution and code of the snippet, formatted together",
+        required: false,
+    },
+    PromptVariable {
+        name: "title",
+        description: "Current title of the snippet",
+        required: false,
+    },
+    PromptVariable {
+        name: "problem",
+        description: "Problem description",
+        required: false,
+    },
+    PromptVariable {
+        name: "solution",
+        description: "Solution text (may be empty)",
+        required: false,
+    },
+    PromptVariable {
+        name: "code",
+        description: "Code files as fenced blocks (may be empty)",
+        required: false,
+    },
+];
+
+pub const PROMPTS: &[PromptDefinition] = &[
+    PromptDefinition {
+        key: GENERATE_SOLUTION,
+        name: "Generate solution",
+        description: "Drafts a solution for a problem description",
+        variables: &[PromptVariable {
+            name: "problem",
+            description: "Problem description entered by the user",
+            required: true,
+        }],
+        default_template: r#"You are an AI assistant helping developers solve programming problems.
+Given the following development problem, provide a clear and practical solution in markdown format.
+Be concise and focus on actionable steps.
+
+Problem:
+{{problem}}
+
+Solution:"#,
+    },
+    PromptDefinition {
+        key: SUGGEST_TAGS,
+        name: "Suggest tags",
+        description: "Suggests tags for snippet content; the answer is forced into JSON",
+        variables: &[
+            PromptVariable {
+                name: "content",
+                description: "Title, problem, solution and code of the snippet",
+                required: true,
+            },
+            PromptVariable {
+                name: "existing_tags",
+                description: "Comma-separated names of the tags already in use",
+                required: false,
+            },
+        ],
+        default_template: r#"Analyze the following development-related content and suggest 3-5 relevant tags.
+Tags should include technology stacks, categories, and key concepts.
+Prefer tags from the existing vocabulary and reuse their exact spelling;
+only propose a new tag when no existing tag fits.
+
+Existing tags:
+{{existing_tags}}
+
+Content:
+{{content}}
+
+Respond ONLY with a JSON object of the form {"existing": [...], "new": [...]}
+where "existing" lists tags from the vocabulary and "new" lists new tags."#,
+    },
+    PromptDefinition {
+        key: CHAT,
+        name: "Chat",
+        description: "Answers questions from the snippets retrieved for them",
+        variables: &[
+            PromptVariable {
+                name: "question",
+                description: "The user's message",
+                required: true,
+            },
+            PromptVariable {
+                name: "context",
+                description: "Retrieved snippets, numbered [1], [2], ... for citations",
+                required: true,
+            },
+            PromptVariable {
+                name: "current_snippet",
+                description: "The snippet the user is viewing, if any",
+                required: false,
+            },
+        ],
+        default_template: r#"You are a recall assistant for a developer's code snippet knowledge base.
+The user has previously recorded solutions to programming problems.
+Your job is to help them find and recall relevant information from their notes.
+
+Based on the following relevant snippets from their knowledge base, answer the user's question.
+If no relevant snippets are found, let the user know.
+Cite the snippets you draw from by their number in square brackets, e.g. [1] or [2][3],
+right after the statement they support. Do not cite snippets you did not use.
+
+## Relevant snippets from knowledge base:
+{{context}}
+{{current_snippet}}
+## User question:
+{{question}}
+
+## Your answer:"#,
+    },
+    PromptDefinition {
+        key: GENERATE_TITLE,
+        name: "Generate title",
+        description: "Suggests a concise title for a snippet",
+        variables: SNIPPET_VARIABLES,
+        default_template: r#"You write titles for entries in a developer's knowledge base of solved problems.
+Write one concise, specific title (at most 10 words) for the entry below.
+Name the technology and the problem. Respond with the title only.
+
+{{snippet}}
+Title:"#,
+    },
+    PromptDefinition {
+        key: SUMMARIZE_SNIPPET,
+        name: "Summarize snippet",
+        description: "Writes the one-paragraph summary stored with a snippet",
+        variables: SNIPPET_VARIABLES,
+        default_template: r#"Summarize the following entry from a developer's knowledge base in one short paragraph
+(2-3 sentences): what went wrong and how it was fixed. Respond with the summary only.
+
+Title: {{title}}
+{{snippet}}
+Summary:"#,
+    },
+    PromptDefinition {
+        key: DETECT_LANGUAGE,
+        name: "Detect language",
+        description: "Names the language of code the built-in detection cannot tell; only known languages are accepted",
+        variables: &[PromptVariable {
+            name: "code",
+            description: "Excerpt of the code as a fenced block",
+            required: true,
+        }],
+        default_template: r#"Identify the programming or configuration language of the following code.
+Answer with the language name only, nothing else.
+
+{{code}}"#,
+    },
+];
+
+pub fn definition(key: &str) -> Option<&'static PromptDefinition> {
+    PROMPTS.iter().find(|p| p.key == key)
+}
+
+/// Check that a template only uses the prompt's variables and uses all required ones
+pub fn validate(definition: &PromptDefinition, template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Prompt template cannot be empty".to_string());
+    }
+
+    let used = placeholders::names(template);
+    let unknown: Vec<&str> = used
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !definition.variables.iter().any(|v| v.name == *name))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown variable(s) for {}: {}",
+            definition.name,
+            unknown.join(", ")
+        ));
+    }
+
+    let missing: Vec<&str> = definition
+        .variables
+        .iter()
+        .filter(|v| v.required && !used.iter().any(|name| name == v.name))
+        .map(|v| v.name)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "{} prompt must use: {}",
+            definition.name,
+            missing
+                .iter()
+                .map(|name| format!("{{{{{name}}}}}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if used.is_empty() {
+        return Err(format!(
+            "{} prompt must use at least one variable",
+            definition.name
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fill `{{name}}` placeholders from `values`; unknown placeholders are left as is
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    placeholders::render(template, |name| {
+        values
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.clone())
+    })
+}
+
+/// Code files of a snippet as fenced blocks, each cut to `MAX_FILE_CHARS`
+fn format_code(snippet: &Snippet) -> String {
+    let mut code = String::new();
+    for file in &snippet.files {
+        let excerpt: String = file.content.chars().take(MAX_FILE_CHARS).collect();
+        let label = file.filename.as_deref().unwrap_or("Code");
+        code.push_str(&format!(
+            "\n{label}:\n```{}\n{excerpt}\n```\n",
+            file.language.as_deref().unwrap_or("")
+        ));
+    }
+    code
+}
+
+/// Values for the snippet variables of the title and summary prompts
+pub fn snippet_variables(snippet: &Snippet) -> Vec<(&'static str, String)> {
+    let solution = snippet
+        .solution
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_default();
+    let code = format_code(snippet);
+
+    let mut material = format!("Problem:\n{}\n", snippet.problem);
+    if !solution.is_empty() {
+        material.push_str(&format!("\nSolution:\n{solution}\n"));
+    }
+    material.push_str(&code);
+
+    vec![
+        ("snippet", material),
+        ("title", snippet.title.clone()),
+        ("problem", snippet.problem.clone()),
+        ("solution", solution),
+        ("code", code),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SnippetFile;
+
+    fn make_snippet() -> Snippet {
+        Snippet {
+            id: "s1".to_string(),
+            title: "fix".to_string(),
+            problem: "Container cannot resolve DNS".to_string(),
+            solution: Some("Set dns in daemon.json".to_string()),
+            summary: None,
+            code: Some("{\"dns\": [\"8.8.8.8\"]}".to_string()),
+            code_language: Some("json".to_string()),
+            reference_url: None,
+            tags: vec![],
+            files: vec![SnippetFile {
+                id: "f1".to_string(),
+                snippet_id: "s1".to_string(),
+                filename: Some("daemon.json".to_string()),
+                language: Some("json".to_string()),
+                content: "{\"dns\": [\"8.8.8.8\"]}".to_string(),
+                position: 0,
+            }],
+            created_at: "2026-02-09".to_string(),
+            updated_at: "2026-02-09".to_string(),
+            is_favorite: false,
+            is_deleted: false,
+            deleted_at: None,
+            last_accessed_at: None,
+            source_app: None,
+            captured_at: None,
+            status: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_default_templates_are_valid() {
+        for prompt in PROMPTS {
+            assert!(
+                validate(prompt, prompt.default_template).is_ok(),
+                "default {} prompt is invalid",
+                prompt.key
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_and_missing_variables() {
+        let chat = definition(CHAT).unwrap();
+
+        let err = validate(chat, "{{context}} {{question}} {{mood}}").unwrap_err();
+        assert!(err.contains("mood"));
+
+        let err = validate(chat, "Answer in Korean: {{question}}").unwrap_err();
+        assert!(err.contains("{{context}}"));
+
+        assert!(validate(chat, "Answer in Korean.\n{{context}}\n{{question}}").is_ok());
+        assert!(validate(chat, "  ").is_err());
+    }
+
+    #[test]
+    fn test_validate_requires_some_variable() {
+        let title = definition(GENERATE_TITLE).unwrap();
+
+        assert!(validate(title, "Write a title").is_err());
+        assert!(validate(title, "Title for: {{problem}}").is_ok());
+    }
+
+    #[test]
+    fn test_snippet_prompts_include_problem_solution_and_code() {
+        let values = snippet_variables(&make_snippet());
+
+        for key in [GENERATE_TITLE, SUMMARIZE_SNIPPET] {
+            let prompt = render(definition(key).unwrap().default_template, &values);
+            assert!(prompt.contains("Container cannot resolve DNS"));
+            assert!(prompt.contains("Set dns in daemon.json"));
+            assert!(prompt.contains("daemon.json:\n```json"));
+            assert!(!prompt.contains("{{"));
+        }
+    }
+
+    #[test]
+    fn test_render_leaves_unknown_placeholders() {
+        let rendered = render("{{problem}} {{other}}", &[("problem", "Crash".to_string())]);
+        assert_eq!(rendered, "Crash {{other}}");
+    }
+}
@@ -0,0 +1,102 @@
+use super::ollama::{GenerateOptions, OllamaClient};
+
+/// Longest title accepted from the LLM
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Strip a `Label:` prefix the model may echo back
+fn strip_label<'a>(text: &'a str, label: &str) -> &'a str {
+    match text.get(..label.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(label) => text[label.len()..].trim_start(),
+        _ => text,
+    }
+}
+
+/// First non-empty line of the response, without quotes, markdown or a trailing period
+pub fn clean_title(raw: &str) -> String {
+    let line = raw
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("");
+    let line = strip_label(line.trim_start_matches('#').trim(), "title:");
+    let line = line
+        .trim_matches(|c: char| c == '"' || c == '\'' || c == '*' || c == '`')
+        .trim()
+        .trim_end_matches('.');
+
+    if line.chars().count() <= MAX_TITLE_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(MAX_TITLE_CHARS).collect();
+    match cut.rfind(' ') {
+        Some(space) => cut[..space].to_string(),
+        None => cut,
+    }
+}
+
+/// The response collapsed to a single paragraph
+pub fn clean_summary(raw: &str) -> String {
+    let text = strip_label(raw.trim(), "summary:");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Run a rendered title prompt and clean up the answer
+pub async fn generate_title(
+    ollama: &OllamaClient,
+    prompt: &str,
+    model: &str,
+    options: &GenerateOptions,
+) -> Result<String, String> {
+    let response = ollama.generate(prompt, model, options).await?;
+    let title = clean_title(&response);
+    if title.is_empty() {
+        return Err("The model returned an empty title".to_string());
+    }
+    Ok(title)
+}
+
+/// Run a rendered summary prompt and clean up the answer
+pub async fn generate_summary(
+    ollama: &OllamaClient,
+    prompt: &str,
+    model: &str,
+    options: &GenerateOptions,
+) -> Result<String, String> {
+    let response = ollama.generate(prompt, model, options).await?;
+    let summary = clean_summary(&response);
+    if summary.is_empty() {
+        return Err("The model returned an empty summary".to_string());
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            clean_title("\n\"Fix Docker DNS resolution.\"\n"),
+            "Fix Docker DNS resolution"
+        );
+        assert_eq!(clean_title("Title: **Docker DNS**"), "Docker DNS");
+        assert_eq!(clean_title("# Docker DNS\nExtra text"), "Docker DNS");
+    }
+
+    #[test]
+    fn test_clean_title_truncates_at_word_boundary() {
+        let long = "word ".repeat(30);
+        let title = clean_title(&long);
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+        assert!(title.ends_with("word"));
+    }
+
+    #[test]
+    fn test_clean_summary_single_paragraph() {
+        assert_eq!(
+            clean_summary("Summary: Containers could not resolve DNS.\n\nSetting dns fixed it.  "),
+            "Containers could not resolve DNS. Setting dns fixed it."
+        );
+    }
+}
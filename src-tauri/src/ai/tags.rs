@@ -0,0 +1,221 @@
+use serde::Deserialize;
+
+use crate::ai::vector;
+use crate::models::{Tag, TagSuggestions};
+
+/// Most new tags accepted from one suggestion
+pub const MAX_NEW_TAGS: usize = 5;
+
+/// Longest tag name accepted from the LLM
+const MAX_TAG_CHARS: usize = 40;
+
+/// JSON schema the LLM's answer must follow
+pub fn suggestion_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "existing": { "type": "array", "items": { "type": "string" } },
+            "new": { "type": "array", "items": { "type": "string" } }
+        },
+        "required": ["existing", "new"]
+    })
+}
+
+/// Tag names as they appear in the prompt's vocabulary list
+pub fn format_vocabulary(tags: &[Tag]) -> String {
+    if tags.is_empty() {
+        return "(no tags yet)".to_string();
+    }
+    tags.iter()
+        .map(|t| t.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The LLM's answer before it is checked against the tag table
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct RawTagSuggestions {
+    #[serde(default)]
+    pub existing: Vec<String>,
+    #[serde(default, rename = "new")]
+    pub new_tags: Vec<String>,
+}
+
+/// Parse a `{"existing": [...], "new": [...]}` answer, also when wrapped in other text
+pub fn parse_suggestions(response: &str) -> Option<RawTagSuggestions> {
+    if let Ok(parsed) = serde_json::from_str(response.trim()) {
+        return Some(parsed);
+    }
+    let start = response.find('{')?;
+    let end = response.rfind('}')?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&response[start..=end]).ok()
+}
+
+/// Comparison key for tag names: case, spaces, `-`, `_` and `.` are ignored
+fn tag_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Tidy a new tag name: trimmed, lowercase, inner whitespace turned into `-`
+fn clean_new_tag(name: &str) -> Option<String> {
+    let cleaned = name
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase();
+    (!cleaned.is_empty() && cleaned.chars().count() <= MAX_TAG_CHARS).then_some(cleaned)
+}
+
+/// Resolve the LLM's names against `vocabulary`. Any name matching an existing tag (in
+/// either list) is returned as that tag; other names become new tags.
+pub fn resolve_suggestions(raw: RawTagSuggestions, vocabulary: &[Tag]) -> TagSuggestions {
+    let mut existing: Vec<Tag> = vec![];
+    let mut new_tags: Vec<String> = vec![];
+
+    for name in raw.existing.iter().chain(raw.new_tags.iter()) {
+        let key = tag_key(name);
+        if key.is_empty() {
+            continue;
+        }
+        if let Some(tag) = vocabulary.iter().find(|t| tag_key(&t.name) == key) {
+            if !existing.iter().any(|t| t.id == tag.id) {
+                existing.push(tag.clone());
+            }
+            continue;
+        }
+        if new_tags.len() >= MAX_NEW_TAGS {
+            continue;
+        }
+        if let Some(cleaned) = clean_new_tag(name) {
+            if !new_tags.iter().any(|t| tag_key(t) == key) {
+                new_tags.push(cleaned);
+            }
+        }
+    }
+
+    TagSuggestions {
+        existing,
+        new_tags,
+        fallback: false,
+    }
+}
+
+/// Mean of the given vectors; vectors of a different length than the first are skipped
+pub fn centroid(vectors: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let dims = vectors.first()?.len();
+    let mut sum = vec![0.0f32; dims];
+    let mut count = 0;
+    for vector in vectors.iter().filter(|v| v.len() == dims) {
+        for (total, value) in sum.iter_mut().zip(vector) {
+            *total += value;
+        }
+        count += 1;
+    }
+    Some(sum.into_iter().map(|v| v / count as f32).collect())
+}
+
+/// Tags whose centroid is most similar to `query`, best first
+pub fn rank_by_similarity(
+    query: &[f32],
+    centroids: &[(Tag, Vec<f32>)],
+    min_score: f64,
+    limit: usize,
+) -> Vec<Tag> {
+    let mut scored: Vec<(&Tag, f64)> = centroids
+        .iter()
+        .map(|(tag, center)| (tag, vector::cosine(query, center)))
+        .filter(|(_, score)| *score >= min_score)
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(tag, _)| tag.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(id: &str, name: &str) -> Tag {
+        Tag {
+            id: id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_suggestions() {
+        let direct = parse_suggestions(r#"{"existing": ["rust"], "new": ["tokio"]}"#).unwrap();
+        assert_eq!(direct.existing, vec!["rust"]);
+        assert_eq!(direct.new_tags, vec!["tokio"]);
+
+        let wrapped =
+            parse_suggestions("Sure:\n{\"existing\": [], \"new\": [\"wasm\"]}\nDone").unwrap();
+        assert_eq!(wrapped.new_tags, vec!["wasm"]);
+
+        assert!(parse_suggestions(r#"["rust"]"#).is_none());
+    }
+
+    #[test]
+    fn test_resolve_matches_existing_tags_in_either_list() {
+        // Given
+        let vocabulary = vec![tag("t1", "rust"), tag("t2", "Node.js"), tag("t3", "ci-cd")];
+        let raw = RawTagSuggestions {
+            existing: vec!["Rust".to_string(), "kubernetes".to_string()],
+            new_tags: vec![
+                "nodejs".to_string(),
+                "CI CD".to_string(),
+                "Error Handling".to_string(),
+            ],
+        };
+
+        // When
+        let suggestions = resolve_suggestions(raw, &vocabulary);
+
+        // Then
+        let ids: Vec<&str> = suggestions.existing.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["t1", "t2", "t3"]);
+        assert_eq!(suggestions.new_tags, vec!["kubernetes", "error-handling"]);
+        assert!(!suggestions.fallback);
+    }
+
+    #[test]
+    fn test_resolve_dedupes_and_limits_new_tags() {
+        let raw = RawTagSuggestions {
+            existing: vec![],
+            new_tags: ["a", "A", "b", "c", "d", "e", "f", " "]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        };
+
+        let suggestions = resolve_suggestions(raw, &[]);
+
+        assert_eq!(suggestions.new_tags, vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn test_centroid_and_ranking() {
+        // Given
+        let docker = centroid(&[vec![1.0, 0.0], vec![0.8, 0.2]]).unwrap();
+        let react = centroid(&[vec![0.0, 1.0]]).unwrap();
+        let centroids = vec![(tag("t1", "docker"), docker), (tag("t2", "react"), react)];
+
+        // When
+        let ranked = rank_by_similarity(&[1.0, 0.1], &centroids, 0.5, 5);
+
+        // Then
+        let names: Vec<&str> = ranked.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["docker"]);
+    }
+}
@@ -0,0 +1,279 @@
+/// How embedding vectors are stored in the `embeddings.embedding` blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorEncoding {
+    /// 4 bytes per dimension, little-endian
+    F32,
+    /// 2 bytes per dimension (IEEE half precision), little-endian
+    F16,
+    /// A little-endian f32 scale followed by 1 signed byte per dimension
+    Int8,
+}
+
+impl VectorEncoding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VectorEncoding::F32 => "f32",
+            VectorEncoding::F16 => "f16",
+            VectorEncoding::Int8 => "int8",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "f32" => Some(VectorEncoding::F32),
+            "f16" => Some(VectorEncoding::F16),
+            "int8" => Some(VectorEncoding::Int8),
+            _ => None,
+        }
+    }
+}
+
+/// Euclidean length of a vector
+pub fn norm(vector: &[f32]) -> f64 {
+    vector
+        .iter()
+        .map(|x| (*x as f64) * (*x as f64))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Scale a vector to unit length; a zero vector is returned unchanged
+pub fn normalize(vector: &[f32]) -> Vec<f32> {
+    let length = norm(vector);
+    if length == 0.0 {
+        return vector.to_vec();
+    }
+    vector.iter().map(|x| (*x as f64 / length) as f32).collect()
+}
+
+/// Cosine similarity of two vectors of any length; 0 if either is zero or the
+/// dimensions differ
+pub fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    let (norm_a, norm_b) = (norm(a), norm(b));
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot(a, b) / (norm_a * norm_b)
+}
+
+/// Dot product; equals cosine similarity for unit vectors. Vectors of different
+/// dimensions score 0.
+pub fn dot(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (*x as f64) * (*y as f64))
+        .sum()
+}
+
+pub fn encode(vector: &[f32], encoding: VectorEncoding) -> Vec<u8> {
+    match encoding {
+        VectorEncoding::F32 => vector.iter().flat_map(|x| x.to_le_bytes()).collect(),
+        VectorEncoding::F16 => vector
+            .iter()
+            .flat_map(|x| f32_to_f16(*x).to_le_bytes())
+            .collect(),
+        VectorEncoding::Int8 => {
+            let max = vector.iter().fold(0.0f32, |max, x| max.max(x.abs()));
+            let scale = max / 127.0;
+            let mut bytes = Vec::with_capacity(4 + vector.len());
+            bytes.extend_from_slice(&scale.to_le_bytes());
+            for x in vector {
+                let quantized = if scale == 0.0 {
+                    0
+                } else {
+                    (x / scale).round().clamp(-127.0, 127.0) as i8
+                };
+                bytes.push(quantized as u8);
+            }
+            bytes
+        }
+    }
+}
+
+pub fn decode(blob: &[u8], encoding: VectorEncoding) -> Vec<f32> {
+    match encoding {
+        VectorEncoding::F32 => blob
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect(),
+        VectorEncoding::F16 => blob
+            .chunks_exact(2)
+            .map(|chunk| f16_to_f32(u16::from_le_bytes(chunk.try_into().unwrap())))
+            .collect(),
+        VectorEncoding::Int8 => {
+            let Some((scale, values)) = blob.split_first_chunk::<4>() else {
+                return vec![];
+            };
+            let scale = f32::from_le_bytes(*scale);
+            values.iter().map(|b| (*b as i8) as f32 * scale).collect()
+        }
+    }
+}
+
+/// Decode a stored blob given the encoding name from the database; unknown names
+/// are read as f32
+pub fn decode_stored(blob: &[u8], encoding: &str) -> Vec<f32> {
+    decode(
+        blob,
+        VectorEncoding::parse(encoding).unwrap_or(VectorEncoding::F32),
+    )
+}
+
+/// Round an f32 to the nearest IEEE 754 half-precision value
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        // Infinity stays infinity, NaN stays NaN
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        // Subnormal half: shift in the implicit leading bit
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        let rounded = (mantissa >> shift) + ((mantissa >> (shift - 1)) & 1);
+        return sign | rounded as u16;
+    }
+
+    let half = sign | ((exponent as u16) << 10) | (mantissa >> 13) as u16;
+    // A carry out of the mantissa correctly bumps the exponent
+    if mantissa & 0x1000 != 0 {
+        half + 1
+    } else {
+        half
+    }
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+
+    match exponent {
+        0 => {
+            let value = mantissa as f32 * 2f32.powi(-24);
+            if sign != 0 {
+                -value
+            } else {
+                value
+            }
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (mantissa << 13)),
+        _ => f32::from_bits(sign | ((exponent + 127 - 15) << 23) | (mantissa << 13)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_error(a: &[f32], b: &[f32]) -> f32 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0.0, f32::max)
+    }
+
+    fn sample() -> Vec<f32> {
+        normalize(
+            &(0..768)
+                .map(|i| ((i * 37 % 101) as f32 - 50.0) / 7.0)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_normalize_gives_unit_length() {
+        let unit = normalize(&[3.0, 4.0]);
+
+        assert_eq!(unit, vec![0.6, 0.8]);
+        assert!((norm(&unit) - 1.0).abs() < 1e-6);
+        assert_eq!(normalize(&[0.0, 0.0]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_dot_of_unit_vectors_is_cosine() {
+        let a = normalize(&[1.0, 0.0]);
+        let b = normalize(&[1.0, 1.0]);
+
+        assert!((dot(&a, &b) - std::f64::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(dot(&a, &[1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_cosine() {
+        assert!((cosine(&[1.0, 0.0, 0.0], &[2.0, 0.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine(&[1.0, 0.0], &[0.0, 1.0]).abs() < 1e-6);
+        assert!((cosine(&[1.0, 0.0], &[-1.0, 0.0]) + 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[], &[]), 0.0);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_f32_round_trip_is_exact() {
+        let vector = sample();
+
+        let blob = encode(&vector, VectorEncoding::F32);
+
+        assert_eq!(blob.len(), 768 * 4);
+        assert_eq!(decode(&blob, VectorEncoding::F32), vector);
+    }
+
+    #[test]
+    fn test_f16_round_trip_is_close() {
+        let vector = sample();
+
+        let blob = encode(&vector, VectorEncoding::F16);
+        let decoded = decode(&blob, VectorEncoding::F16);
+
+        assert_eq!(blob.len(), 768 * 2);
+        assert!(max_error(&vector, &decoded) < 1e-4);
+        assert!((dot(&vector, &decoded) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_f16_special_values() {
+        for value in [0.0f32, -0.0, 1.0, -2.5, 65504.0, 2f32.powi(-24)] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)), value);
+        }
+        assert_eq!(f16_to_f32(f32_to_f16(1.0e6)), f32::INFINITY);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+    }
+
+    #[test]
+    fn test_int8_round_trip_keeps_similarity() {
+        let vector = sample();
+
+        let blob = encode(&vector, VectorEncoding::Int8);
+        let decoded = decode(&blob, VectorEncoding::Int8);
+
+        assert_eq!(blob.len(), 4 + 768);
+        assert_eq!(decoded.len(), 768);
+        assert!((dot(&vector, &decoded) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_encoding_names() {
+        for encoding in [
+            VectorEncoding::F32,
+            VectorEncoding::F16,
+            VectorEncoding::Int8,
+        ] {
+            assert_eq!(VectorEncoding::parse(encoding.as_str()), Some(encoding));
+        }
+        assert_eq!(VectorEncoding::parse("f64"), None);
+    }
+}
@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    recallsnippet_lib::cli::run()
+}
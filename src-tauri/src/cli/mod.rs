@@ -0,0 +1,488 @@
+//! Headless front end over the same database and AI core as the desktop app, for
+//! capturing and recalling snippets from a terminal or a script.
+
+mod transfer;
+
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+
+use crate::ai::embedding::{self, BatchOptions};
+use crate::ai::ollama::OllamaHttp;
+use crate::clipboard;
+use crate::commands::ai::ai_chat_internal;
+use crate::commands::search::hybrid_search_internal;
+use crate::commands::snippet::{create_snippet_internal, ensure_not_duplicate};
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::mcp::{serve_stdio, McpServer};
+use crate::models::{
+    AiChatResponse, CreateSnippetInput, SearchHit, Snippet, SnippetFileInput, SnippetFilter,
+    SnippetStatus, SnippetSummary,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "recallsnippet-cli",
+    version,
+    about = "Capture and recall RecallSnippet snippets from the terminal"
+)]
+struct Cli {
+    /// Database file [default: the desktop app's database]
+    #[arg(long, global = true, env = "RECALLSNIPPET_DB")]
+    db: Option<PathBuf>,
+
+    /// Print JSON instead of human-readable text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Save a snippet; code comes from --file, --clipboard or stdin
+    Add(AddArgs),
+    /// Find snippets by meaning, then by keyword
+    Search {
+        query: String,
+        /// Maximum number of results [default: the search limit setting]
+        #[arg(long, short = 'n')]
+        limit: Option<usize>,
+        /// Skip the semantic search (no Ollama needed)
+        #[arg(long)]
+        keyword_only: bool,
+        /// Include drafts and archived snippets
+        #[arg(long)]
+        all: bool,
+    },
+    /// Print one snippet with all its files
+    Show { id: String },
+    /// List snippets, newest first
+    List {
+        /// Only snippets with this tag name
+        #[arg(long)]
+        tag: Option<String>,
+        #[arg(long)]
+        language: Option<String>,
+        #[arg(long)]
+        favorites: bool,
+        /// Only snippets with this status [default: active]
+        #[arg(long, value_parser = parse_status, conflicts_with = "all")]
+        status: Option<SnippetStatus>,
+        /// Snippets of every status
+        #[arg(long)]
+        all: bool,
+        /// List the trash instead
+        #[arg(long)]
+        trash: bool,
+    },
+    /// Ask a question answered from your snippets
+    Ask {
+        question: String,
+        /// Include drafts and archived snippets
+        #[arg(long)]
+        all: bool,
+    },
+    /// Write every snippet outside the trash as JSON
+    Export {
+        /// Output file [default: stdout]
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Load snippets from an export as drafts; existing ids are skipped
+    Import {
+        /// Export file, or `-` for stdin
+        path: PathBuf,
+        /// Keep the exported status instead of importing as drafts
+        #[arg(long)]
+        keep_status: bool,
+    },
+    /// Serve the knowledge base to AI agents over MCP on stdin/stdout
+    Mcp {
+        /// Refuse writes even when settings allow agents to add snippets
+        #[arg(long)]
+        read_only: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+struct AddArgs {
+    #[arg(long, short)]
+    title: String,
+    /// Problem description [default: the title]
+    #[arg(long, short)]
+    problem: Option<String>,
+    #[arg(long, short)]
+    solution: Option<String>,
+    #[arg(long, short)]
+    language: Option<String>,
+    /// Tag name, created if missing; repeat for several tags
+    #[arg(long = "tag", short = 'T')]
+    tags: Vec<String>,
+    #[arg(long)]
+    url: Option<String>,
+    /// Read the code from this file
+    #[arg(long, short, conflicts_with = "clipboard")]
+    file: Option<PathBuf>,
+    /// Read the code from the clipboard
+    #[arg(long)]
+    clipboard: bool,
+    /// Save even if similar snippets already exist
+    #[arg(long)]
+    allow_duplicate: bool,
+    /// Save as a draft to review later
+    #[arg(long)]
+    draft: bool,
+}
+
+fn parse_status(value: &str) -> Result<SnippetStatus, String> {
+    SnippetStatus::parse(value)
+        .ok_or_else(|| format!("expected draft, active or archived, got {value}"))
+}
+
+/// Entry point of the `recallsnippet-cli` binary
+pub fn run() -> ExitCode {
+    let cli = Cli::parse();
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: failed to start runtime: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match runtime.block_on(execute(cli)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn execute(cli: Cli) -> Result<(), String> {
+    let path = cli.db.unwrap_or_else(Database::default_path);
+    let db = Database::new(path).map_err(|e| AppError::Database(e).to_string())?;
+    let http = OllamaHttp::default();
+    let json = cli.json;
+
+    match cli.command {
+        Command::Add(args) => {
+            let snippet = add(&db, &http, args).await?;
+            print(json, &snippet, || {
+                format!("Saved {}  {}", snippet.id, snippet.title)
+            })
+        }
+        Command::Search {
+            query,
+            limit,
+            keyword_only,
+            all,
+        } => {
+            let search =
+                hybrid_search_internal(&db, &http, &query, limit, keyword_only, all).await?;
+            if let Some(e) = search.semantic_error {
+                eprintln!("warning: semantic search unavailable, showing keyword matches: {e}");
+            }
+            let hits = search.hits;
+            print(json, &hits, || {
+                hits.iter().map(hit_line).collect::<Vec<_>>().join("\n")
+            })
+        }
+        Command::Show { id } => {
+            let snippet = db.snippets().get(&id)?;
+            print(json, &snippet, || snippet.to_markdown())
+        }
+        Command::List {
+            tag,
+            language,
+            favorites,
+            status,
+            all,
+            trash,
+        } => {
+            let snippets = db.snippets().list(&SnippetFilter {
+                tag,
+                language,
+                favorites_only: Some(favorites),
+                trash_only: Some(trash),
+                status,
+                any_status: Some(all),
+                ..Default::default()
+            })?;
+            print(json, &snippets, || {
+                snippets
+                    .iter()
+                    .map(summary_line)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+        }
+        Command::Ask { question, all } => {
+            let response = ai_chat_internal(&db, &http, question, None, all).await?;
+            print(json, &response, || answer_text(&response))
+        }
+        Command::Export { output } => {
+            let export = transfer::export_snippets(&db)?;
+            let document = serde_json::to_string_pretty(&export).map_err(|e| e.to_string())?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, document)
+                        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+                    let count = export.snippets.len();
+                    eprintln!("Exported {count} snippets to {}", path.display());
+                }
+                None => println!("{document}"),
+            }
+            Ok(())
+        }
+        Command::Import { path, keep_status } => {
+            let text = read_input(&path)?;
+            let export: transfer::ExportFile =
+                serde_json::from_str(&text).map_err(|e| format!("Invalid export file: {e}"))?;
+            let report = transfer::import_snippets(&db, &export, keep_status)?;
+
+            // Best-effort, like saving in the app; a reindex picks up whatever fails here
+            let settings = db.settings().get_or_default();
+            let _ = embedding::embed_snippets(
+                &db,
+                &http.client(&settings),
+                &report.imported,
+                &settings.embedding_model,
+                BatchOptions::from_settings(&settings),
+                |_| {},
+            )
+            .await;
+
+            print(json, &report, || {
+                format!(
+                    "Imported {} snippets, skipped {} already present",
+                    report.imported.len(),
+                    report.skipped.len()
+                )
+            })
+        }
+        Command::Mcp { read_only } => {
+            let read_only = read_only || db.settings().get_or_default().mcp_read_only;
+            let server = McpServer::new(&db, &http, read_only);
+            serve_stdio(&server).await.map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Print `value` as JSON, or the text rendering when `json` is off
+fn print<T: Serialize>(json: bool, value: &T, text: impl FnOnce() -> String) -> Result<(), String> {
+    if json {
+        let rendered = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+        println!("{rendered}");
+    } else {
+        let rendered = text();
+        if !rendered.is_empty() {
+            println!("{rendered}");
+        }
+    }
+    Ok(())
+}
+
+fn read_input(path: &Path) -> Result<String, String> {
+    if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read stdin: {e}"))?;
+        return Ok(text);
+    }
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))
+}
+
+async fn add(db: &Database, http: &OllamaHttp, args: AddArgs) -> Result<Snippet, String> {
+    let code = if let Some(path) = &args.file {
+        Some(read_input(path)?)
+    } else if args.clipboard {
+        Some(clipboard::read_text().ok_or("The clipboard has no text")?)
+    } else if !std::io::stdin().is_terminal() {
+        Some(read_input(Path::new("-"))?).filter(|code| !code.trim().is_empty())
+    } else {
+        None
+    };
+
+    let filename = args
+        .file
+        .as_deref()
+        .and_then(Path::file_name)
+        .map(|name| name.to_string_lossy().into_owned());
+
+    // A named file keeps its filename so language detection can use the extension
+    let (code, files) = match (code, filename) {
+        (Some(content), Some(filename)) => (
+            None,
+            vec![SnippetFileInput {
+                filename: Some(filename),
+                language: args.language.clone(),
+                content,
+            }],
+        ),
+        (code, _) => (code, vec![]),
+    };
+
+    let mut input = CreateSnippetInput {
+        problem: args.problem.unwrap_or_else(|| args.title.clone()),
+        title: args.title,
+        solution: args.solution,
+        code,
+        code_language: args.language,
+        reference_url: args.url,
+        tag_ids: vec![],
+        files,
+        allow_duplicate: args.allow_duplicate,
+        source_app: None,
+        captured_at: None,
+        status: if args.draft {
+            SnippetStatus::Draft
+        } else {
+            SnippetStatus::Active
+        },
+    };
+
+    // Tags are only created once the snippet is known not to be refused
+    let draft_embedding = ensure_not_duplicate(db, http, &input).await?;
+    input.tag_ids = db
+        .tags()
+        .get_or_create_all(&args.tags)?
+        .into_iter()
+        .map(|tag| tag.id)
+        .collect();
+    create_snippet_internal(db, &Journal::default(), http, input, draft_embedding)
+        .await
+        .map_err(String::from)
+}
+
+fn summary_line(snippet: &SnippetSummary) -> String {
+    let mut line = format!("{}  {}", snippet.id, snippet.title);
+    if let Some(language) = &snippet.code_language {
+        line.push_str(&format!("  [{language}]"));
+    }
+    for tag in &snippet.tags {
+        line.push_str(&format!(" #{}", tag.name));
+    }
+    line
+}
+
+fn hit_line(hit: &SearchHit) -> String {
+    let score = match hit.score {
+        Some(score) => format!("{score:.2}"),
+        None => "  kw".to_string(),
+    };
+    format!("{score}  {}", summary_line(&hit.snippet))
+}
+
+fn answer_text(response: &AiChatResponse) -> String {
+    let mut text = response.answer.trim().to_string();
+    if !response.sources.is_empty() {
+        text.push_str("\n\nSources:");
+        for source in &response.sources {
+            text.push_str(&format!(
+                "\n  [{}] {}  {}",
+                source.citation, source.title, source.id
+            ));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{HitKind, Tag};
+
+    fn summary(id: &str) -> SnippetSummary {
+        SnippetSummary {
+            id: id.to_string(),
+            title: format!("Title {id}"),
+            problem: "p".to_string(),
+            summary: None,
+            code_language: Some("rust".to_string()),
+            code_preview: None,
+            tags: vec![Tag {
+                id: "t1".to_string(),
+                name: "async".to_string(),
+            }],
+            created_at: String::new(),
+            is_favorite: false,
+            is_deleted: false,
+            deleted_at: None,
+            last_accessed_at: None,
+            status: SnippetStatus::Active,
+        }
+    }
+
+    #[test]
+    fn test_parse_add_with_tags_and_global_flags() {
+        // When
+        let cli = Cli::try_parse_from([
+            "recallsnippet-cli",
+            "add",
+            "--title",
+            "Prune docker",
+            "-T",
+            "docker",
+            "--tag",
+            "ops",
+            "--file",
+            "prune.sh",
+            "--json",
+        ])
+        .unwrap();
+
+        // Then
+        assert!(cli.json);
+        let Command::Add(args) = cli.command else {
+            panic!("expected add");
+        };
+        assert_eq!(args.tags, vec!["docker", "ops"]);
+        assert_eq!(args.file, Some(PathBuf::from("prune.sh")));
+    }
+
+    #[test]
+    fn test_parse_rejects_file_with_clipboard() {
+        let result = Cli::try_parse_from([
+            "recallsnippet-cli",
+            "add",
+            "--title",
+            "x",
+            "--file",
+            "a.sh",
+            "--clipboard",
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_list_status() {
+        let cli = Cli::try_parse_from(["recallsnippet-cli", "list", "--status", "draft"]).unwrap();
+        let Command::List { status, .. } = cli.command else {
+            panic!("expected list");
+        };
+        assert_eq!(status, Some(SnippetStatus::Draft));
+
+        let result = Cli::try_parse_from(["recallsnippet-cli", "list", "--status", "done"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_text_lines() {
+        let hit = SearchHit {
+            snippet: summary("s1"),
+            score: Some(0.876),
+            kind: HitKind::Semantic,
+        };
+
+        assert_eq!(hit_line(&hit), "0.88  s1  Title s1  [rust] #async");
+    }
+}
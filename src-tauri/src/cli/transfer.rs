@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+
+use crate::commands::snippet_file::insert_file;
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::{Snippet, SnippetFileInput, SnippetFilter, SnippetStatus};
+
+/// Version of the export document; bumped on incompatible changes
+pub const EXPORT_VERSION: u32 = 1;
+
+/// A portable dump of the knowledge base, as written by `export` and read by `import`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportFile {
+    pub version: u32,
+    pub exported_at: String,
+    pub snippets: Vec<Snippet>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    /// Ids of the snippets that were inserted
+    pub imported: Vec<String>,
+    /// Ids that already existed and were left untouched
+    pub skipped: Vec<String>,
+}
+
+/// Every snippet outside the trash, whatever its status, with tags and files
+pub fn export_snippets(db: &Database) -> Result<ExportFile, AppError> {
+    let snippets = db
+        .snippets()
+        .list(&SnippetFilter {
+            any_status: Some(true),
+            ..Default::default()
+        })?
+        .into_iter()
+        .map(|summary| db.snippets().get(&summary.id))
+        .collect::<Result<_, _>>()?;
+
+    Ok(ExportFile {
+        version: EXPORT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        snippets,
+    })
+}
+
+/// Insert the exported snippets under their original ids. Snippets whose id is already
+/// present are skipped, so importing the same file twice is harmless. Tags are matched
+/// by name and created when missing. Imports land as drafts for triage unless
+/// `keep_status` is set.
+pub fn import_snippets(
+    db: &Database,
+    export: &ExportFile,
+    keep_status: bool,
+) -> Result<ImportReport, AppError> {
+    if export.version > EXPORT_VERSION {
+        return Err(AppError::Validation(format!(
+            "Export version {} is newer than this build supports ({EXPORT_VERSION})",
+            export.version
+        )));
+    }
+
+    let mut report = ImportReport::default();
+    for snippet in &export.snippets {
+        if db.snippets().get(&snippet.id).is_ok() {
+            report.skipped.push(snippet.id.clone());
+            continue;
+        }
+        let status = if keep_status {
+            snippet.status
+        } else {
+            SnippetStatus::Draft
+        };
+
+        let tag_ids = snippet
+            .tags
+            .iter()
+            .map(|tag| db.tags().get_or_create(&tag.name).map(|tag| tag.id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        db.with_transaction(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem, solution, summary, code_language,
+                                       reference_url, is_favorite, created_at, updated_at,
+                                       source_app, captured_at, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                rusqlite::params![
+                    snippet.id,
+                    snippet.title,
+                    snippet.problem,
+                    snippet.solution,
+                    snippet.summary,
+                    snippet.code_language,
+                    snippet.reference_url,
+                    snippet.is_favorite,
+                    snippet.created_at,
+                    snippet.updated_at,
+                    snippet.source_app,
+                    snippet.captured_at,
+                    status,
+                ],
+            )?;
+
+            let mut files: Vec<_> = snippet.files.iter().collect();
+            files.sort_by_key(|file| file.position);
+            for file in files {
+                let input = SnippetFileInput {
+                    filename: file.filename.clone(),
+                    language: file.language.clone(),
+                    content: file.content.clone(),
+                };
+                insert_file(conn, &snippet.id, &input)?;
+            }
+
+            for tag_id in &tag_ids {
+                conn.execute(
+                    "INSERT OR IGNORE INTO snippet_tags (snippet_id, tag_id) VALUES (?1, ?2)",
+                    rusqlite::params![snippet.id, tag_id],
+                )?;
+            }
+            Ok(())
+        })?;
+
+        report.imported.push(snippet.id.clone());
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem, solution, is_favorite)
+                     VALUES ('s1', 'Docker prune', 'Disk full', 'docker system prune', 1);
+                 INSERT INTO snippets (id, title, problem, is_deleted)
+                     VALUES ('s2', 'Trashed', 'p', 1);
+                 INSERT INTO snippets (id, title, problem, status)
+                     VALUES ('s3', 'Old notes', 'p', 'archived');
+                 INSERT INTO snippet_files (id, snippet_id, filename, language, content, position)
+                     VALUES ('f1', 's1', 'prune.sh', 'bash', 'docker system prune -af', 0),
+                            ('f2', 's1', NULL, 'yaml', 'services: {}', 1);
+                 INSERT INTO tags (id, name) VALUES ('t1', 'docker');
+                 INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('s1', 't1');",
+            )
+        })
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_export_skips_trash_and_includes_files_and_tags() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let export = export_snippets(&db).unwrap();
+
+        // Then
+        assert_eq!(export.version, EXPORT_VERSION);
+        let ids: Vec<_> = export.snippets.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"s3"));
+        let docker = export.snippets.iter().find(|s| s.id == "s1").unwrap();
+        assert_eq!(docker.files.len(), 2);
+        assert_eq!(docker.tags[0].name, "docker");
+    }
+
+    #[test]
+    fn test_import_round_trip_into_empty_database() {
+        // Given
+        let json = serde_json::to_string(&export_snippets(&setup_db()).unwrap()).unwrap();
+        let target = Database::new_in_memory().unwrap();
+
+        // When
+        let export: ExportFile = serde_json::from_str(&json).unwrap();
+        let report = import_snippets(&target, &export, true).unwrap();
+
+        // Then
+        assert_eq!(report.imported.len(), 2);
+        assert_eq!(
+            target.snippets().get("s3").unwrap().status,
+            SnippetStatus::Archived
+        );
+        let snippet = target.snippets().get("s1").unwrap();
+        assert_eq!(snippet.title, "Docker prune");
+        assert!(snippet.is_favorite);
+        assert_eq!(snippet.code.as_deref(), Some("docker system prune -af"));
+        let filenames: Vec<_> = snippet.files.iter().map(|f| f.filename.clone()).collect();
+        assert_eq!(filenames, vec![Some("prune.sh".to_string()), None]);
+        assert_eq!(snippet.tags[0].name, "docker");
+    }
+
+    #[test]
+    fn test_import_skips_existing_ids_and_reuses_tags() {
+        // Given
+        let db = setup_db();
+        let export = export_snippets(&db).unwrap();
+
+        // When
+        let report = import_snippets(&db, &export, false).unwrap();
+
+        // Then
+        assert!(report.imported.is_empty());
+        assert_eq!(report.skipped.len(), 2);
+        assert_eq!(db.tags().list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_import_lands_as_drafts_by_default() {
+        // Given
+        let export = export_snippets(&setup_db()).unwrap();
+        let target = Database::new_in_memory().unwrap();
+
+        // When
+        import_snippets(&target, &export, false).unwrap();
+
+        // Then
+        assert_eq!(
+            target.snippets().get("s1").unwrap().status,
+            SnippetStatus::Draft
+        );
+        assert_eq!(target.snippets().counts().unwrap().drafts, 2);
+    }
+
+    #[test]
+    fn test_import_rejects_newer_versions() {
+        let export = ExportFile {
+            version: EXPORT_VERSION + 1,
+            exported_at: String::new(),
+            snippets: vec![],
+        };
+
+        let result = import_snippets(&Database::new_in_memory().unwrap(), &export, false);
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+}
@@ -0,0 +1,5 @@
+/// Read the current text contents of the system clipboard, if any
+pub fn read_text() -> Option<String> {
+    let mut clipboard = arboard::Clipboard::new().ok()?;
+    clipboard.get_text().ok().filter(|text| !text.is_empty())
+}
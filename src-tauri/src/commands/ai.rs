@@ -1,90 +1,140 @@
 use serde::Deserialize;
 use tauri::State;
 
-use crate::ai::ollama;
+use crate::ai::ollama::{GenerateOptions, OllamaHttp};
+use crate::ai::tags::{self, RawTagSuggestions};
+use crate::ai::{citation, context, diagnostics, prompts};
+use crate::commands::prompt::render_prompt;
+use crate::db::repo::RankScope;
 use crate::db::Database;
-use crate::models::{AiChatResponse, Settings, SnippetSource, SnippetSummary, Tag};
-
-fn get_settings_internal(db: &Database) -> Settings {
-    db.with_connection(|conn| {
-        conn.query_row(
-            "SELECT theme, ollama_base_url, llm_model, embedding_model, search_limit, data_path
-             FROM settings WHERE id = 1",
-            [],
-            |row| {
-                Ok(Settings {
-                    theme: row.get(0)?,
-                    ollama_base_url: row.get(1)?,
-                    llm_model: row.get(2)?,
-                    embedding_model: row.get(3)?,
-                    search_limit: row.get(4)?,
-                    data_path: row.get(5)?,
-                })
-            },
-        )
-    })
-    .unwrap_or_default()
+use crate::errors::AppError;
+use crate::models::{AiChatResponse, AiFeature, OllamaDiagnostics, Tag, TagSuggestions};
+
+#[tauri::command]
+pub async fn check_ollama_connection(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+) -> Result<bool, String> {
+    let settings = db.settings().get_or_default();
+    http.client(&settings).check_connection().await
 }
 
+/// Step-by-step report of why Ollama is or is not reachable
 #[tauri::command]
-pub async fn check_ollama_connection(db: State<'_, Database>) -> Result<bool, String> {
-    let settings = get_settings_internal(&db);
-    ollama::check_connection(&settings.ollama_base_url).await
+pub async fn diagnose_ollama_connection(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+) -> Result<OllamaDiagnostics, String> {
+    let settings = db.settings().get_or_default();
+    Ok(diagnostics::diagnose(&http.client(&settings)).await)
 }
 
 #[tauri::command]
-pub async fn list_ollama_models(db: State<'_, Database>) -> Result<Vec<String>, String> {
-    let settings = get_settings_internal(&db);
-    ollama::list_models(&settings.ollama_base_url).await
+pub async fn list_ollama_models(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+) -> Result<Vec<String>, String> {
+    let settings = db.settings().get_or_default();
+    http.client(&settings).list_models().await
 }
 
 #[tauri::command]
 pub async fn generate_solution(
     db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
     problem: String,
     model: Option<String>,
 ) -> Result<String, String> {
-    let settings = get_settings_internal(&db);
-    let model = model.as_deref().unwrap_or(&settings.llm_model);
+    let settings = db.settings().get_or_default();
+    let model = model
+        .as_deref()
+        .unwrap_or(settings.model_for(AiFeature::Solution));
+    let options = GenerateOptions::from_settings(&settings);
 
-    let prompt = format!(
-        r#"You are an AI assistant helping developers solve programming problems.
-Given the following development problem, provide a clear and practical solution in markdown format.
-Be concise and focus on actionable steps.
+    let prompt = render_prompt(&db, prompts::GENERATE_SOLUTION, &[("problem", problem)]);
 
-Problem:
-{problem}
+    http.client(&settings).generate(&prompt, model, &options).await
+}
 
-Solution:"#
-    );
+/// Most tags listed in the suggestion prompt; the most used ones are kept
+const MAX_PROMPT_TAGS: usize = 200;
+
+/// Most tags returned by the embedding fallback
+const MAX_FALLBACK_TAGS: usize = 5;
+
+/// Minimum similarity for the embedding fallback to suggest a tag
+const FALLBACK_MIN_SCORE: f64 = 0.5;
 
-    ollama::generate(&prompt, model, &settings.ollama_base_url).await
+/// Mean embedding of the snippets carrying each tag, for snippets embedded with `model`
+fn fetch_tag_centroids(db: &Database, model: &str) -> Vec<(Tag, Vec<f32>)> {
+    db.embeddings()
+        .by_tag(model)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|group| tags::centroid(&group.vectors).map(|center| (group.tag, center)))
+        .collect()
 }
 
+/// Suggest tags for content. The LLM picks from the existing tag vocabulary and may
+/// propose new tags; when it is unavailable, existing tags are ranked by how close the
+/// content's embedding is to the snippets already carrying them.
 #[tauri::command]
 pub async fn suggest_tags(
     db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
     content: String,
     model: Option<String>,
-) -> Result<Vec<String>, String> {
-    let settings = get_settings_internal(&db);
-    let model = model.as_deref().unwrap_or(&settings.llm_model);
-
-    let prompt = format!(
-        r#"Analyze the following development-related content and suggest 3-5 relevant tags.
-Tags should include technology stacks, categories, and key concepts.
-Respond ONLY with a JSON array of strings. No explanation.
-
-Content:
-{content}
+) -> Result<TagSuggestions, String> {
+    suggest_tags_internal(&db, &http, content, model.as_deref()).await
+}
 
-Tags (JSON array only):"#
+pub(crate) async fn suggest_tags_internal(
+    db: &Database,
+    http: &OllamaHttp,
+    content: String,
+    model: Option<&str>,
+) -> Result<TagSuggestions, String> {
+    let settings = db.settings().get_or_default();
+    let model = model.unwrap_or(settings.model_for(AiFeature::Tags));
+    let options = GenerateOptions::from_settings(&settings);
+    let ollama = http.client(&settings);
+
+    let vocabulary = db.tags().by_usage().unwrap_or_default();
+    let listed = &vocabulary[..vocabulary.len().min(MAX_PROMPT_TAGS)];
+    let prompt = render_prompt(
+        db,
+        prompts::SUGGEST_TAGS,
+        &[
+            ("content", content.clone()),
+            ("existing_tags", tags::format_vocabulary(listed)),
+        ],
     );
 
-    let response = ollama::generate(&prompt, model, &settings.ollama_base_url).await?;
+    let llm_error = match ollama
+        .generate_json(&prompt, model, &options, &tags::suggestion_schema())
+        .await
+    {
+        Ok(response) => {
+            // Models that ignore the schema may still answer with a plain array
+            let raw = tags::parse_suggestions(&response).unwrap_or_else(|| RawTagSuggestions {
+                existing: vec![],
+                new_tags: parse_tags_from_response(&response).unwrap_or_default(),
+            });
+            return Ok(tags::resolve_suggestions(raw, &vocabulary));
+        }
+        Err(e) => e,
+    };
 
-    // Try to parse JSON array from response
-    parse_tags_from_response(&response)
+    let query = ollama
+        .create_embedding(&content, &settings.embedding_model)
+        .await
+        .map_err(|_| llm_error)?;
+    let centroids = fetch_tag_centroids(db, &settings.embedding_model);
+    Ok(TagSuggestions {
+        existing: tags::rank_by_similarity(&query, &centroids, FALLBACK_MIN_SCORE, MAX_FALLBACK_TAGS),
+        new_tags: vec![],
+        fallback: true,
+    })
 }
 
 #[derive(Debug, Deserialize)]
@@ -96,145 +146,83 @@ pub struct SnippetContext {
     pub code: Option<String>,
 }
 
-fn fetch_tags_for_snippet(db: &Database, snippet_id: &str) -> Vec<Tag> {
-    db.with_connection(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT t.id, t.name FROM tags t
-             INNER JOIN snippet_tags st ON st.tag_id = t.id
-             WHERE st.snippet_id = ?1",
-        )?;
-        let tags = stmt
-            .query_map([snippet_id], |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(tags)
-    })
-    .unwrap_or_default()
-}
-
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
-    if a.len() != b.len() || a.is_empty() {
-        return 0.0;
-    }
-    let dot: f64 = a.iter().zip(b.iter()).map(|(x, y)| (*x as f64) * (*y as f64)).sum();
-    let mag_a: f64 = a.iter().map(|x| (*x as f64) * (*x as f64)).sum::<f64>().sqrt();
-    let mag_b: f64 = b.iter().map(|x| (*x as f64) * (*x as f64)).sum::<f64>().sqrt();
-    if mag_a == 0.0 || mag_b == 0.0 {
-        return 0.0;
-    }
-    dot / (mag_a * mag_b)
-}
-
-fn decode_embedding(blob: &[u8]) -> Vec<f32> {
-    blob.chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
-        .collect()
-}
-
-fn search_similar_snippets(db: &Database, query_embedding: &[f32], limit: usize) -> Result<Vec<(SnippetSummary, f64)>, String> {
-    let rows: Vec<(String, Vec<u8>)> = db
-        .with_connection(|conn| {
-            let mut stmt = conn.prepare("SELECT snippet_id, embedding FROM embeddings")?;
-            let rows = stmt
-                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(rows)
-        })
-        .map_err(|e| format!("Failed to load embeddings: {}", e))?;
-
-    let mut scored: Vec<(String, f64)> = rows
-        .iter()
-        .map(|(id, blob)| {
-            let emb = decode_embedding(blob);
-            let score = cosine_similarity(query_embedding, &emb);
-            (id.clone(), score)
-        })
-        .collect();
-
-    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+/// Ids of the `limit` snippets most similar to the query that score at least `threshold`,
+/// ranked against vectors from `model` only
+fn search_similar_snippets(
+    db: &Database,
+    query_embedding: &[f32],
+    model: &str,
+    limit: usize,
+    threshold: f64,
+    include_inactive: bool,
+) -> Result<Vec<(String, f64)>, AppError> {
+    let scope = RankScope {
+        model: Some(model),
+        include_inactive,
+        ..Default::default()
+    };
+    let mut scored = db.embeddings().rank(query_embedding, scope)?;
+    scored.retain(|(_, score)| *score >= threshold);
     scored.truncate(limit);
 
-    let mut results = Vec::with_capacity(scored.len());
-    for (snippet_id, score) in scored {
-        let summary = db
-            .with_connection(|conn| {
-                conn.query_row(
-                    "SELECT id, title, problem, code_language, SUBSTR(code, 1, 200), created_at, is_favorite, is_deleted, deleted_at, last_accessed_at
-                     FROM snippets WHERE id = ?1",
-                    [&snippet_id],
-                    |row| {
-                        Ok(SnippetSummary {
-                            id: row.get(0)?,
-                            title: row.get(1)?,
-                            problem: row.get(2)?,
-                            code_language: row.get(3)?,
-                            code_preview: row.get(4)?,
-                            tags: vec![],
-                            created_at: row.get(5)?,
-                            is_favorite: row.get(6)?,
-                            is_deleted: row.get(7)?,
-                            deleted_at: row.get(8)?,
-                            last_accessed_at: row.get(9)?,
-                        })
-                    },
-                )
-            })
-            .map_err(|e| format!("Failed to fetch snippet: {}", e))?;
-
-        let tags = fetch_tags_for_snippet(db, &snippet_id);
-        let summary = SnippetSummary { tags, ..summary };
-        results.push((summary, score));
-    }
-
-    Ok(results)
+    Ok(scored)
 }
 
 #[tauri::command]
 pub async fn ai_chat(
     db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
     message: String,
     snippet_context: Option<SnippetContext>,
+    include_inactive: Option<bool>,
 ) -> Result<AiChatResponse, String> {
-    let settings = get_settings_internal(&db);
+    ai_chat_internal(
+        &db,
+        &http,
+        message,
+        snippet_context,
+        include_inactive.unwrap_or(false),
+    )
+    .await
+    .map_err(String::from)
+}
 
-    // Semantic search for relevant snippets
-    let query_embedding = ollama::create_embedding(&message, &settings.embedding_model, &settings.ollama_base_url).await?;
-    let similar = search_similar_snippets(&db, &query_embedding, 5)?;
-
-    // Collect sources with score >= 0.3
-    let sources: Vec<SnippetSource> = similar
-        .iter()
-        .filter(|(_, score)| *score >= 0.3)
-        .map(|(s, score)| SnippetSource {
-            id: s.id.clone(),
-            title: s.title.clone(),
-            score: *score,
-        })
-        .collect();
+/// Answer a question from the knowledge base; shared by the GUI and the CLI. Drafts
+/// and archived snippets are only used when `include_inactive` is set.
+pub(crate) async fn ai_chat_internal(
+    db: &Database,
+    http: &OllamaHttp,
+    message: String,
+    snippet_context: Option<SnippetContext>,
+    include_inactive: bool,
+) -> Result<AiChatResponse, AppError> {
+    let settings = db.settings().get_or_default();
+    let ollama = http.client(&settings);
 
-    // Build context from search results
-    let mut context_parts = Vec::new();
-    for (snippet, score) in &similar {
-        if *score < 0.3 {
-            continue;
-        }
-        context_parts.push(format!(
-            "### {} (Relevance: {:.0}%)\n**Problem:** {}\n{}",
-            snippet.title,
-            score * 100.0,
-            snippet.problem,
-            snippet.code_preview.as_deref().map(|c| format!("**Code preview:** ```\n{}\n```", c)).unwrap_or_default(),
-        ));
+    // Semantic search for relevant snippets
+    let query_embedding = ollama
+        .create_embedding(&message, &settings.embedding_model)
+        .await
+        .map_err(AppError::Ollama)?;
+    let similar = search_similar_snippets(
+        db,
+        &query_embedding,
+        &settings.embedding_model,
+        settings.chat_top_k.max(1) as usize,
+        settings.chat_relevance_threshold,
+        include_inactive,
+    )?;
+
+    let mut hits = Vec::with_capacity(similar.len());
+    for (id, score) in similar {
+        let snippet = db.snippets().get(&id)?;
+        hits.push((snippet, score));
     }
 
-    let snippets_context = if context_parts.is_empty() {
+    let snippets_context = if hits.is_empty() {
         "No relevant snippets found in the knowledge base.".to_string()
     } else {
-        context_parts.join("\n\n")
+        context::build_context(&hits, settings.chat_context_tokens.max(0) as usize)
     };
 
     // Build snippet-specific context if provided
@@ -249,25 +237,25 @@ pub async fn ai_chat(
         None => String::new(),
     };
 
-    let prompt = format!(
-        r#"You are a recall assistant for a developer's code snippet knowledge base.
-The user has previously recorded solutions to programming problems.
-Your job is to help them find and recall relevant information from their notes.
-
-Based on the following relevant snippets from their knowledge base, answer the user's question.
-If no relevant snippets are found, let the user know.
-Always reference which snippet(s) you're drawing from.
-
-## Relevant snippets from knowledge base:
-{snippets_context}
-{snippet_section}
-## User question:
-{message}
-
-## Your answer:"#
+    let prompt = render_prompt(
+        db,
+        prompts::CHAT,
+        &[
+            ("context", snippets_context),
+            ("current_snippet", snippet_section),
+            ("question", message),
+        ],
     );
 
-    let answer = ollama::generate(&prompt, &settings.llm_model, &settings.ollama_base_url).await?;
+    let answer = ollama
+        .generate(
+            &prompt,
+            settings.model_for(AiFeature::Chat),
+            &GenerateOptions::from_settings(&settings),
+        )
+        .await
+        .map_err(AppError::Ollama)?;
+    let sources = citation::cited_sources(&answer, &hits);
     Ok(AiChatResponse { answer, sources })
 }
 
@@ -329,4 +317,70 @@ mod tests {
         let tags = parse_tags_from_response(input).unwrap();
         assert!(tags.is_empty());
     }
+
+    fn encode(vector: &[f32]) -> Vec<u8> {
+        vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_tag_vocabulary_and_centroids() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO tags (id, name) VALUES ('t1', 'docker'), ('t2', 'react'), ('t3', 'unused');
+                 INSERT INTO snippets (id, title, problem) VALUES ('s1', 'a', 'p'), ('s2', 'b', 'p'), ('s3', 'c', 'p');
+                 INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('s1', 't1'), ('s2', 't1'), ('s3', 't2');",
+            )?;
+            for (id, vector, model) in [
+                ("s1", [1.0f32, 0.0], "nomic-embed-text"),
+                ("s2", [0.0, 1.0], "nomic-embed-text"),
+                ("s3", [1.0, 1.0], "other-model"),
+            ] {
+                conn.execute(
+                    "INSERT INTO embeddings (snippet_id, embedding, embedding_model) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![id, encode(&vector), model],
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        // When
+        let vocabulary = db.tags().by_usage().unwrap_or_default();
+        let centroids = fetch_tag_centroids(&db, "nomic-embed-text");
+
+        // Then
+        let names: Vec<&str> = vocabulary.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["docker", "react", "unused"]);
+        assert_eq!(centroids.len(), 1);
+        assert_eq!(centroids[0].0.name, "docker");
+        assert_eq!(centroids[0].1, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_similar_snippets_ignore_vectors_from_other_models() {
+        // Given - 's2' was embedded by a previous model with the same dimensions
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'a', 'p'), ('s2', 'b', 'p');",
+            )
+        })
+        .unwrap();
+        db.embeddings()
+            .save_one("s1", &[0.6, 0.8], "nomic-embed-text")
+            .unwrap();
+        db.embeddings()
+            .save_one("s2", &[1.0, 0.0], "old-model")
+            .unwrap();
+
+        // When
+        let similar =
+            search_similar_snippets(&db, &[1.0, 0.0], "nomic-embed-text", 5, 0.0, false).unwrap();
+
+        // Then
+        let ids: Vec<&str> = similar.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["s1"]);
+    }
 }
@@ -0,0 +1,448 @@
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+use tauri::State;
+
+use crate::ai::embedding::{self, BatchOptions};
+use crate::ai::ollama::OllamaHttp;
+use crate::db::journal::snapshot_snippets;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::language;
+use crate::models::BulkItemResult;
+
+enum BulkAction<'a> {
+    Delete,
+    Restore,
+    PermanentDelete,
+    SetFavorite(bool),
+    AddTags(&'a [String]),
+    RemoveTags(&'a [String]),
+    SetLanguage(Option<&'a str>),
+}
+
+impl BulkAction<'_> {
+    /// Human-readable description used in the undo history
+    fn label(&self, count: usize) -> String {
+        let verb = match self {
+            BulkAction::Delete => "Delete",
+            BulkAction::Restore => "Restore",
+            BulkAction::PermanentDelete => "Permanently delete",
+            BulkAction::SetFavorite(true) => "Favorite",
+            BulkAction::SetFavorite(false) => "Unfavorite",
+            BulkAction::AddTags(_) => "Add tags to",
+            BulkAction::RemoveTags(_) => "Remove tags from",
+            BulkAction::SetLanguage(_) => "Set language of",
+        };
+        format!("{verb} {count} snippet(s)")
+    }
+}
+
+/// Apply the action to a single snippet, returning whether anything changed
+fn apply_action(conn: &Connection, id: &str, action: &BulkAction) -> Result<bool, rusqlite::Error> {
+    let affected = match action {
+        BulkAction::Delete => conn.execute(
+            "UPDATE snippets SET is_deleted = 1, deleted_at = CURRENT_TIMESTAMP
+             WHERE id = ?1 AND is_deleted = 0",
+            [id],
+        )?,
+        BulkAction::Restore => conn.execute(
+            "UPDATE snippets SET is_deleted = 0, deleted_at = NULL WHERE id = ?1 AND is_deleted = 1",
+            [id],
+        )?,
+        BulkAction::PermanentDelete => conn.execute("DELETE FROM snippets WHERE id = ?1", [id])?,
+        BulkAction::SetFavorite(favorite) => conn.execute(
+            "UPDATE snippets SET is_favorite = ?2 WHERE id = ?1 AND is_favorite != ?2",
+            rusqlite::params![id, favorite],
+        )?,
+        BulkAction::AddTags(tag_ids) => {
+            let mut inserted = 0;
+            for tag_id in tag_ids.iter() {
+                inserted += conn.execute(
+                    "INSERT OR IGNORE INTO snippet_tags (snippet_id, tag_id) VALUES (?1, ?2)",
+                    rusqlite::params![id, tag_id],
+                )?;
+            }
+            inserted
+        }
+        BulkAction::RemoveTags(tag_ids) => {
+            let mut removed = 0;
+            for tag_id in tag_ids.iter() {
+                removed += conn.execute(
+                    "DELETE FROM snippet_tags WHERE snippet_id = ?1 AND tag_id = ?2",
+                    rusqlite::params![id, tag_id],
+                )?;
+            }
+            removed
+        }
+        BulkAction::SetLanguage(language) => conn.execute(
+            "UPDATE snippets SET code_language = ?2, updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1 AND code_language IS NOT ?2",
+            rusqlite::params![id, language],
+        )?,
+    };
+
+    Ok(affected > 0)
+}
+
+fn ensure_tags_exist(db: &Database, tag_ids: &[String]) -> Result<(), AppError> {
+    for tag_id in tag_ids {
+        let exists = db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM tags WHERE id = ?1)",
+                [tag_id],
+                |row| row.get::<_, bool>(0),
+            )
+        })?;
+        if !exists {
+            return Err(AppError::NotFound(format!(
+                "Tag with id '{tag_id}' not found"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Apply an action to every snippet in a single transaction.
+/// Missing snippets are reported per id; any database error rolls back the whole batch.
+fn run_bulk(
+    db: &Database,
+    ids: &[String],
+    action: &BulkAction,
+) -> Result<Vec<BulkItemResult>, AppError> {
+    if let BulkAction::AddTags(tag_ids) | BulkAction::RemoveTags(tag_ids) = action {
+        ensure_tags_exist(db, tag_ids)?;
+    }
+
+    db.with_transaction(|conn| {
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let exists: bool = conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM snippets WHERE id = ?1)",
+                [id],
+                |row| row.get(0),
+            )?;
+
+            if !exists {
+                results.push(BulkItemResult {
+                    id: id.clone(),
+                    success: false,
+                    changed: false,
+                    error: Some(format!("Snippet with id '{id}' not found")),
+                });
+                continue;
+            }
+
+            let changed = apply_action(conn, id, action)?;
+            results.push(BulkItemResult {
+                id: id.clone(),
+                success: true,
+                changed,
+                error: None,
+            });
+        }
+        Ok(results)
+    })
+    .map_err(AppError::from)
+}
+
+/// Snapshot the embedding text of each snippet so changes can be detected afterwards
+fn embedding_texts(db: &Database, ids: &[String]) -> HashMap<String, String> {
+    ids.iter()
+        .filter_map(|id| {
+            db.snippets()
+                .get(id)
+                .ok()
+                .map(|snippet| (id.clone(), embedding::prepare_text(&snippet)))
+        })
+        .collect()
+}
+
+/// Run a bulk action, re-embed only the snippets whose embedded content changed
+/// and record the whole batch as a single undoable operation
+async fn run_bulk_and_reembed(
+    db: &Database,
+    journal: &Journal,
+    http: &OllamaHttp,
+    ids: &[String],
+    action: BulkAction<'_>,
+) -> Result<Vec<BulkItemResult>, String> {
+    let snapshot = snapshot_snippets(db, ids).map_err(String::from)?;
+    let before = embedding_texts(db, ids);
+    let results = run_bulk(db, ids, &action).map_err(String::from)?;
+
+    if !matches!(action, BulkAction::PermanentDelete) {
+        let settings = db.settings().get_or_default();
+        let stale: Vec<String> = results
+            .iter()
+            .filter(|r| r.changed)
+            .filter(|r| {
+                db.snippets().get(&r.id).is_ok_and(|snippet| {
+                    before.get(&r.id) != Some(&embedding::prepare_text(&snippet))
+                })
+            })
+            .map(|r| r.id.clone())
+            .collect();
+        // Best-effort, like single-snippet updates
+        embedding::embed_snippets(
+            db,
+            &http.client(&settings),
+            &stale,
+            &settings.embedding_model,
+            BatchOptions::from_settings(&settings),
+            |_| {},
+        )
+        .await;
+    }
+
+    let changed = results.iter().filter(|r| r.changed).count();
+    journal.record_snippets(db, &action.label(changed), snapshot);
+
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn bulk_delete_snippets(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    ids: Vec<String>,
+) -> Result<Vec<BulkItemResult>, String> {
+    run_bulk_and_reembed(&db, &journal, &http, &ids, BulkAction::Delete).await
+}
+
+#[tauri::command]
+pub async fn bulk_restore_snippets(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    ids: Vec<String>,
+) -> Result<Vec<BulkItemResult>, String> {
+    run_bulk_and_reembed(&db, &journal, &http, &ids, BulkAction::Restore).await
+}
+
+#[tauri::command]
+pub async fn bulk_permanent_delete_snippets(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    ids: Vec<String>,
+) -> Result<Vec<BulkItemResult>, String> {
+    run_bulk_and_reembed(&db, &journal, &http, &ids, BulkAction::PermanentDelete).await
+}
+
+#[tauri::command]
+pub async fn bulk_set_favorite(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    ids: Vec<String>,
+    favorite: bool,
+) -> Result<Vec<BulkItemResult>, String> {
+    run_bulk_and_reembed(
+        &db,
+        &journal,
+        &http,
+        &ids,
+        BulkAction::SetFavorite(favorite),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn bulk_add_tags(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    ids: Vec<String>,
+    tag_ids: Vec<String>,
+) -> Result<Vec<BulkItemResult>, String> {
+    run_bulk_and_reembed(&db, &journal, &http, &ids, BulkAction::AddTags(&tag_ids)).await
+}
+
+#[tauri::command]
+pub async fn bulk_remove_tags(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    ids: Vec<String>,
+    tag_ids: Vec<String>,
+) -> Result<Vec<BulkItemResult>, String> {
+    run_bulk_and_reembed(&db, &journal, &http, &ids, BulkAction::RemoveTags(&tag_ids)).await
+}
+
+#[tauri::command]
+pub async fn bulk_set_language(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    ids: Vec<String>,
+    language: Option<String>,
+) -> Result<Vec<BulkItemResult>, String> {
+    let language = language.as_deref().and_then(language::canonicalize);
+    run_bulk_and_reembed(
+        &db,
+        &journal,
+        &http,
+        &ids,
+        BulkAction::SetLanguage(language.as_deref()),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_bulk, BulkAction};
+    use crate::db::Database;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute("INSERT INTO tags (id, name) VALUES ('tag-rust', 'rust')", [])?;
+            conn.execute("INSERT INTO tags (id, name) VALUES ('tag-cli', 'cli')", [])?;
+            for id in ["s1", "s2", "s3"] {
+                conn.execute(
+                    "INSERT INTO snippets (id, title, problem, code_language) VALUES (?1, ?1, 'problem', 'rust')",
+                    [id],
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_bulk_delete_and_restore() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let results = run_bulk(&db, &ids(&["s1", "s2"]), &BulkAction::Delete).unwrap();
+
+        // Then
+        assert!(results.iter().all(|r| r.success && r.changed));
+        assert!(db.snippets().get("s1").unwrap().is_deleted);
+        assert!(!db.snippets().get("s3").unwrap().is_deleted);
+
+        // Restoring only changes snippets that were actually trashed
+        let results = run_bulk(&db, &ids(&["s1", "s3"]), &BulkAction::Restore).unwrap();
+        assert!(results[0].changed);
+        assert!(!results[1].changed);
+        assert!(!db.snippets().get("s1").unwrap().is_deleted);
+    }
+
+    #[test]
+    fn test_bulk_reports_missing_ids() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let results = run_bulk(
+            &db,
+            &ids(&["s1", "missing"]),
+            &BulkAction::SetFavorite(true),
+        )
+        .unwrap();
+
+        // Then
+        assert!(results[0].success);
+        assert!(!results[1].success);
+        assert!(results[1].error.as_deref().unwrap().contains("missing"));
+        assert!(db.snippets().get("s1").unwrap().is_favorite);
+    }
+
+    #[test]
+    fn test_bulk_set_favorite_is_idempotent() {
+        // Given
+        let db = setup_db();
+        run_bulk(&db, &ids(&["s1"]), &BulkAction::SetFavorite(true)).unwrap();
+
+        // When
+        let results = run_bulk(&db, &ids(&["s1"]), &BulkAction::SetFavorite(true)).unwrap();
+
+        // Then
+        assert!(results[0].success);
+        assert!(!results[0].changed);
+    }
+
+    #[test]
+    fn test_bulk_add_and_remove_tags() {
+        // Given
+        let db = setup_db();
+        let tags = ids(&["tag-rust", "tag-cli"]);
+
+        // When
+        run_bulk(&db, &ids(&["s1", "s2"]), &BulkAction::AddTags(&tags)).unwrap();
+
+        // Then
+        assert_eq!(db.snippets().get("s1").unwrap().tags.len(), 2);
+        assert_eq!(db.snippets().get("s2").unwrap().tags.len(), 2);
+
+        let remove = ids(&["tag-cli"]);
+        let results = run_bulk(&db, &ids(&["s1", "s3"]), &BulkAction::RemoveTags(&remove)).unwrap();
+        assert!(results[0].changed);
+        assert!(!results[1].changed);
+        let tags = db.snippets().get("s1").unwrap().tags;
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "rust");
+    }
+
+    #[test]
+    fn test_bulk_add_unknown_tag_fails_without_changes() {
+        // Given
+        let db = setup_db();
+        let tags = ids(&["tag-rust", "tag-unknown"]);
+
+        // When
+        let result = run_bulk(&db, &ids(&["s1"]), &BulkAction::AddTags(&tags));
+
+        // Then
+        assert!(result.is_err());
+        assert!(db.snippets().get("s1").unwrap().tags.is_empty());
+    }
+
+    #[test]
+    fn test_bulk_set_language() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let results = run_bulk(
+            &db,
+            &ids(&["s1", "s2"]),
+            &BulkAction::SetLanguage(Some("python")),
+        )
+        .unwrap();
+
+        // Then
+        assert!(results.iter().all(|r| r.changed));
+        assert_eq!(
+            db.snippets().get("s1").unwrap().code_language.as_deref(),
+            Some("python")
+        );
+
+        // Clearing the language is also a change
+        let results = run_bulk(&db, &ids(&["s1"]), &BulkAction::SetLanguage(None)).unwrap();
+        assert!(results[0].changed);
+        assert!(db.snippets().get("s1").unwrap().code_language.is_none());
+    }
+
+    #[test]
+    fn test_bulk_permanent_delete() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let results = run_bulk(&db, &ids(&["s1", "s2"]), &BulkAction::PermanentDelete).unwrap();
+
+        // Then
+        assert!(results.iter().all(|r| r.success && r.changed));
+        assert!(db.snippets().get("s1").is_err());
+        assert!(db.snippets().get("s3").is_ok());
+    }
+}
@@ -0,0 +1,358 @@
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::active_window::{self, ActiveWindow};
+use crate::ai::language as ai_language;
+use crate::ai::ollama::{GenerateOptions, OllamaHttp};
+use crate::ai::{prompts, summary};
+use crate::clipboard;
+use crate::commands::ai::suggest_tags_internal;
+use crate::commands::duplicate::check_duplicates_internal;
+use crate::commands::prompt::render_prompt;
+use crate::commands::snippet::create_snippet_internal;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::models::{AiFeature, CapturedSnippet, CreateSnippetInput, SnippetStatus};
+
+/// Event emitted with the new snippet and its likely duplicates after a shortcut capture
+pub const CAPTURE_EVENT: &str = "snippet-captured";
+
+/// Event emitted with the error message when a shortcut capture fails
+pub const CAPTURE_FAILED_EVENT: &str = "snippet-capture-failed";
+
+/// Event emitted with the error message when the capture shortcut cannot be bound at launch
+pub const CAPTURE_SHORTCUT_FAILED_EVENT: &str = "capture-shortcut-failed";
+
+/// Title used when the captured text has no usable first line and the LLM is unavailable
+const FALLBACK_TITLE: &str = "Captured snippet";
+
+fn empty_clipboard() -> String {
+    AppError::Validation("The clipboard has no text".to_string()).into()
+}
+
+/// First line of the captured text, cleaned up like an LLM title
+fn fallback_title(text: &str) -> String {
+    let title = summary::clean_title(text);
+    if title.is_empty() {
+        FALLBACK_TITLE.to_string()
+    } else {
+        title
+    }
+}
+
+/// Problem text of a capture, naming where the text was copied from
+fn capture_problem(window: Option<&ActiveWindow>) -> String {
+    match window {
+        Some(ActiveWindow {
+            app,
+            title: Some(title),
+        }) => format!("Captured from \"{title}\" in {app}"),
+        Some(ActiveWindow { app, title: None }) => format!("Captured from {app}"),
+        None => "Captured from the clipboard".to_string(),
+    }
+}
+
+/// Ids of the tags suggested for the text, creating suggested new tags. Suggestions are
+/// best-effort: without Ollama the capture is saved untagged.
+async fn suggested_tag_ids(db: &Database, http: &OllamaHttp, text: &str) -> Vec<String> {
+    let Ok(suggestions) = suggest_tags_internal(db, http, text.to_string(), None).await else {
+        return vec![];
+    };
+    let mut ids: Vec<String> = suggestions.existing.into_iter().map(|t| t.id).collect();
+    if let Ok(created) = db.tags().get_or_create_all(&suggestions.new_tags) {
+        ids.extend(created.into_iter().map(|t| t.id));
+    }
+    ids
+}
+
+/// Save captured text as a draft snippet with a detected language, suggested tags and a
+/// generated title; the first line of the text is the title when the LLM is unavailable.
+/// Likely duplicates are reported along with the draft.
+pub(crate) async fn capture_internal(
+    db: &Database,
+    journal: &Journal,
+    http: &OllamaHttp,
+    text: String,
+    window: Option<ActiveWindow>,
+) -> Result<CapturedSnippet, String> {
+    if text.trim().is_empty() {
+        return Err(empty_clipboard());
+    }
+    let settings = db.settings().get_or_default();
+    let ollama = http.client(&settings);
+
+    let window = window.map(|window| ActiveWindow {
+        title: window.title.filter(|_| settings.capture_window_title),
+        ..window
+    });
+    let code_language = ai_language::resolve(db, &ollama, &settings, None, &text, None).await;
+    let mut input = CreateSnippetInput {
+        title: fallback_title(&text),
+        problem: capture_problem(window.as_ref()),
+        solution: None,
+        code: Some(text),
+        code_language,
+        reference_url: None,
+        tag_ids: vec![],
+        files: vec![],
+        allow_duplicate: false,
+        source_app: window.map(|window| window.app),
+        captured_at: Some(chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+        status: SnippetStatus::Draft,
+    };
+
+    let text = input.code.as_deref().unwrap_or_default();
+    input.tag_ids = suggested_tag_ids(db, http, text).await;
+
+    let prompt = render_prompt(
+        db,
+        prompts::GENERATE_TITLE,
+        &prompts::snippet_variables(&input.draft()),
+    );
+    if let Ok(title) = summary::generate_title(
+        &ollama,
+        &prompt,
+        settings.model_for(AiFeature::Summary),
+        &GenerateOptions::from_settings(&settings),
+    )
+    .await
+    {
+        input.title = title;
+    }
+
+    // A capture is never refused; the matches are shown with the new draft instead
+    let check = check_duplicates_internal(db, http, &input)
+        .await
+        .map_err(String::from)?;
+    let snippet = create_snippet_internal(db, journal, http, input, check.embedding).await?;
+    Ok(CapturedSnippet {
+        snippet,
+        duplicates: check.duplicates,
+    })
+}
+
+/// Capture the clipboard as a new snippet, like the global shortcut
+#[tauri::command]
+pub async fn capture_clipboard(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+) -> Result<CapturedSnippet, String> {
+    let text = clipboard::read_text().ok_or_else(empty_clipboard)?;
+    capture_internal(&db, &journal, &http, text, None).await
+}
+
+/// Capture in the background; the result is emitted as `CAPTURE_EVENT` or
+/// `CAPTURE_FAILED_EVENT`
+fn spawn_capture(app: AppHandle) {
+    // Read both right away, before the user moves on to another window
+    let window = active_window::current();
+    let text = clipboard::read_text();
+
+    tauri::async_runtime::spawn(async move {
+        let result = match text {
+            Some(text) => {
+                capture_internal(
+                    &app.state::<Database>(),
+                    &app.state::<Journal>(),
+                    &app.state::<OllamaHttp>(),
+                    text,
+                    window,
+                )
+                .await
+            }
+            None => Err(empty_clipboard()),
+        };
+        let _ = match result {
+            Ok(captured) => app.emit(CAPTURE_EVENT, captured),
+            Err(e) => app.emit(CAPTURE_FAILED_EVENT, e),
+        };
+    });
+}
+
+/// Reject shortcuts the global shortcut plugin cannot parse; empty disables capture
+pub(crate) fn validate_shortcut(shortcut: &str) -> Result<(), String> {
+    let shortcut = shortcut.trim();
+    if shortcut.is_empty() || shortcut.parse::<Shortcut>().is_ok() {
+        return Ok(());
+    }
+    Err(format!(
+        "Invalid shortcut: {shortcut} (expected e.g. CmdOrCtrl+Shift+Alt+S)"
+    ))
+}
+
+/// Bind the capture shortcut, replacing the previous binding; an empty shortcut only unbinds
+pub fn register_capture_shortcut(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let shortcuts = app.global_shortcut();
+    shortcuts.unregister_all().map_err(|e| e.to_string())?;
+
+    let shortcut = shortcut.trim();
+    if shortcut.is_empty() {
+        return Ok(());
+    }
+    shortcuts
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                spawn_capture(app.clone());
+            }
+        })
+        .map_err(|e| format!("Cannot register shortcut {shortcut}: {e}"))
+}
+
+/// Bind the shortcut from settings at launch; another app holding it is not fatal and is
+/// emitted as `CAPTURE_SHORTCUT_FAILED_EVENT`
+pub fn init_capture_shortcut(app: &AppHandle) {
+    let shortcut = app
+        .state::<Database>()
+        .settings()
+        .get_or_default()
+        .capture_shortcut;
+    if let Err(e) = register_capture_shortcut(app, &shortcut) {
+        let _ = app.emit(CAPTURE_SHORTCUT_FAILED_EVENT, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SnippetFilter;
+
+    const RUST_CODE: &str =
+        "fn main() {\n    let mut v: Vec<i32> = vec![];\n    println!(\"{:?}\", v);\n}";
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            // Nothing listens here, so Ollama calls fail fast
+            conn.execute_batch(
+                "UPDATE settings SET ollama_base_url = 'http://127.0.0.1:9', ollama_max_retries = 0;",
+            )
+        })
+        .unwrap();
+        db
+    }
+
+    fn editor_window() -> Option<ActiveWindow> {
+        Some(ActiveWindow {
+            app: "Code".to_string(),
+            title: Some("main.rs - demo".to_string()),
+        })
+    }
+
+    #[test]
+    fn test_fallback_title_uses_first_line() {
+        assert_eq!(
+            fallback_title("\n  # Restart nginx\nsudo ..."),
+            "Restart nginx"
+        );
+        assert_eq!(fallback_title("```"), FALLBACK_TITLE);
+    }
+
+    #[test]
+    fn test_capture_problem_names_the_source() {
+        assert_eq!(
+            capture_problem(editor_window().as_ref()),
+            "Captured from \"main.rs - demo\" in Code"
+        );
+        assert_eq!(
+            capture_problem(Some(&ActiveWindow {
+                app: "Terminal".to_string(),
+                title: None,
+            })),
+            "Captured from Terminal"
+        );
+        assert_eq!(capture_problem(None), "Captured from the clipboard");
+    }
+
+    #[test]
+    fn test_validate_shortcut() {
+        assert!(validate_shortcut("CmdOrCtrl+Shift+Alt+S").is_ok());
+        assert!(validate_shortcut("").is_ok());
+        assert!(validate_shortcut("Ctrl+Nope").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_capture_without_ollama_stores_source_and_detected_language() {
+        // Given
+        let (db, journal, http) = (setup_db(), Journal::default(), OllamaHttp::default());
+
+        // When
+        let snippet =
+            capture_internal(&db, &journal, &http, RUST_CODE.to_string(), editor_window())
+                .await
+                .unwrap()
+                .snippet;
+
+        // Then
+        assert_eq!(snippet.title, "fn main() {");
+        assert_eq!(snippet.problem, "Captured from \"main.rs - demo\" in Code");
+        assert_eq!(snippet.code.as_deref(), Some(RUST_CODE));
+        assert_eq!(snippet.code_language.as_deref(), Some("rust"));
+        assert_eq!(snippet.source_app.as_deref(), Some("Code"));
+        assert!(snippet.captured_at.is_some());
+        assert_eq!(snippet.status, SnippetStatus::Draft);
+        assert!(snippet.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_capture_respects_window_title_setting() {
+        // Given
+        let (db, journal, http) = (setup_db(), Journal::default(), OllamaHttp::default());
+        db.with_connection(|conn| {
+            conn.execute_batch("UPDATE settings SET capture_window_title = 0;")
+        })
+        .unwrap();
+
+        // When
+        let snippet =
+            capture_internal(&db, &journal, &http, RUST_CODE.to_string(), editor_window())
+                .await
+                .unwrap()
+                .snippet;
+
+        // Then
+        assert_eq!(snippet.problem, "Captured from Code");
+        assert_eq!(snippet.source_app.as_deref(), Some("Code"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_saves_duplicates_and_reports_them() {
+        // Given
+        let (db, journal, http) = (setup_db(), Journal::default(), OllamaHttp::default());
+        let first = capture_internal(&db, &journal, &http, RUST_CODE.to_string(), None)
+            .await
+            .unwrap();
+
+        // When
+        let second = capture_internal(&db, &journal, &http, RUST_CODE.to_string(), None)
+            .await
+            .unwrap();
+
+        // Then
+        assert!(first.duplicates.is_empty());
+        let duplicate_ids: Vec<&str> = second
+            .duplicates
+            .iter()
+            .map(|d| d.snippet.id.as_str())
+            .collect();
+        assert_eq!(duplicate_ids, vec![first.snippet.id.as_str()]);
+        let filter = SnippetFilter {
+            status: Some(SnippetStatus::Draft),
+            ..Default::default()
+        };
+        assert_eq!(db.snippets().list(&filter).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_capture_rejects_blank_text() {
+        // Given
+        let (db, journal, http) = (setup_db(), Journal::default(), OllamaHttp::default());
+
+        // When
+        let result = capture_internal(&db, &journal, &http, "  \n".to_string(), None).await;
+
+        // Then
+        assert!(result.unwrap_err().starts_with("Invalid input"));
+        assert!(db.snippets().list(&Default::default()).unwrap().is_empty());
+    }
+}
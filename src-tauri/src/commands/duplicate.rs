@@ -0,0 +1,638 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use tauri::State;
+use uuid::Uuid;
+
+use crate::ai::embedding;
+use crate::ai::ollama::OllamaHttp;
+use crate::ai::vector;
+use crate::commands::snippet::DraftEmbedding;
+use crate::commands::snippet_file::insert_file;
+use crate::db::journal::snapshot_snippets;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::models::{
+    CreateSnippetInput, DuplicateCluster, SearchResult, Snippet, SnippetFileInput,
+};
+
+/// Embedding similarity above which two snippets are considered duplicates
+pub(crate) const DEFAULT_DUPLICATE_THRESHOLD: f64 = 0.92;
+
+/// Trim each line, collapse inner whitespace and drop blank lines
+fn normalize_code(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Hash of a snippet's normalized file contents, `None` when it has no code
+fn code_hash<'a>(contents: impl IntoIterator<Item = &'a str>) -> Option<u64> {
+    let normalized: Vec<String> = contents
+        .into_iter()
+        .map(normalize_code)
+        .filter(|c| !c.is_empty())
+        .collect();
+    if normalized.is_empty() {
+        return None;
+    }
+
+    let mut hasher = DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+struct Candidate {
+    id: String,
+    embedding: Option<Vec<f32>>,
+    code_hash: Option<u64>,
+}
+
+/// Embeddings from the configured model and code hashes of every snippet outside the trash
+fn load_candidates(db: &Database) -> Result<Vec<Candidate>, AppError> {
+    let model = db.settings().get_or_default().embedding_model;
+    let mut embeddings = db.embeddings().outside_trash(&model)?;
+    let (ids, files) = db.with_connection(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT id FROM snippets WHERE is_deleted = 0 ORDER BY created_at, id")?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT f.snippet_id, f.content FROM snippet_files f
+             INNER JOIN snippets s ON s.id = f.snippet_id
+             WHERE s.is_deleted = 0
+             ORDER BY f.snippet_id, f.position, f.id",
+        )?;
+        let mut files: HashMap<String, Vec<String>> = HashMap::new();
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (snippet_id, content) = row?;
+            files.entry(snippet_id).or_default().push(content);
+        }
+        Ok((ids, files))
+    })?;
+
+    Ok(ids
+        .into_iter()
+        .map(|id| Candidate {
+            embedding: embeddings.remove(&id),
+            code_hash: files
+                .get(&id)
+                .and_then(|contents| code_hash(contents.iter().map(String::as_str))),
+            id,
+        })
+        .collect())
+}
+
+/// Similarity between two candidates, if high enough to count as duplicates. Embeddings
+/// are unit-length, so their dot product is the cosine similarity.
+fn match_score(
+    a_embedding: Option<&[f32]>,
+    a_hash: Option<u64>,
+    b: &Candidate,
+    threshold: f64,
+) -> Option<(f64, bool)> {
+    if a_hash.is_some() && a_hash == b.code_hash {
+        return Some((1.0, true));
+    }
+    let score = vector::dot(a_embedding?, b.embedding.as_deref()?);
+    (score >= threshold).then_some((score, false))
+}
+
+fn find_root(parents: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+        root = parents[root];
+    }
+    // Path compression
+    let mut node = i;
+    while parents[node] != root {
+        let next = parents[node];
+        parents[node] = root;
+        node = next;
+    }
+    root
+}
+
+/// Cluster snippets connected by a duplicate match; returns (member indices, similarity, same code)
+fn cluster_candidates(candidates: &[Candidate], threshold: f64) -> Vec<(Vec<usize>, f64, bool)> {
+    let mut parents: Vec<usize> = (0..candidates.len()).collect();
+    let mut edges = vec![];
+
+    for (i, a) in candidates.iter().enumerate() {
+        for (j, b) in candidates.iter().enumerate().skip(i + 1) {
+            if let Some((score, same_code)) =
+                match_score(a.embedding.as_deref(), a.code_hash, b, threshold)
+            {
+                let (root_a, root_b) = (find_root(&mut parents, i), find_root(&mut parents, j));
+                parents[root_b] = root_a;
+                edges.push((i, score, same_code));
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, (Vec<usize>, f64, bool)> = HashMap::new();
+    for i in 0..candidates.len() {
+        let root = find_root(&mut parents, i);
+        clusters
+            .entry(root)
+            .or_insert((vec![], 0.0, false))
+            .0
+            .push(i);
+    }
+    for (i, score, same_code) in edges {
+        let root = find_root(&mut parents, i);
+        if let Some(cluster) = clusters.get_mut(&root) {
+            cluster.1 = cluster.1.max(score);
+            cluster.2 |= same_code;
+        }
+    }
+
+    let mut result: Vec<_> = clusters
+        .into_values()
+        .filter(|(members, _, _)| members.len() > 1)
+        .collect();
+    for (members, _, _) in result.iter_mut() {
+        members.sort_unstable();
+    }
+    result.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.0.cmp(&b.0))
+    });
+    result
+}
+
+fn find_duplicates_internal(
+    db: &Database,
+    threshold: f64,
+) -> Result<Vec<DuplicateCluster>, String> {
+    let candidates = load_candidates(db).map_err(String::from)?;
+
+    cluster_candidates(&candidates, threshold)
+        .into_iter()
+        .map(|(members, similarity, same_code)| {
+            let snippets = members
+                .into_iter()
+                .map(|i| db.snippets().summary(&candidates[i].id))
+                .collect::<Result<Vec<_>, AppError>>()?;
+            Ok(DuplicateCluster {
+                snippets,
+                similarity,
+                same_code,
+            })
+        })
+        .collect()
+}
+
+/// Existing snippets that look like duplicates of a snippet about to be created
+fn draft_duplicates(
+    db: &Database,
+    draft_embedding: Option<&[f32]>,
+    draft_hash: Option<u64>,
+    threshold: f64,
+) -> Result<Vec<SearchResult>, AppError> {
+    let candidates = load_candidates(db)?;
+    let draft_embedding = draft_embedding.map(vector::normalize);
+
+    let mut matches: Vec<(String, f64)> = candidates
+        .iter()
+        .filter_map(|c| {
+            match_score(draft_embedding.as_deref(), draft_hash, c, threshold)
+                .map(|(score, _)| (c.id.clone(), score))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    matches
+        .into_iter()
+        .map(|(id, score)| {
+            Ok(SearchResult {
+                snippet: db.snippets().summary(&id)?,
+                score,
+            })
+        })
+        .collect()
+}
+
+/// Duplicates found for a snippet about to be created
+pub(crate) struct DraftCheck {
+    pub duplicates: Vec<SearchResult>,
+    /// The draft's embedding, for the new snippet to reuse
+    pub embedding: DraftEmbedding,
+}
+
+/// Check a snippet input against existing snippets. The embedding is best-effort:
+/// without Ollama only identical code is detected.
+pub(crate) async fn check_duplicates_internal(
+    db: &Database,
+    http: &OllamaHttp,
+    input: &CreateSnippetInput,
+) -> Result<DraftCheck, AppError> {
+    let draft = input.draft();
+    let contents = input
+        .code
+        .iter()
+        .map(String::as_str)
+        .chain(input.files.iter().map(|f| f.content.as_str()));
+    let draft_hash = code_hash(contents);
+
+    let settings = db.settings().get_or_default();
+    let text = embedding::prepare_text(&draft);
+    let draft_embedding = match http
+        .client(&settings)
+        .create_embedding(&text, &settings.embedding_model)
+        .await
+    {
+        Ok(vector) => DraftEmbedding::Ready { text, vector },
+        Err(_) => DraftEmbedding::Unavailable,
+    };
+
+    let duplicates = draft_duplicates(
+        db,
+        draft_embedding.vector(),
+        draft_hash,
+        DEFAULT_DUPLICATE_THRESHOLD,
+    )?;
+    Ok(DraftCheck {
+        duplicates,
+        embedding: draft_embedding,
+    })
+}
+
+/// Fold `source_ids` into `target_id`: fill the target's empty fields, append files it
+/// does not already have, union tags, link the target as superseding each source and
+/// move the sources to the trash
+fn merge_internal(db: &Database, target_id: &str, source_ids: &[String]) -> Result<(), AppError> {
+    if source_ids.is_empty() {
+        return Err(AppError::Validation(
+            "Select at least one snippet to merge".to_string(),
+        ));
+    }
+    if source_ids.iter().any(|id| id == target_id) {
+        return Err(AppError::Validation(
+            "Cannot merge a snippet into itself".to_string(),
+        ));
+    }
+
+    let target = db.snippets().get(target_id)?;
+    let sources = source_ids
+        .iter()
+        .map(|id| db.snippets().get(id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let first_set = |pick: fn(&Snippet) -> &Option<String>| {
+        pick(&target).clone().filter(|v| !v.is_empty()).or_else(|| {
+            sources
+                .iter()
+                .find_map(|s| pick(s).clone().filter(|v| !v.is_empty()))
+        })
+    };
+    let solution = first_set(|s| &s.solution);
+    let reference_url = first_set(|s| &s.reference_url);
+    let code_language = first_set(|s| &s.code_language);
+
+    let mut known_code: HashSet<String> = target
+        .files
+        .iter()
+        .map(|f| normalize_code(&f.content))
+        .collect();
+
+    db.with_transaction(|conn| {
+        conn.execute(
+            "UPDATE snippets SET solution = ?1, reference_url = ?2, code_language = ?3,
+                                 updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?4",
+            rusqlite::params![solution, reference_url, code_language, target_id],
+        )?;
+
+        for source in &sources {
+            for file in &source.files {
+                if !known_code.insert(normalize_code(&file.content)) {
+                    continue;
+                }
+                let input = SnippetFileInput {
+                    filename: file.filename.clone(),
+                    language: file.language.clone(),
+                    content: file.content.clone(),
+                };
+                insert_file(conn, target_id, &input)?;
+            }
+
+            conn.execute(
+                "INSERT OR IGNORE INTO snippet_tags (snippet_id, tag_id)
+                 SELECT ?1, tag_id FROM snippet_tags WHERE snippet_id = ?2",
+                [target_id, source.id.as_str()],
+            )?;
+            conn.execute(
+                "INSERT OR IGNORE INTO snippet_links (id, source_id, target_id, link_type)
+                 VALUES (?1, ?2, ?3, 'supersedes')",
+                rusqlite::params![Uuid::new_v4().to_string(), target_id, source.id],
+            )?;
+            conn.execute(
+                "UPDATE snippets SET is_deleted = 1, deleted_at = CURRENT_TIMESTAMP
+                 WHERE id = ?1 AND is_deleted = 0",
+                [&source.id],
+            )?;
+        }
+
+        Ok(())
+    })
+    .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn find_duplicates(
+    db: State<'_, Database>,
+    threshold: Option<f64>,
+) -> Result<Vec<DuplicateCluster>, String> {
+    find_duplicates_internal(&db, threshold.unwrap_or(DEFAULT_DUPLICATE_THRESHOLD))
+}
+
+#[tauri::command]
+pub async fn check_snippet_duplicates(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    input: CreateSnippetInput,
+) -> Result<Vec<SearchResult>, String> {
+    check_duplicates_internal(&db, &http, &input)
+        .await
+        .map(|check| check.duplicates)
+        .map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn merge_snippets(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    target_id: String,
+    source_ids: Vec<String>,
+) -> Result<Snippet, String> {
+    let mut ids = vec![target_id.clone()];
+    ids.extend(source_ids.iter().cloned());
+    let before = snapshot_snippets(&db, &ids).map_err(String::from)?;
+
+    merge_internal(&db, &target_id, &source_ids).map_err(String::from)?;
+
+    let snippet = db.snippets().get(&target_id).map_err(String::from)?;
+    let settings = db.settings().get_or_default();
+    let _ = embedding::embed_snippet(
+        &db,
+        &http.client(&settings),
+        &snippet,
+        &settings.embedding_model,
+    )
+    .await;
+
+    journal.record_snippets(&db, "Merge snippets", before);
+
+    Ok(snippet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute("INSERT INTO tags (id, name) VALUES ('t1', 'docker')", [])?;
+            conn.execute("INSERT INTO tags (id, name) VALUES ('t2', 'network')", [])?;
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    fn insert_snippet(db: &Database, id: &str, created_at: &str, code: Option<&str>) {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem, created_at) VALUES (?1, ?1, 'problem', ?2)",
+                [id, created_at],
+            )?;
+            if let Some(code) = code {
+                conn.execute(
+                    "INSERT INTO snippet_files (id, snippet_id, content, position) VALUES (?1 || '-f', ?1, ?2, 0)",
+                    [id, code],
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    fn embed(db: &Database, id: &str, vector: &[f32]) {
+        let model = db.settings().get_or_default().embedding_model;
+        db.embeddings().save_one(id, vector, &model).unwrap();
+    }
+
+    fn cluster_ids(cluster: &DuplicateCluster) -> Vec<&str> {
+        cluster.snippets.iter().map(|s| s.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_normalize_code_ignores_whitespace() {
+        assert_eq!(
+            normalize_code("  docker   ps\n\n\t-a  \n"),
+            normalize_code("docker ps\n-a")
+        );
+        assert_eq!(code_hash(["  \n "]), None);
+    }
+
+    #[test]
+    fn test_find_duplicates_clusters_by_embedding() {
+        // Given - a and b are near-identical, b and c too, d is unrelated; e matches a but
+        // was embedded by a previous model
+        let db = setup_db();
+        insert_snippet(&db, "a", "2026-01-01", None);
+        insert_snippet(&db, "b", "2026-01-02", None);
+        insert_snippet(&db, "c", "2026-01-03", None);
+        insert_snippet(&db, "d", "2026-01-04", None);
+        insert_snippet(&db, "e", "2026-01-05", None);
+        embed(&db, "a", &[1.0, 0.0, 0.0]);
+        embed(&db, "b", &[0.97, 0.2, 0.0]);
+        embed(&db, "c", &[0.9, 0.4, 0.0]);
+        embed(&db, "d", &[0.0, 0.0, 1.0]);
+        db.embeddings()
+            .save_one("e", &[1.0, 0.0, 0.0], "old-model")
+            .unwrap();
+
+        // When
+        let clusters = find_duplicates_internal(&db, 0.95).unwrap();
+
+        // Then - a, b and c are chained into one cluster, oldest first
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(cluster_ids(&clusters[0]), vec!["a", "b", "c"]);
+        assert!(!clusters[0].same_code);
+        assert!(clusters[0].similarity >= 0.95);
+    }
+
+    #[test]
+    fn test_find_duplicates_matches_normalized_code() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "a", "2026-01-01", Some("docker network prune"));
+        insert_snippet(&db, "b", "2026-01-02", Some("  docker  network prune\n"));
+        insert_snippet(&db, "c", "2026-01-03", Some("docker volume prune"));
+
+        // When
+        let clusters = find_duplicates_internal(&db, DEFAULT_DUPLICATE_THRESHOLD).unwrap();
+
+        // Then
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(cluster_ids(&clusters[0]), vec!["a", "b"]);
+        assert!(clusters[0].same_code);
+        assert_eq!(clusters[0].similarity, 1.0);
+    }
+
+    #[test]
+    fn test_find_duplicates_ignores_trash() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "a", "2026-01-01", Some("ls"));
+        insert_snippet(&db, "b", "2026-01-02", Some("ls"));
+        db.with_connection(|conn| {
+            conn.execute("UPDATE snippets SET is_deleted = 1 WHERE id = 'b'", [])?;
+            Ok(())
+        })
+        .unwrap();
+
+        // When
+        let clusters = find_duplicates_internal(&db, DEFAULT_DUPLICATE_THRESHOLD).unwrap();
+
+        // Then
+        assert!(clusters.is_empty());
+    }
+
+    #[test]
+    fn test_draft_duplicates_by_code_and_embedding() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "same-code", "2026-01-01", Some("kubectl get pods"));
+        insert_snippet(&db, "similar", "2026-01-02", None);
+        insert_snippet(&db, "other", "2026-01-03", None);
+        embed(&db, "similar", &[1.0, 0.0]);
+        embed(&db, "other", &[0.0, 1.0]);
+
+        // When
+        let hash = code_hash(["kubectl  get pods"]);
+        let matches = draft_duplicates(&db, Some(&[0.99, 0.05]), hash, 0.9).unwrap();
+
+        // Then - identical code ranks first
+        let ids: Vec<&str> = matches.iter().map(|m| m.snippet.id.as_str()).collect();
+        assert_eq!(ids, vec!["same-code", "similar"]);
+    }
+
+    #[tokio::test]
+    async fn test_check_duplicates_without_ollama_keeps_database_errors_apart() {
+        // Given
+        let db = setup_db();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "UPDATE settings SET ollama_base_url = 'http://127.0.0.1:9', ollama_max_retries = 0;",
+            )
+        })
+        .unwrap();
+        insert_snippet(&db, "same-code", "2026-01-01", Some("kubectl get pods"));
+        let input = CreateSnippetInput {
+            title: "Pods".to_string(),
+            problem: "List pods".to_string(),
+            solution: None,
+            code: Some("kubectl get pods".to_string()),
+            code_language: None,
+            reference_url: None,
+            tag_ids: vec![],
+            files: vec![],
+            allow_duplicate: false,
+            source_app: None,
+            captured_at: None,
+            status: Default::default(),
+        };
+
+        // When
+        let check = check_duplicates_internal(&db, &OllamaHttp::default(), &input)
+            .await
+            .unwrap();
+        db.with_connection(|conn| conn.execute_batch("DROP TABLE embeddings;"))
+            .unwrap();
+        let broken = check_duplicates_internal(&db, &OllamaHttp::default(), &input).await;
+
+        // Then
+        assert_eq!(check.duplicates.len(), 1);
+        assert!(matches!(check.embedding, DraftEmbedding::Unavailable));
+        assert!(matches!(broken, Err(AppError::Database(_))));
+    }
+
+    #[test]
+    fn test_merge_snippets() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "keep", "2026-01-01", Some("docker ps"));
+        insert_snippet(&db, "dup", "2026-01-02", Some("docker ps"));
+        db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE snippets SET solution = 'Restart the daemon', reference_url = 'https://docs.docker.com' WHERE id = 'dup'",
+                [],
+            )?;
+            conn.execute(
+                "INSERT INTO snippet_files (id, snippet_id, filename, content, position) VALUES ('dup-f2', 'dup', 'daemon.json', '{}', 1)",
+                [],
+            )?;
+            conn.execute("INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('keep', 't1')", [])?;
+            conn.execute("INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('dup', 't2')", [])?;
+            Ok(())
+        })
+        .unwrap();
+
+        // When
+        merge_internal(&db, "keep", &["dup".to_string()]).unwrap();
+
+        // Then - missing fields, new files and tags are folded in
+        let kept = db.snippets().get("keep").unwrap();
+        assert_eq!(kept.solution.as_deref(), Some("Restart the daemon"));
+        assert_eq!(
+            kept.reference_url.as_deref(),
+            Some("https://docs.docker.com")
+        );
+        let files: Vec<&str> = kept.files.iter().map(|f| f.content.as_str()).collect();
+        assert_eq!(files, vec!["docker ps", "{}"]);
+        let mut tags: Vec<&str> = kept.tags.iter().map(|t| t.name.as_str()).collect();
+        tags.sort();
+        assert_eq!(tags, vec!["docker", "network"]);
+
+        // The loser is trashed and superseded by the target
+        let dup = db.snippets().get("dup").unwrap();
+        assert!(dup.is_deleted);
+        let links: i32 = db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM snippet_links WHERE source_id = 'keep' AND target_id = 'dup' AND link_type = 'supersedes'",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap();
+        assert_eq!(links, 1);
+    }
+
+    #[test]
+    fn test_merge_rejects_self() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "a", "2026-01-01", None);
+
+        // When
+        let result = merge_internal(&db, "a", &["a".to_string()]);
+
+        // Then
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+}
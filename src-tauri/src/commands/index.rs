@@ -0,0 +1,123 @@
+use tauri::{AppHandle, Emitter, State};
+
+use crate::ai::embedding::{self, BatchOptions};
+use crate::ai::ollama::OllamaHttp;
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::{ReindexProgress, ReindexReport};
+
+/// Event emitted as batches of snippets are embedded
+pub const REINDEX_PROGRESS_EVENT: &str = "reindex-progress";
+
+/// Snippets to embed: all of them, or only those without a current embedding from `model`
+fn snippets_to_index(db: &Database, model: &str, all: bool) -> Result<Vec<String>, AppError> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT s.id FROM snippets s
+             LEFT JOIN embeddings e ON e.snippet_id = s.id
+             WHERE ?1 OR e.snippet_id IS NULL OR e.embedding_model != ?2 OR e.stale
+             ORDER BY s.created_at, s.id",
+        )?;
+        let ids = stmt
+            .query_map(rusqlite::params![all, model], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(ids)
+    })
+    .map_err(AppError::from)
+}
+
+/// Embed snippets in batches. By default only snippets that are missing an embedding, were
+/// embedded with another model or are marked stale are indexed; `all` re-embeds every snippet.
+#[tauri::command]
+pub async fn reindex_embeddings(
+    app: AppHandle,
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    all: Option<bool>,
+) -> Result<ReindexReport, String> {
+    let settings = db.settings().get_or_default();
+    let ids = snippets_to_index(&db, &settings.embedding_model, all.unwrap_or(false))
+        .map_err(String::from)?;
+    let total = ids.len();
+
+    let results = embedding::embed_snippets(
+        &db,
+        &http.client(&settings),
+        &ids,
+        &settings.embedding_model,
+        BatchOptions::from_settings(&settings),
+        |done| {
+            let _ = app.emit(REINDEX_PROGRESS_EVENT, ReindexProgress { done, total });
+        },
+    )
+    .await;
+
+    let (embedded, failed): (Vec<_>, Vec<_>) = results.into_iter().partition(|r| r.success);
+    Ok(ReindexReport {
+        total,
+        embedded: embedded.len(),
+        failed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem, created_at) VALUES
+                    ('fresh', 'A', 'a', '2026-01-01'),
+                    ('stale', 'B', 'b', '2026-01-02'),
+                    ('missing', 'C', 'c', '2026-01-03');
+                 INSERT INTO embeddings (snippet_id, embedding, embedding_model) VALUES
+                    ('fresh', x'0000803f', 'nomic-embed-text'),
+                    ('stale', x'0000803f', 'all-minilm');",
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_only_missing_and_stale_snippets_are_indexed() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let ids = snippets_to_index(&db, "nomic-embed-text", false).unwrap();
+
+        // Then
+        assert_eq!(ids, vec!["stale", "missing"]);
+    }
+
+    #[test]
+    fn test_all_snippets_are_indexed_on_request() {
+        let db = setup_db();
+
+        let ids = snippets_to_index(&db, "nomic-embed-text", true).unwrap();
+
+        assert_eq!(ids, vec!["fresh", "stale", "missing"]);
+    }
+
+    #[test]
+    fn test_embeddings_marked_stale_are_indexed_until_replaced() {
+        // Given
+        let db = setup_db();
+        db.embeddings().mark_all_stale().unwrap();
+
+        // When
+        let ids = snippets_to_index(&db, "nomic-embed-text", false).unwrap();
+        db.embeddings()
+            .save_one("fresh", &[1.0], "nomic-embed-text")
+            .unwrap();
+        let after_save = snippets_to_index(&db, "nomic-embed-text", false).unwrap();
+
+        // Then
+        assert_eq!(ids, vec!["fresh", "stale", "missing"]);
+        assert_eq!(after_save, vec!["stale", "missing"]);
+    }
+}
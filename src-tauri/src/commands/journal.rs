@@ -0,0 +1,25 @@
+use tauri::State;
+
+use crate::db::{Database, Journal};
+use crate::models::HistoryState;
+
+#[tauri::command]
+pub fn undo(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+) -> Result<Option<String>, String> {
+    journal.undo(&db).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn redo(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+) -> Result<Option<String>, String> {
+    journal.redo(&db).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn get_history_state(journal: State<'_, Journal>) -> HistoryState {
+    journal.state()
+}
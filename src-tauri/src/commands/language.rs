@@ -0,0 +1,38 @@
+use tauri::State;
+
+use crate::ai::language as ai_language;
+use crate::ai::ollama::OllamaHttp;
+use crate::db::Database;
+use crate::language::LANGUAGES;
+use crate::models::LanguageInfo;
+
+#[tauri::command]
+pub fn list_languages() -> Vec<LanguageInfo> {
+    LANGUAGES
+        .iter()
+        .map(|lang| LanguageInfo {
+            id: lang.id.to_string(),
+            name: lang.name.to_string(),
+            aliases: lang.aliases.iter().map(|a| a.to_string()).collect(),
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn detect_language(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    code: String,
+    filename: Option<String>,
+) -> Result<Option<String>, String> {
+    let settings = db.settings().get_or_default();
+    Ok(ai_language::resolve(
+        &db,
+        &http.client(&settings),
+        &settings,
+        None,
+        &code,
+        filename.as_deref(),
+    )
+    .await)
+}
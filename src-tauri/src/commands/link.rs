@@ -0,0 +1,383 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use tauri::State;
+use uuid::Uuid;
+
+use crate::db::journal::snapshot_snippets;
+use crate::db::repo::RankScope;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::models::{
+    CreateLinkInput, LinkType, LinkedSnippet, SearchResult, SnippetLink, SnippetLinks,
+};
+
+impl FromSql for LinkType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        LinkType::parse(text)
+            .ok_or_else(|| FromSqlError::Other(format!("Unknown link type: {text}").into()))
+    }
+}
+
+/// Number of related snippets returned when no limit is given
+const DEFAULT_RELATED_LIMIT: usize = 5;
+
+/// Nearest neighbours of a snippet's own embedding, excluding itself and trashed snippets
+fn related_snippets_internal(
+    db: &Database,
+    id: &str,
+    limit: usize,
+) -> Result<Vec<SearchResult>, String> {
+    db.snippets().get(id).map_err(String::from)?;
+
+    // Not embedded yet (e.g. Ollama was offline when it was saved)
+    let Some(own) = db.embeddings().get(id).map_err(String::from)? else {
+        return Ok(vec![]);
+    };
+
+    let mut scored = db
+        .embeddings()
+        .rank(
+            &own.vector,
+            RankScope {
+                model: Some(&own.model),
+                exclude: Some(id),
+                ..Default::default()
+            },
+        )
+        .map_err(String::from)?;
+    scored.truncate(limit);
+
+    scored
+        .into_iter()
+        .map(|(other_id, score)| {
+            Ok(SearchResult {
+                snippet: db.snippets().summary(&other_id)?,
+                score,
+            })
+        })
+        .collect::<Result<_, AppError>>()
+        .map_err(String::from)
+}
+
+fn fetch_link_by_id(db: &Database, id: &str) -> Result<SnippetLink, AppError> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            "SELECT id, source_id, target_id, link_type, created_at FROM snippet_links WHERE id = ?1",
+            [id],
+            |row| {
+                Ok(SnippetLink {
+                    id: row.get(0)?,
+                    source_id: row.get(1)?,
+                    target_id: row.get(2)?,
+                    link_type: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            },
+        )
+    })
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            AppError::NotFound(format!("Link with id '{id}' not found"))
+        }
+        other => AppError::Database(other),
+    })
+}
+
+fn create_link_internal(db: &Database, input: &CreateLinkInput) -> Result<SnippetLink, AppError> {
+    if input.source_id == input.target_id {
+        return Err(AppError::Validation(
+            "A snippet cannot link to itself".to_string(),
+        ));
+    }
+    db.snippets().get(&input.source_id)?;
+    db.snippets().get(&input.target_id)?;
+
+    let id = Uuid::new_v4().to_string();
+    let inserted = db.with_connection(|conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO snippet_links (id, source_id, target_id, link_type)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![
+                id,
+                input.source_id,
+                input.target_id,
+                input.link_type.as_str()
+            ],
+        )
+    })?;
+    if inserted == 0 {
+        return Err(AppError::Validation(format!(
+            "These snippets are already linked as '{}'",
+            input.link_type.as_str()
+        )));
+    }
+
+    fetch_link_by_id(db, &id)
+}
+
+/// Links from (`outgoing`) or to (`incoming`) a snippet, skipping trashed snippets
+fn linked_snippets(
+    db: &Database,
+    snippet_id: &str,
+    outgoing: bool,
+) -> Result<Vec<LinkedSnippet>, String> {
+    let (this_end, other_end) = if outgoing {
+        ("source_id", "target_id")
+    } else {
+        ("target_id", "source_id")
+    };
+
+    let rows: Vec<(String, LinkType, String)> = db
+        .with_connection(|conn| {
+            let mut stmt = conn.prepare(&format!(
+                "SELECT l.id, l.link_type, l.{other_end} FROM snippet_links l
+                 INNER JOIN snippets s ON s.id = l.{other_end}
+                 WHERE l.{this_end} = ?1 AND s.is_deleted = 0
+                 ORDER BY l.created_at, l.id"
+            ))?;
+            let rows = stmt
+                .query_map([snippet_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })
+        .map_err(|e| AppError::Database(e).to_string())?;
+
+    rows.into_iter()
+        .map(|(link_id, link_type, other_id)| {
+            Ok(LinkedSnippet {
+                link_id,
+                link_type,
+                snippet: db.snippets().summary(&other_id)?,
+            })
+        })
+        .collect::<Result<_, AppError>>()
+        .map_err(String::from)
+}
+
+fn snippet_links_internal(db: &Database, snippet_id: &str) -> Result<SnippetLinks, String> {
+    db.snippets().get(snippet_id).map_err(String::from)?;
+    Ok(SnippetLinks {
+        outgoing: linked_snippets(db, snippet_id, true)?,
+        incoming: linked_snippets(db, snippet_id, false)?,
+    })
+}
+
+#[tauri::command]
+pub fn get_related_snippets(
+    db: State<'_, Database>,
+    id: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchResult>, String> {
+    related_snippets_internal(&db, &id, limit.unwrap_or(DEFAULT_RELATED_LIMIT))
+}
+
+#[tauri::command]
+pub fn get_snippet_links(
+    db: State<'_, Database>,
+    snippet_id: String,
+) -> Result<SnippetLinks, String> {
+    snippet_links_internal(&db, &snippet_id)
+}
+
+#[tauri::command]
+pub fn create_snippet_link(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    input: CreateLinkInput,
+) -> Result<SnippetLink, String> {
+    let before =
+        snapshot_snippets(&db, std::slice::from_ref(&input.source_id)).map_err(String::from)?;
+    let link = create_link_internal(&db, &input).map_err(String::from)?;
+
+    journal.record_snippets(&db, "Link snippets", before);
+
+    Ok(link)
+}
+
+#[tauri::command]
+pub fn delete_snippet_link(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    id: String,
+) -> Result<(), String> {
+    let link = fetch_link_by_id(&db, &id).map_err(String::from)?;
+    let before = snapshot_snippets(&db, &[link.source_id]).map_err(String::from)?;
+
+    db.with_connection(|conn| {
+        conn.execute("DELETE FROM snippet_links WHERE id = ?1", [&id])?;
+        Ok(())
+    })
+    .map_err(|e| AppError::Database(e).to_string())?;
+
+    journal.record_snippets(&db, "Unlink snippets", before);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            for (id, title) in [("s1", "Docker"), ("s2", "Compose"), ("s3", "Pizza")] {
+                conn.execute(
+                    "INSERT INTO snippets (id, title, problem) VALUES (?1, ?2, 'problem')",
+                    [id, title],
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    fn embed(db: &Database, id: &str, vector: &[f32]) {
+        db.embeddings().save_one(id, vector, "test").unwrap();
+    }
+
+    fn trash(db: &Database, id: &str) {
+        db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE snippets SET is_deleted = 1, deleted_at = CURRENT_TIMESTAMP WHERE id = ?1",
+                [id],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    fn link(source: &str, target: &str, link_type: LinkType) -> CreateLinkInput {
+        CreateLinkInput {
+            source_id: source.to_string(),
+            target_id: target.to_string(),
+            link_type,
+        }
+    }
+
+    #[test]
+    fn test_related_snippets_ranked_without_self() {
+        // Given
+        let db = setup_db();
+        embed(&db, "s1", &[0.9, 0.1, 0.0]);
+        embed(&db, "s2", &[0.8, 0.2, 0.0]);
+        embed(&db, "s3", &[0.0, 0.1, 0.9]);
+
+        // When
+        let related = related_snippets_internal(&db, "s1", 5).unwrap();
+
+        // Then
+        let ids: Vec<&str> = related.iter().map(|r| r.snippet.id.as_str()).collect();
+        assert_eq!(ids, vec!["s2", "s3"]);
+        assert!(related[0].score > related[1].score);
+    }
+
+    #[test]
+    fn test_related_snippets_excludes_trash() {
+        // Given
+        let db = setup_db();
+        embed(&db, "s1", &[0.9, 0.1, 0.0]);
+        embed(&db, "s2", &[0.8, 0.2, 0.0]);
+        trash(&db, "s2");
+
+        // When
+        let related = related_snippets_internal(&db, "s1", 5).unwrap();
+
+        // Then
+        assert!(related.is_empty());
+    }
+
+    #[test]
+    fn test_related_snippets_without_embedding_is_empty() {
+        // Given
+        let db = setup_db();
+        embed(&db, "s2", &[0.8, 0.2, 0.0]);
+
+        // When
+        let related = related_snippets_internal(&db, "s1", 5).unwrap();
+
+        // Then
+        assert!(related.is_empty());
+    }
+
+    #[test]
+    fn test_create_link_and_backlinks() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let created = create_link_internal(&db, &link("s2", "s1", LinkType::Supersedes)).unwrap();
+
+        // Then
+        assert_eq!(created.link_type, LinkType::Supersedes);
+
+        let from_s2 = snippet_links_internal(&db, "s2").unwrap();
+        assert_eq!(from_s2.outgoing.len(), 1);
+        assert_eq!(from_s2.outgoing[0].snippet.id, "s1");
+        assert!(from_s2.incoming.is_empty());
+
+        let to_s1 = snippet_links_internal(&db, "s1").unwrap();
+        assert_eq!(to_s1.incoming.len(), 1);
+        assert_eq!(to_s1.incoming[0].snippet.id, "s2");
+        assert_eq!(to_s1.incoming[0].link_id, created.id);
+    }
+
+    #[test]
+    fn test_create_link_rejects_self_and_duplicates() {
+        // Given
+        let db = setup_db();
+        create_link_internal(&db, &link("s1", "s2", LinkType::SeeAlso)).unwrap();
+
+        // When
+        let duplicate = create_link_internal(&db, &link("s1", "s2", LinkType::SeeAlso));
+        let to_self = create_link_internal(&db, &link("s1", "s1", LinkType::SeeAlso));
+        let missing = create_link_internal(&db, &link("s1", "nope", LinkType::SeeAlso));
+
+        // Then
+        assert!(matches!(duplicate, Err(AppError::Validation(_))));
+        assert!(matches!(to_self, Err(AppError::Validation(_))));
+        assert!(matches!(missing, Err(AppError::NotFound(_))));
+
+        // A different link type between the same snippets is allowed
+        assert!(create_link_internal(&db, &link("s1", "s2", LinkType::CausedBy)).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_link_type_is_an_error() {
+        // Given - a row the CHECK constraint would normally refuse
+        let db = setup_db();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "PRAGMA ignore_check_constraints = ON;
+                 INSERT INTO snippet_links (id, source_id, target_id, link_type)
+                 VALUES ('l1', 's1', 's2', 'blocks');
+                 PRAGMA ignore_check_constraints = OFF;",
+            )
+        })
+        .unwrap();
+
+        // When
+        let fetched = fetch_link_by_id(&db, "l1");
+        let listed = snippet_links_internal(&db, "s1");
+
+        // Then
+        assert!(matches!(fetched, Err(AppError::Database(_))));
+        assert!(listed.is_err());
+    }
+
+    #[test]
+    fn test_links_to_trashed_snippets_are_hidden() {
+        // Given
+        let db = setup_db();
+        create_link_internal(&db, &link("s1", "s2", LinkType::SeeAlso)).unwrap();
+        trash(&db, "s2");
+
+        // When
+        let links = snippet_links_internal(&db, "s1").unwrap();
+
+        // Then
+        assert!(links.outgoing.is_empty());
+    }
+}
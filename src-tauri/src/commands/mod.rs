@@ -1,11 +1,41 @@
 pub mod ai;
+pub mod bulk;
+pub mod capture;
+pub mod duplicate;
+pub mod index;
+pub mod journal;
+pub mod language;
+pub mod link;
+pub mod model;
+pub mod prompt;
+pub mod reference;
 pub mod search;
+pub mod server;
 pub mod settings;
 pub mod snippet;
+pub mod snippet_file;
+pub mod summary;
 pub mod tag;
+pub mod template;
+pub mod trash;
 
 pub use ai::*;
+pub use bulk::*;
+pub use capture::*;
+pub use duplicate::*;
+pub use index::*;
+pub use journal::*;
+pub use language::*;
+pub use link::*;
+pub use model::*;
+pub use prompt::*;
+pub use reference::*;
 pub use search::*;
+pub use server::*;
 pub use settings::*;
 pub use snippet::*;
+pub use snippet_file::*;
+pub use summary::*;
 pub use tag::*;
+pub use template::*;
+pub use trash::*;
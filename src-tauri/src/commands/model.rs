@@ -0,0 +1,193 @@
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::ai::ollama::{OllamaClient, OllamaHttp};
+use crate::commands::settings::model_available;
+use crate::db::Database;
+use crate::models::{
+    AiFeature, ConfiguredModel, ModelDetails, ModelHealth, PullProgress, Settings,
+};
+
+/// Event emitted for every progress update while a model is pulled
+pub const PULL_PROGRESS_EVENT: &str = "ollama-pull-progress";
+
+/// Event emitted with the result of the startup model check
+pub const MODEL_HEALTH_EVENT: &str = "ollama-model-health";
+
+/// Every model named in settings, with the features that use it; the language model only
+/// counts while LLM language detection is on
+fn configured_models(settings: &Settings) -> Vec<ConfiguredModel> {
+    let language = (
+        settings.model_for(AiFeature::Language),
+        "language detection",
+    );
+    let uses = [
+        (settings.llm_model.as_str(), "default"),
+        (settings.model_for(AiFeature::Chat), "chat"),
+        (settings.model_for(AiFeature::Solution), "solutions"),
+        (settings.model_for(AiFeature::Tags), "tags"),
+        (settings.model_for(AiFeature::Summary), "summaries"),
+    ]
+    .into_iter()
+    .chain(settings.detect_language_with_llm.then_some(language))
+    .chain([(settings.embedding_model.as_str(), "embeddings")]);
+
+    let mut models: Vec<ConfiguredModel> = vec![];
+    for (model, feature) in uses {
+        match models.iter_mut().find(|m| m.model == model) {
+            Some(entry) => entry.used_for.push(feature.to_string()),
+            None => models.push(ConfiguredModel {
+                model: model.to_string(),
+                used_for: vec![feature.to_string()],
+            }),
+        }
+    }
+    models
+}
+
+fn missing_models(configured: Vec<ConfiguredModel>, installed: &[String]) -> Vec<ConfiguredModel> {
+    configured
+        .into_iter()
+        .filter(|m| !model_available(installed, &m.model))
+        .collect()
+}
+
+async fn check_model_health_internal(ollama: &OllamaClient, settings: &Settings) -> ModelHealth {
+    match ollama.list_models().await {
+        Ok(installed) => ModelHealth {
+            missing: missing_models(configured_models(settings), &installed),
+            reachable: true,
+            installed,
+        },
+        Err(_) => ModelHealth {
+            reachable: false,
+            installed: vec![],
+            missing: vec![],
+        },
+    }
+}
+
+/// Check the configured models once at startup and report the result to the frontend
+pub fn spawn_model_health_check(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let settings = app.state::<Database>().settings().get_or_default();
+        let ollama = app.state::<OllamaHttp>().client(&settings);
+        let health = check_model_health_internal(&ollama, &settings).await;
+        let _ = app.emit(MODEL_HEALTH_EVENT, health);
+    });
+}
+
+#[tauri::command]
+pub async fn check_model_health(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+) -> Result<ModelHealth, String> {
+    let settings = db.settings().get_or_default();
+    Ok(check_model_health_internal(&http.client(&settings), &settings).await)
+}
+
+/// Download a model; progress is emitted as `PULL_PROGRESS_EVENT` events
+#[tauri::command]
+pub async fn pull_ollama_model(
+    app: AppHandle,
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    name: String,
+) -> Result<(), String> {
+    let settings = db.settings().get_or_default();
+    let ollama = http.client(&settings);
+    ollama
+        .pull_model(&name, |status| {
+            let _ = app.emit(
+                PULL_PROGRESS_EVENT,
+                PullProgress {
+                    model: name.clone(),
+                    status: status.status.clone(),
+                    digest: status.digest.clone(),
+                    total: status.total,
+                    completed: status.completed,
+                },
+            );
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_ollama_model(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    name: String,
+) -> Result<(), String> {
+    let settings = db.settings().get_or_default();
+    http.client(&settings).delete_model(&name).await
+}
+
+#[tauri::command]
+pub async fn show_ollama_model(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    name: String,
+) -> Result<ModelDetails, String> {
+    let settings = db.settings().get_or_default();
+    http.client(&settings).show_model(&name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_models_groups_features() {
+        // Given
+        let settings = Settings {
+            tags_model: Some("qwen2.5:0.5b".to_string()),
+            ..Settings::default()
+        };
+
+        // When
+        let models = configured_models(&settings);
+
+        // Then
+        assert_eq!(
+            models,
+            vec![
+                ConfiguredModel {
+                    model: "qwen2.5-coder:7b".to_string(),
+                    used_for: vec!["default", "chat", "solutions", "summaries"]
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                },
+                ConfiguredModel {
+                    model: "qwen2.5:0.5b".to_string(),
+                    used_for: vec!["tags".to_string()],
+                },
+                ConfiguredModel {
+                    model: "nomic-embed-text".to_string(),
+                    used_for: vec!["embeddings".to_string()],
+                },
+            ]
+        );
+
+        // The language model is only checked while LLM detection is on
+        let detecting = Settings {
+            detect_language_with_llm: true,
+            language_model: Some("qwen2.5:0.5b".to_string()),
+            ..settings
+        };
+        assert_eq!(
+            configured_models(&detecting)[1].used_for,
+            vec!["tags", "language detection"]
+        );
+    }
+
+    #[test]
+    fn test_missing_models() {
+        let configured = configured_models(&Settings::default());
+        let installed = vec!["nomic-embed-text:latest".to_string()];
+
+        let missing = missing_models(configured, &installed);
+
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].model, "qwen2.5-coder:7b");
+    }
+}
@@ -0,0 +1,303 @@
+use rusqlite::OptionalExtension;
+use tauri::State;
+
+use crate::ai::{context, prompts, tags};
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::{PromptTemplate, PromptVariableInfo, Snippet};
+
+/// Question used when previewing the chat prompt without one
+const PREVIEW_QUESTION: &str = "How did I fix this?";
+
+fn find_definition(key: &str) -> Result<&'static prompts::PromptDefinition, AppError> {
+    prompts::definition(key)
+        .ok_or_else(|| AppError::NotFound(format!("Prompt template '{key}' not found")))
+}
+
+/// The user's edited template and when it was saved, if the prompt was customized
+fn fetch_override(db: &Database, key: &str) -> Result<Option<(String, String)>, AppError> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            "SELECT template, updated_at FROM prompt_templates WHERE key = ?1",
+            [key],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+    })
+    .map_err(AppError::from)
+}
+
+fn to_prompt_template(
+    definition: &prompts::PromptDefinition,
+    custom: Option<(String, String)>,
+) -> PromptTemplate {
+    let is_customized = custom.is_some();
+    let (template, updated_at) = match custom {
+        Some((template, updated_at)) => (template, Some(updated_at)),
+        None => (definition.default_template.to_string(), None),
+    };
+
+    PromptTemplate {
+        key: definition.key.to_string(),
+        name: definition.name.to_string(),
+        description: definition.description.to_string(),
+        template,
+        default_template: definition.default_template.to_string(),
+        variables: definition
+            .variables
+            .iter()
+            .map(|v| PromptVariableInfo {
+                name: v.name.to_string(),
+                description: v.description.to_string(),
+                required: v.required,
+            })
+            .collect(),
+        is_customized,
+        updated_at,
+    }
+}
+
+fn fetch_prompt_template(db: &Database, key: &str) -> Result<PromptTemplate, AppError> {
+    let definition = find_definition(key)?;
+    Ok(to_prompt_template(definition, fetch_override(db, key)?))
+}
+
+/// Template text for a prompt: the user's version if saved, otherwise the default
+pub(crate) fn load_prompt_template(db: &Database, key: &str) -> String {
+    match fetch_override(db, key) {
+        Ok(Some((template, _))) => template,
+        _ => prompts::definition(key)
+            .map(|d| d.default_template.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Load a prompt and fill in its variables
+pub(crate) fn render_prompt(db: &Database, key: &str, values: &[(&str, String)]) -> String {
+    prompts::render(&load_prompt_template(db, key), values)
+}
+
+fn save_prompt_template(db: &Database, key: &str, template: &str) -> Result<(), AppError> {
+    let definition = find_definition(key)?;
+    prompts::validate(definition, template).map_err(AppError::Validation)?;
+
+    db.with_connection(|conn| {
+        conn.execute(
+            "INSERT INTO prompt_templates (key, template, updated_at)
+             VALUES (?1, ?2, datetime('now'))
+             ON CONFLICT(key) DO UPDATE SET template = excluded.template, updated_at = excluded.updated_at",
+            rusqlite::params![key, template],
+        )?;
+        Ok(())
+    })
+    .map_err(AppError::from)
+}
+
+/// Values for every prompt variable, taken from `snippet`
+fn preview_variables(
+    snippet: &Snippet,
+    question: Option<&str>,
+    context_tokens: usize,
+) -> Vec<(&'static str, String)> {
+    let mut values = prompts::snippet_variables(snippet);
+
+    let content = [
+        Some(snippet.title.as_str()),
+        Some(snippet.problem.as_str()),
+        snippet.solution.as_deref(),
+        snippet.code.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.trim().is_empty())
+    .collect::<Vec<_>>()
+    .join("\n\n");
+    values.push(("content", content));
+    values.push(("existing_tags", tags::format_vocabulary(&snippet.tags)));
+
+    let hits = [(snippet.clone(), 1.0)];
+    values.push(("context", context::build_context(&hits, context_tokens)));
+    values.push((
+        "question",
+        question
+            .filter(|q| !q.trim().is_empty())
+            .unwrap_or(PREVIEW_QUESTION)
+            .to_string(),
+    ));
+    values.push(("current_snippet", String::new()));
+    values
+}
+
+#[tauri::command]
+pub fn list_prompt_templates(db: State<'_, Database>) -> Result<Vec<PromptTemplate>, String> {
+    prompts::PROMPTS
+        .iter()
+        .map(|definition| fetch_prompt_template(&db, definition.key))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(String::from)
+}
+
+#[tauri::command]
+pub fn update_prompt_template(
+    db: State<'_, Database>,
+    key: String,
+    template: String,
+) -> Result<PromptTemplate, String> {
+    save_prompt_template(&db, &key, &template).map_err(String::from)?;
+    fetch_prompt_template(&db, &key).map_err(String::from)
+}
+
+/// Drop the user's edits and go back to the built-in prompt
+#[tauri::command]
+pub fn reset_prompt_template(
+    db: State<'_, Database>,
+    key: String,
+) -> Result<PromptTemplate, String> {
+    find_definition(&key).map_err(String::from)?;
+    db.with_connection(|conn| {
+        conn.execute("DELETE FROM prompt_templates WHERE key = ?1", [&key])?;
+        Ok(())
+    })
+    .map_err(|e| AppError::Database(e).to_string())?;
+    fetch_prompt_template(&db, &key).map_err(String::from)
+}
+
+/// Render a prompt against a snippet without calling the model. `template` previews
+/// unsaved edits; otherwise the stored (or default) template is used.
+#[tauri::command]
+pub fn preview_prompt_template(
+    db: State<'_, Database>,
+    key: String,
+    snippet_id: String,
+    template: Option<String>,
+    question: Option<String>,
+) -> Result<String, String> {
+    let definition = find_definition(&key).map_err(String::from)?;
+    let template = match template {
+        Some(template) => {
+            prompts::validate(definition, &template)?;
+            template
+        }
+        None => load_prompt_template(&db, &key),
+    };
+
+    let snippet = db.snippets().get(&snippet_id).map_err(String::from)?;
+    let settings = db.settings().get_or_default();
+    let values = preview_variables(
+        &snippet,
+        question.as_deref(),
+        settings.chat_context_tokens.max(0) as usize,
+    );
+    Ok(prompts::render(&template, &values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        Database::new_in_memory().unwrap()
+    }
+
+    #[test]
+    fn test_defaults_are_used_until_customized() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let prompt = fetch_prompt_template(&db, prompts::CHAT).unwrap();
+
+        // Then
+        assert!(!prompt.is_customized);
+        assert_eq!(prompt.template, prompt.default_template);
+        assert!(prompt
+            .variables
+            .iter()
+            .any(|v| v.name == "question" && v.required));
+    }
+
+    #[test]
+    fn test_save_and_load_custom_template() {
+        // Given
+        let db = setup_db();
+        let custom = "Answer in Korean using bullet points.\n{{context}}\nQ: {{question}}";
+
+        // When
+        save_prompt_template(&db, prompts::CHAT, custom).unwrap();
+
+        // Then
+        let prompt = fetch_prompt_template(&db, prompts::CHAT).unwrap();
+        assert!(prompt.is_customized);
+        assert!(prompt.updated_at.is_some());
+        assert_eq!(load_prompt_template(&db, prompts::CHAT), custom);
+        let rendered = render_prompt(
+            &db,
+            prompts::CHAT,
+            &[
+                ("context", "[1] ctx".to_string()),
+                ("question", "Why?".to_string()),
+            ],
+        );
+        assert_eq!(
+            rendered,
+            "Answer in Korean using bullet points.\n[1] ctx\nQ: Why?"
+        );
+    }
+
+    #[test]
+    fn test_save_rejects_invalid_template() {
+        let db = setup_db();
+
+        let result = save_prompt_template(&db, prompts::GENERATE_SOLUTION, "Solve {{issue}}");
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert!(
+            !fetch_prompt_template(&db, prompts::GENERATE_SOLUTION)
+                .unwrap()
+                .is_customized
+        );
+    }
+
+    #[test]
+    fn test_unknown_prompt_key() {
+        let db = setup_db();
+
+        let result = save_prompt_template(&db, "write_poem", "{{problem}}");
+
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_preview_variables_cover_every_prompt() {
+        // Given
+        let db = setup_db();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem, solution) VALUES ('s1', 'DNS', 'No DNS', 'Set dns')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        let snippet = db.snippets().get("s1").unwrap();
+
+        // When
+        let values = preview_variables(&snippet, None, 1000);
+
+        // Then
+        for definition in prompts::PROMPTS {
+            let rendered = prompts::render(definition.default_template, &values);
+            assert!(
+                !rendered.contains("{{"),
+                "{} left placeholders",
+                definition.key
+            );
+        }
+        let chat = prompts::render(
+            prompts::definition(prompts::CHAT).unwrap().default_template,
+            &values,
+        );
+        assert!(chat.contains("### [1] DNS"));
+        assert!(chat.contains(PREVIEW_QUESTION));
+    }
+}
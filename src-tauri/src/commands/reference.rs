@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Url;
+use tauri::State;
+
+use crate::ai::embedding;
+use crate::ai::ollama::OllamaHttp;
+use crate::db::journal::snapshot_snippets;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::html::{self, Page};
+use crate::models::{Snippet, SnippetReference};
+
+/// Give up on pages that take longer than this to download
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Largest page body read when archiving a reference
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Longest text kept for one archived page
+const MAX_ARCHIVED_CHARS: usize = 200_000;
+
+fn parse_url(url: &str) -> Result<Url, AppError> {
+    let parsed = Url::parse(url.trim())
+        .map_err(|e| AppError::Validation(format!("Invalid URL {url}: {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(AppError::Validation(format!(
+            "Only http and https pages can be archived: {url}"
+        )));
+    }
+    Ok(parsed)
+}
+
+/// Download a page and extract its readable text; plain text pages are kept as they are
+async fn fetch_page(url: &Url) -> Result<Page, String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("RecallSnippet/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {url}: {e}"))?;
+    if !response.status().is_success() {
+        return Err(format!("{url} returned status: {}", response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+    let is_html = content_type.contains("html");
+    if !is_html && !content_type.starts_with("text/") {
+        return Err(format!("{url} is not a web page ({content_type})"));
+    }
+
+    let mut body = vec![];
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read {url}: {e}"))?
+    {
+        body.extend_from_slice(&chunk);
+        if body.len() > MAX_PAGE_BYTES {
+            return Err(format!(
+                "{url} is larger than {} MB",
+                MAX_PAGE_BYTES / 1024 / 1024
+            ));
+        }
+    }
+    let body = String::from_utf8_lossy(&body);
+
+    Ok(if is_html {
+        html::extract(&body)
+    } else {
+        Page {
+            title: None,
+            text: body.trim().to_string(),
+        }
+    })
+}
+
+/// Refresh the snippet's embedding when archived pages are part of it
+async fn reembed(db: &Database, http: &OllamaHttp, snippet: &Snippet) {
+    let settings = db.settings().get_or_default();
+    if settings.embed_references {
+        let ollama = http.client(&settings);
+        let _ = embedding::embed_snippet(db, &ollama, snippet, &settings.embedding_model).await;
+    }
+}
+
+/// Fetch `url` and archive its text as a reference of the snippet. Fetching a URL the
+/// snippet already refers to refreshes its copy; when the fetch fails the old copy is kept.
+pub(crate) async fn fetch_reference_internal(
+    db: &Database,
+    journal: &Journal,
+    http: &OllamaHttp,
+    snippet_id: &str,
+    url: &str,
+) -> Result<SnippetReference, String> {
+    let snippet = db.snippets().get(snippet_id).map_err(String::from)?;
+    let url = parse_url(url).map_err(String::from)?;
+
+    let page = fetch_page(&url).await?;
+    if page.text.is_empty() {
+        return Err(format!("No readable text found at {url}"));
+    }
+    let content: String = page.text.chars().take(MAX_ARCHIVED_CHARS).collect();
+    let before = snapshot_snippets(db, std::slice::from_ref(&snippet.id)).map_err(String::from)?;
+    let reference = db
+        .references()
+        .save(snippet_id, url.as_str(), page.title.as_deref(), &content)
+        .map_err(String::from)?;
+
+    reembed(db, http, &snippet).await;
+    journal.record_snippets(db, "Archive reference", before);
+    Ok(reference)
+}
+
+#[tauri::command]
+pub fn get_snippet_references(
+    db: State<'_, Database>,
+    snippet_id: String,
+) -> Result<Vec<SnippetReference>, String> {
+    db.snippets().get(&snippet_id).map_err(String::from)?;
+    db.references()
+        .for_snippet(&snippet_id)
+        .map_err(String::from)
+}
+
+#[tauri::command]
+pub async fn fetch_snippet_reference(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    snippet_id: String,
+    url: String,
+) -> Result<SnippetReference, String> {
+    fetch_reference_internal(&db, &journal, &http, &snippet_id, &url).await
+}
+
+#[tauri::command]
+pub async fn delete_snippet_reference(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    id: String,
+) -> Result<(), String> {
+    let reference = db.references().get(&id).map_err(String::from)?;
+    let before = snapshot_snippets(&db, std::slice::from_ref(&reference.snippet_id))
+        .map_err(String::from)?;
+    db.references().delete(&id).map_err(String::from)?;
+
+    let snippet = db
+        .snippets()
+        .get(&reference.snippet_id)
+        .map_err(String::from)?;
+    reembed(&db, &http, &snippet).await;
+    journal.record_snippets(&db, "Delete reference", before);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANSWER: &str = "<html><head><title>How to free a port</title></head><body>\
+        <nav>Questions</nav><main><p>Run <code>lsof -i :8080</code> first.</p></main>\
+        </body></html>";
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Port in use', 'p');",
+            )
+        })
+        .unwrap();
+        db
+    }
+
+    fn response(status: &str, content_type: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// Stand-in web server answering one canned response per connection, in order
+    async fn serve(responses: Vec<String>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = [0u8; 4096];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_fetch_archives_page_and_keeps_it_when_the_page_is_gone() {
+        // Given
+        let (db, journal, http) = (setup_db(), Journal::default(), OllamaHttp::default());
+        let base = serve(vec![
+            response("200 OK", "text/html; charset=utf-8", ANSWER),
+            response("404 Not Found", "text/html", "<p>Gone</p>"),
+        ])
+        .await;
+        let url = format!("{base}/questions/1");
+
+        // When
+        let archived = fetch_reference_internal(&db, &journal, &http, "s1", &url)
+            .await
+            .unwrap();
+        let refetched = fetch_reference_internal(&db, &journal, &http, "s1", &url).await;
+
+        // Then
+        assert_eq!(archived.url, url);
+        assert_eq!(archived.title.as_deref(), Some("How to free a port"));
+        assert_eq!(archived.content, "Run lsof -i :8080 first.");
+        assert!(refetched.unwrap_err().contains("404"));
+        let references = db.references().for_snippet("s1").unwrap();
+        assert_eq!(references, vec![archived]);
+
+        // Undo takes the archived copy back out
+        assert_eq!(
+            journal.undo(&db).unwrap().as_deref(),
+            Some("Archive reference")
+        );
+        assert!(db.references().for_snippet("s1").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_keeps_plain_text_and_refuses_other_content() {
+        // Given
+        let (db, journal, http) = (setup_db(), Journal::default(), OllamaHttp::default());
+        let base = serve(vec![
+            response("200 OK", "text/plain", "  docker system prune -af\n"),
+            response("200 OK", "application/pdf", "%PDF-1.7"),
+        ])
+        .await;
+
+        // When
+        let notes =
+            fetch_reference_internal(&db, &journal, &http, "s1", &format!("{base}/notes.txt"))
+                .await
+                .unwrap();
+        let pdf =
+            fetch_reference_internal(&db, &journal, &http, "s1", &format!("{base}/paper.pdf"))
+                .await;
+
+        // Then
+        assert_eq!(notes.title, None);
+        assert_eq!(notes.content, "docker system prune -af");
+        assert!(pdf.unwrap_err().contains("not a web page"));
+        assert_eq!(db.references().for_snippet("s1").unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_bad_urls_and_unknown_snippets() {
+        // Given
+        let (db, journal, http) = (setup_db(), Journal::default(), OllamaHttp::default());
+
+        // When
+        let file = fetch_reference_internal(&db, &journal, &http, "s1", "file:///etc/passwd").await;
+        let garbage = fetch_reference_internal(&db, &journal, &http, "s1", "not a url").await;
+        let missing =
+            fetch_reference_internal(&db, &journal, &http, "nope", "https://a.test").await;
+
+        // Then
+        assert!(file.unwrap_err().starts_with("Invalid input"));
+        assert!(garbage.unwrap_err().starts_with("Invalid input"));
+        assert!(missing.unwrap_err().starts_with("Not found"));
+    }
+}
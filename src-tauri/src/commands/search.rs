@@ -1,258 +1,200 @@
 use tauri::State;
 
-use crate::ai::ollama;
+use crate::ai::ollama::OllamaHttp;
+use crate::db::repo::RankScope;
 use crate::db::Database;
-use crate::models::{SearchResult, Settings, SnippetSummary, Tag};
-
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
-    if a.len() != b.len() || a.is_empty() {
-        return 0.0;
-    }
-    let dot: f64 = a.iter().zip(b.iter()).map(|(x, y)| (*x as f64) * (*y as f64)).sum();
-    let mag_a: f64 = a.iter().map(|x| (*x as f64) * (*x as f64)).sum::<f64>().sqrt();
-    let mag_b: f64 = b.iter().map(|x| (*x as f64) * (*x as f64)).sum::<f64>().sqrt();
-    if mag_a == 0.0 || mag_b == 0.0 {
-        return 0.0;
-    }
-    dot / (mag_a * mag_b)
-}
-
-fn decode_embedding(blob: &[u8]) -> Vec<f32> {
-    blob.chunks_exact(4)
-        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
-        .collect()
-}
-
-fn fetch_tags_for_snippet(db: &Database, snippet_id: &str) -> Vec<Tag> {
-    db.with_connection(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT t.id, t.name FROM tags t
-             INNER JOIN snippet_tags st ON st.tag_id = t.id
-             WHERE st.snippet_id = ?1",
-        )?;
-        let tags = stmt
-            .query_map([snippet_id], |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(tags)
-    })
-    .unwrap_or_default()
-}
-
-fn get_settings_internal(db: &Database) -> Settings {
-    db.with_connection(|conn| {
-        conn.query_row(
-            "SELECT theme, ollama_base_url, llm_model, embedding_model, search_limit, data_path
-             FROM settings WHERE id = 1",
-            [],
-            |row| {
-                Ok(Settings {
-                    theme: row.get(0)?,
-                    ollama_base_url: row.get(1)?,
-                    llm_model: row.get(2)?,
-                    embedding_model: row.get(3)?,
-                    search_limit: row.get(4)?,
-                    data_path: row.get(5)?,
-                })
-            },
-        )
-    })
-    .unwrap_or_default()
-}
+use crate::errors::AppError;
+use crate::models::{HitKind, SearchHit, SearchResult, SnippetFilter, SnippetSummary};
 
 #[tauri::command]
 pub async fn semantic_search(
     db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
     query: String,
     limit: Option<usize>,
+    include_inactive: Option<bool>,
 ) -> Result<Vec<SearchResult>, String> {
-    let settings = get_settings_internal(&db);
+    semantic_search_internal(
+        &db,
+        &http,
+        &query,
+        limit,
+        include_inactive.unwrap_or(false),
+        true,
+    )
+    .await
+    .map_err(String::from)
+}
+
+/// Rank every embedded snippet against the query. Drafts and archived snippets are only
+/// ranked when `include_inactive` is set, trashed ones when `include_deleted` is.
+pub(crate) async fn semantic_search_internal(
+    db: &Database,
+    http: &OllamaHttp,
+    query: &str,
+    limit: Option<usize>,
+    include_inactive: bool,
+    include_deleted: bool,
+) -> Result<Vec<SearchResult>, AppError> {
+    let settings = db.settings().get_or_default();
     let limit = limit.unwrap_or(settings.search_limit as usize);
 
-    // Generate query embedding
-    let query_embedding = ollama::create_embedding(&query, &settings.embedding_model, &settings.ollama_base_url).await?;
+    let query_embedding = http
+        .client(&settings)
+        .create_embedding(query, &settings.embedding_model)
+        .await
+        .map_err(AppError::Ollama)?;
+
+    let mut scored = db.embeddings().rank(
+        &query_embedding,
+        RankScope {
+            model: Some(&settings.embedding_model),
+            include_deleted,
+            include_inactive,
+            ..Default::default()
+        },
+    )?;
+    scored.truncate(limit);
 
-    // Load all embeddings from DB and compute similarity
-    let rows: Vec<(String, Vec<u8>)> = db
-        .with_connection(|conn| {
-            let mut stmt = conn.prepare(
-                "SELECT snippet_id, embedding FROM embeddings",
-            )?;
-            let rows = stmt
-                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(rows)
+    scored
+        .into_iter()
+        .map(|(snippet_id, score)| {
+            Ok(SearchResult {
+                snippet: db.snippets().summary(&snippet_id)?,
+                score,
+            })
         })
-        .map_err(|e| format!("Failed to load embeddings: {}", e))?;
+        .collect()
+}
 
-    // Compute cosine similarity for each
-    let mut scored: Vec<(String, f64)> = rows
-        .iter()
-        .map(|(id, blob)| {
-            let emb = decode_embedding(blob);
-            let score = cosine_similarity(&query_embedding, &emb);
-            (id.clone(), score)
-        })
-        .collect();
+/// Hybrid search results; `semantic_error` says why only keyword matches are listed
+pub(crate) struct HybridSearch {
+    pub hits: Vec<SearchHit>,
+    pub semantic_error: Option<String>,
+}
 
-    // Sort by score descending
-    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-    scored.truncate(limit);
+/// Semantic matches first, then keyword matches, so results still come back when
+/// Ollama is down
+pub(crate) async fn hybrid_search_internal(
+    db: &Database,
+    http: &OllamaHttp,
+    query: &str,
+    limit: Option<usize>,
+    keyword_only: bool,
+    include_inactive: bool,
+) -> Result<HybridSearch, String> {
+    let limit = limit.unwrap_or(db.settings().get_or_default().search_limit as usize);
+
+    let (semantic, semantic_error) = if keyword_only {
+        (vec![], None)
+    } else {
+        match semantic_search_internal(db, http, query, Some(limit), include_inactive, false).await
+        {
+            Ok(results) => (results, None),
+            Err(e) => (vec![], Some(e.to_string())),
+        }
+    };
+
+    let keyword = db
+        .snippets()
+        .list(&SnippetFilter {
+            search: Some(query.to_string()),
+            any_status: Some(include_inactive),
+            ..Default::default()
+        })
+        .map_err(String::from)?;
 
-    // Fetch snippet summaries
-    let mut results = Vec::with_capacity(scored.len());
-    for (snippet_id, score) in scored {
-        let summary = db
-            .with_connection(|conn| {
-                conn.query_row(
-                    "SELECT id, title, problem, code_language, SUBSTR(code, 1, 200), created_at, is_favorite, is_deleted, deleted_at, last_accessed_at 
-                     FROM snippets WHERE id = ?1",
-                    [&snippet_id],
-                    |row| {
-                        Ok(SnippetSummary {
-                            id: row.get(0)?,
-                            title: row.get(1)?,
-                            problem: row.get(2)?,
-                            code_language: row.get(3)?,
-                            code_preview: row.get(4)?,
-                            tags: vec![],
-                            created_at: row.get(5)?,
-                            is_favorite: row.get(6)?,
-                            is_deleted: row.get(7)?,
-                            deleted_at: row.get(8)?,
-                            last_accessed_at: row.get(9)?,
-                        })
-                    },
-                )
-            })
-            .map_err(|e| format!("Failed to fetch snippet: {}", e))?;
+    Ok(HybridSearch {
+        hits: merge_hits(semantic, keyword, limit),
+        semantic_error,
+    })
+}
 
-        let tags = fetch_tags_for_snippet(&db, &snippet_id);
-        let summary = SnippetSummary { tags, ..summary };
+/// Semantic results in score order, then keyword matches not already listed; trashed
+/// snippets are dropped
+fn merge_hits(
+    semantic: Vec<SearchResult>,
+    keyword: Vec<SnippetSummary>,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let mut hits: Vec<SearchHit> = semantic
+        .into_iter()
+        .filter(|result| !result.snippet.is_deleted)
+        .map(|result| SearchHit {
+            snippet: result.snippet,
+            score: Some(result.score),
+            kind: HitKind::Semantic,
+        })
+        .collect();
 
-        results.push(SearchResult {
-            snippet: summary,
-            score,
-        });
+    for snippet in keyword {
+        if !hits.iter().any(|hit| hit.snippet.id == snippet.id) {
+            hits.push(SearchHit {
+                snippet,
+                score: None,
+                kind: HitKind::Keyword,
+            });
+        }
     }
 
-    Ok(results)
+    hits.truncate(limit);
+    hits
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_cosine_similarity_identical() {
-        let a = vec![1.0, 0.0, 0.0];
-        let b = vec![1.0, 0.0, 0.0];
-        let sim = cosine_similarity(&a, &b);
-        assert!((sim - 1.0).abs() < 1e-6);
-    }
-
-    #[test]
-    fn test_cosine_similarity_orthogonal() {
-        let a = vec![1.0, 0.0];
-        let b = vec![0.0, 1.0];
-        let sim = cosine_similarity(&a, &b);
-        assert!(sim.abs() < 1e-6);
+    fn summary(id: &str, is_deleted: bool) -> SnippetSummary {
+        SnippetSummary {
+            id: id.to_string(),
+            title: format!("Title {id}"),
+            problem: "p".to_string(),
+            summary: None,
+            code_language: Some("rust".to_string()),
+            code_preview: None,
+            tags: vec![],
+            created_at: String::new(),
+            is_favorite: false,
+            is_deleted,
+            deleted_at: None,
+            last_accessed_at: None,
+            status: Default::default(),
+        }
     }
 
-    #[test]
-    fn test_cosine_similarity_opposite() {
-        let a = vec![1.0, 0.0];
-        let b = vec![-1.0, 0.0];
-        let sim = cosine_similarity(&a, &b);
-        assert!((sim - (-1.0)).abs() < 1e-6);
-    }
-
-    #[test]
-    fn test_decode_embedding() {
-        let original = vec![1.0_f32, 2.5, -0.3];
-        let blob: Vec<u8> = original.iter().flat_map(|f| f.to_le_bytes()).collect();
-        let decoded = decode_embedding(&blob);
-        assert_eq!(decoded, original);
-    }
-
-    #[test]
-    fn test_cosine_similarity_empty() {
-        let a: Vec<f32> = vec![];
-        let b: Vec<f32> = vec![];
-        assert_eq!(cosine_similarity(&a, &b), 0.0);
+    fn semantic(id: &str, score: f64, is_deleted: bool) -> SearchResult {
+        SearchResult {
+            snippet: summary(id, is_deleted),
+            score,
+        }
     }
 
     #[test]
-    fn test_cosine_similarity_zero_magnitude() {
-        let a = vec![0.0, 0.0];
-        let b = vec![1.0, 0.0];
-        assert_eq!(cosine_similarity(&a, &b), 0.0);
+    fn test_merge_hits_puts_semantic_first_without_duplicates() {
+        // Given
+        let semantic_results = vec![semantic("s1", 0.9, false), semantic("s2", 0.8, true)];
+        let keyword = vec![summary("s3", false), summary("s1", false)];
+
+        // When
+        let hits = merge_hits(semantic_results, keyword, 10);
+
+        // Then
+        let ids: Vec<&str> = hits.iter().map(|h| h.snippet.id.as_str()).collect();
+        assert_eq!(ids, vec!["s1", "s3"]);
+        assert_eq!(hits[0].kind, HitKind::Semantic);
+        assert_eq!(hits[1].kind, HitKind::Keyword);
+        assert_eq!(hits[1].score, None);
     }
 
     #[test]
-    fn test_semantic_search_db_integration() {
-        // Test that we can store embeddings and retrieve/rank them
-        let db = Database::new_in_memory().unwrap();
-
-        // Insert snippets
-        db.with_connection(|conn| {
-            conn.execute(
-                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Docker networking', 'Container cannot reach host')",
-                [],
-            )?;
-            conn.execute(
-                "INSERT INTO snippets (id, title, problem) VALUES ('s2', 'Pizza recipe', 'Need gluten free dough')",
-                [],
-            )?;
-            Ok(())
-        }).unwrap();
-
-        // Store fake embeddings (3-dim for simplicity)
-        let docker_emb: Vec<u8> = [0.9_f32, 0.1, 0.0].iter().flat_map(|f| f.to_le_bytes()).collect();
-        let pizza_emb: Vec<u8> = [0.0_f32, 0.1, 0.9].iter().flat_map(|f| f.to_le_bytes()).collect();
-
-        db.with_connection(|conn| {
-            conn.execute(
-                "INSERT INTO embeddings (snippet_id, embedding, embedding_model) VALUES ('s1', ?1, 'test')",
-                rusqlite::params![docker_emb],
-            )?;
-            conn.execute(
-                "INSERT INTO embeddings (snippet_id, embedding, embedding_model) VALUES ('s2', ?1, 'test')",
-                rusqlite::params![pizza_emb],
-            )?;
-            Ok(())
-        }).unwrap();
-
-        // Simulate query embedding close to docker
-        let query_emb = vec![0.8_f32, 0.2, 0.0];
-
-        // Load and rank
-        let rows: Vec<(String, Vec<u8>)> = db
-            .with_connection(|conn| {
-                let mut stmt = conn.prepare("SELECT snippet_id, embedding FROM embeddings")?;
-                let rows = stmt
-                    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
-                    .collect::<Result<Vec<_>, _>>()?;
-                Ok(rows)
-            })
-            .unwrap();
-
-        let mut scored: Vec<(String, f64)> = rows
-            .iter()
-            .map(|(id, blob)| {
-                let emb = decode_embedding(blob);
-                (id.clone(), cosine_similarity(&query_emb, &emb))
-            })
-            .collect();
-
-        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-        // Docker should rank first
-        assert_eq!(scored[0].0, "s1");
-        assert!(scored[0].1 > scored[1].1);
+    fn test_merge_hits_respects_limit() {
+        let hits = merge_hits(
+            vec![semantic("s1", 0.9, false)],
+            vec![summary("s2", false), summary("s3", false)],
+            2,
+        );
+
+        assert_eq!(hits.len(), 2);
     }
 }
@@ -0,0 +1,27 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::Database;
+use crate::models::ApiServerStatus;
+use crate::server::ApiServer;
+
+/// Start the API server at launch when it is enabled in settings; a failure to start is
+/// reported by `get_api_server_status`
+pub fn spawn_api_server(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let settings = app.state::<Database>().settings().get_or_default();
+        let _ = app.state::<ApiServer>().apply(app.clone(), &settings).await;
+    });
+}
+
+#[tauri::command]
+pub async fn get_api_server_status(
+    server: State<'_, ApiServer>,
+) -> Result<ApiServerStatus, String> {
+    Ok(server.status().await)
+}
+
+/// Replace the API token; clients using the old one are rejected from the next request
+#[tauri::command]
+pub fn regenerate_api_token(db: State<'_, Database>) -> Result<String, String> {
+    db.settings().regenerate_api_token().map_err(String::from)
+}
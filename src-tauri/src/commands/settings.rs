@@ -1,71 +1,330 @@
-use tauri::State;
+use tauri::{AppHandle, State};
 
+use crate::ai::ollama::OllamaHttp;
+use crate::ai::vector::VectorEncoding;
+use crate::commands::capture::{register_capture_shortcut, validate_shortcut};
 use crate::db::Database;
 use crate::errors::AppError;
 use crate::models::{Settings, UpdateSettingsInput};
-
-fn fetch_settings(db: &Database) -> Result<Settings, AppError> {
-    db.with_connection(|conn| {
-        conn.query_row(
-            "SELECT theme, ollama_base_url, llm_model, embedding_model, search_limit, data_path
-             FROM settings WHERE id = 1",
-            [],
-            |row| {
-                Ok(Settings {
-                    theme: row.get(0)?,
-                    ollama_base_url: row.get(1)?,
-                    llm_model: row.get(2)?,
-                    embedding_model: row.get(3)?,
-                    search_limit: row.get(4)?,
-                    data_path: row.get(5)?,
-                })
-            },
-        )
-    })
-    .map_err(AppError::from)
-}
+use crate::server::ApiServer;
 
 #[tauri::command]
 pub fn get_settings(db: State<'_, Database>) -> Result<Settings, String> {
-    fetch_settings(&db).map_err(String::from)
+    db.settings().get().map_err(String::from)
+}
+
+/// Whether `name` is an installed model; a name without a tag also matches `name:latest`
+pub(crate) fn model_available(installed: &[String], name: &str) -> bool {
+    installed
+        .iter()
+        .any(|m| m == name || (!name.contains(':') && *m == format!("{name}:latest")))
+}
+
+/// Ollama keep_alive durations: a number of seconds or a number with an s/m/h unit, "-1" keeps
+/// the model loaded indefinitely
+fn is_valid_keep_alive(value: &str) -> bool {
+    if value == "-1" {
+        return true;
+    }
+    let digits = value.trim_end_matches(['s', 'm', 'h']);
+    value.len() - digits.len() <= 1
+        && !digits.is_empty()
+        && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+fn validate_generation_options(input: &UpdateSettingsInput) -> Result<(), String> {
+    if let Some(temperature) = input.temperature {
+        if !(0.0..=2.0).contains(&temperature) {
+            return Err("Temperature must be between 0 and 2".to_string());
+        }
+    }
+    if let Some(top_p) = input.top_p {
+        if !(top_p > 0.0 && top_p <= 1.0) {
+            return Err("Top-p must be greater than 0 and at most 1".to_string());
+        }
+    }
+    if let Some(num_ctx) = input.num_ctx {
+        if num_ctx < 256 {
+            return Err("Context window must be at least 256 tokens".to_string());
+        }
+    }
+    if let Some(num_predict) = input.num_predict {
+        if num_predict == 0 || num_predict < -2 {
+            return Err(
+                "Max tokens must be positive, -1 (unlimited) or -2 (fill context)".to_string(),
+            );
+        }
+    }
+    if let Some(ref keep_alive) = input.keep_alive {
+        if !keep_alive.is_empty() && !is_valid_keep_alive(keep_alive) {
+            return Err(format!("Invalid keep-alive duration: {keep_alive}"));
+        }
+    }
+    Ok(())
+}
+
+fn validate_connection_options(input: &UpdateSettingsInput) -> Result<(), String> {
+    for (label, value, max) in [
+        ("Connect timeout", input.ollama_connect_timeout_secs, 120),
+        ("Request timeout", input.ollama_request_timeout_secs, 600),
+        (
+            "Generation timeout",
+            input.ollama_generate_timeout_secs,
+            3600,
+        ),
+    ] {
+        if let Some(secs) = value {
+            if !(1..=max).contains(&secs) {
+                return Err(format!("{label} must be between 1 and {max} seconds"));
+            }
+        }
+    }
+    if let Some(retries) = input.ollama_max_retries {
+        if !(0..=5).contains(&retries) {
+            return Err("Retries must be between 0 and 5".to_string());
+        }
+    }
+    if let Some(size) = input.embedding_batch_size {
+        if !(1..=256).contains(&size) {
+            return Err("Embedding batch size must be between 1 and 256".to_string());
+        }
+    }
+    if let Some(concurrency) = input.embedding_concurrency {
+        if !(1..=8).contains(&concurrency) {
+            return Err("Embedding concurrency must be between 1 and 8".to_string());
+        }
+    }
+    if let Some(ref encoding) = input.embedding_encoding {
+        if VectorEncoding::parse(encoding).is_none() {
+            return Err(format!(
+                "Unknown embedding encoding: {encoding} (expected f32, f16 or int8)"
+            ));
+        }
+    }
+    if let Some(port) = input.api_server_port {
+        if !(1024..=65535).contains(&port) {
+            return Err("API port must be between 1024 and 65535".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Models in `input` that differ from the stored settings; only these need checking
+fn changed_models<'a>(input: &'a UpdateSettingsInput, current: &Settings) -> Vec<&'a str> {
+    [
+        (&input.llm_model, Some(&current.llm_model)),
+        (&input.embedding_model, Some(&current.embedding_model)),
+        (&input.chat_model, current.chat_model.as_ref()),
+        (&input.solution_model, current.solution_model.as_ref()),
+        (&input.tags_model, current.tags_model.as_ref()),
+        (&input.summary_model, current.summary_model.as_ref()),
+        (&input.language_model, current.language_model.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(new, old)| {
+        let new = new.as_deref()?.trim();
+        (!new.is_empty() && Some(new) != old.map(|o| o.as_str())).then_some(new)
+    })
+    .collect()
 }
 
 #[tauri::command]
-pub fn update_settings(
+pub async fn update_settings(
+    app: AppHandle,
     db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    server: State<'_, ApiServer>,
     input: UpdateSettingsInput,
 ) -> Result<Settings, String> {
+    validate_generation_options(&input)?;
+    validate_connection_options(&input)?;
+    if let Some(ref shortcut) = input.capture_shortcut {
+        validate_shortcut(shortcut)?;
+    }
+
+    let current = db.settings().get().map_err(String::from)?;
+    let models = changed_models(&input, &current);
+    if !models.is_empty() {
+        let mut target = current.clone();
+        if let Some(ref url) = input.ollama_base_url {
+            target.ollama_base_url = url.clone();
+        }
+        let installed = http
+            .client(&target)
+            .list_models()
+            .await
+            .map_err(|e| format!("Cannot verify models: {e}"))?;
+        let missing: Vec<&str> = models
+            .into_iter()
+            .filter(|m| !model_available(&installed, m))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!(
+                "Model(s) not installed in Ollama: {}",
+                missing.join(", ")
+            ));
+        }
+    }
+
     // Build dynamic UPDATE query based on provided fields
     let mut sets: Vec<String> = vec![];
     let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
 
     if let Some(ref theme) = input.theme {
-        sets.push(format!("theme = ?{}", sets.len() + 1));
+        sets.push(format!("theme = ?{}", params.len() + 1));
         params.push(Box::new(theme.clone()));
     }
     if let Some(ref url) = input.ollama_base_url {
-        sets.push(format!("ollama_base_url = ?{}", sets.len() + 1));
+        sets.push(format!("ollama_base_url = ?{}", params.len() + 1));
         params.push(Box::new(url.clone()));
     }
     if let Some(ref model) = input.llm_model {
-        sets.push(format!("llm_model = ?{}", sets.len() + 1));
-        params.push(Box::new(model.clone()));
+        sets.push(format!("llm_model = ?{}", params.len() + 1));
+        params.push(Box::new(model.trim().to_string()));
     }
     if let Some(ref model) = input.embedding_model {
-        sets.push(format!("embedding_model = ?{}", sets.len() + 1));
-        params.push(Box::new(model.clone()));
+        sets.push(format!("embedding_model = ?{}", params.len() + 1));
+        params.push(Box::new(model.trim().to_string()));
     }
     if let Some(limit) = input.search_limit {
-        sets.push(format!("search_limit = ?{}", sets.len() + 1));
+        sets.push(format!("search_limit = ?{}", params.len() + 1));
         params.push(Box::new(limit));
     }
     if input.data_path.is_some() {
-        sets.push(format!("data_path = ?{}", sets.len() + 1));
+        sets.push(format!("data_path = ?{}", params.len() + 1));
         params.push(Box::new(input.data_path.clone()));
     }
+    if let Some(days) = input.trash_retention_days {
+        if days < 0 {
+            return Err("Trash retention cannot be negative".to_string());
+        }
+        sets.push(format!("trash_retention_days = ?{}", params.len() + 1));
+        params.push(Box::new(days));
+    }
+    if let Some(enabled) = input.detect_language_with_llm {
+        sets.push(format!("detect_language_with_llm = ?{}", params.len() + 1));
+        params.push(Box::new(enabled));
+    }
+    if let Some(threshold) = input.chat_relevance_threshold {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err("Chat relevance threshold must be between 0 and 1".to_string());
+        }
+        sets.push(format!("chat_relevance_threshold = ?{}", params.len() + 1));
+        params.push(Box::new(threshold));
+    }
+    if let Some(top_k) = input.chat_top_k {
+        if top_k < 1 {
+            return Err("Chat top-k must be at least 1".to_string());
+        }
+        sets.push(format!("chat_top_k = ?{}", params.len() + 1));
+        params.push(Box::new(top_k));
+    }
+    if let Some(tokens) = input.chat_context_tokens {
+        if tokens < 256 {
+            return Err("Chat context budget must be at least 256 tokens".to_string());
+        }
+        sets.push(format!("chat_context_tokens = ?{}", params.len() + 1));
+        params.push(Box::new(tokens));
+    }
+    for (column, model) in [
+        ("chat_model", &input.chat_model),
+        ("solution_model", &input.solution_model),
+        ("tags_model", &input.tags_model),
+        ("summary_model", &input.summary_model),
+        ("language_model", &input.language_model),
+    ] {
+        if let Some(model) = model {
+            let model = Some(model.trim().to_string()).filter(|m| !m.is_empty());
+            sets.push(format!("{column} = ?{}", params.len() + 1));
+            params.push(Box::new(model));
+        }
+    }
+    if input.reset_generation_options {
+        for column in [
+            "temperature",
+            "top_p",
+            "seed",
+            "num_ctx",
+            "num_predict",
+            "keep_alive",
+        ] {
+            sets.push(format!("{column} = NULL"));
+        }
+    }
+    if let Some(temperature) = input.temperature {
+        sets.push(format!("temperature = ?{}", params.len() + 1));
+        params.push(Box::new(temperature));
+    }
+    if let Some(top_p) = input.top_p {
+        sets.push(format!("top_p = ?{}", params.len() + 1));
+        params.push(Box::new(top_p));
+    }
+    if let Some(seed) = input.seed {
+        sets.push(format!("seed = ?{}", params.len() + 1));
+        params.push(Box::new(seed));
+    }
+    if let Some(num_ctx) = input.num_ctx {
+        sets.push(format!("num_ctx = ?{}", params.len() + 1));
+        params.push(Box::new(num_ctx));
+    }
+    if let Some(num_predict) = input.num_predict {
+        sets.push(format!("num_predict = ?{}", params.len() + 1));
+        params.push(Box::new(num_predict));
+    }
+    if let Some(ref keep_alive) = input.keep_alive {
+        let keep_alive = Some(keep_alive.clone()).filter(|k| !k.is_empty());
+        sets.push(format!("keep_alive = ?{}", params.len() + 1));
+        params.push(Box::new(keep_alive));
+    }
+    for (column, value) in [
+        (
+            "ollama_connect_timeout_secs",
+            input.ollama_connect_timeout_secs,
+        ),
+        (
+            "ollama_request_timeout_secs",
+            input.ollama_request_timeout_secs,
+        ),
+        (
+            "ollama_generate_timeout_secs",
+            input.ollama_generate_timeout_secs,
+        ),
+        ("ollama_max_retries", input.ollama_max_retries),
+        ("embedding_batch_size", input.embedding_batch_size),
+        ("embedding_concurrency", input.embedding_concurrency),
+    ] {
+        if let Some(value) = value {
+            sets.push(format!("{column} = ?{}", params.len() + 1));
+            params.push(Box::new(value));
+        }
+    }
+
+    if let Some(ref encoding) = input.embedding_encoding {
+        sets.push(format!("embedding_encoding = ?{}", params.len() + 1));
+        params.push(Box::new(encoding.clone()));
+    }
+    for (column, value) in [
+        ("api_server_enabled", input.api_server_enabled),
+        ("mcp_read_only", input.mcp_read_only),
+        ("capture_window_title", input.capture_window_title),
+        ("embed_references", input.embed_references),
+    ] {
+        if let Some(value) = value {
+            sets.push(format!("{column} = ?{}", params.len() + 1));
+            params.push(Box::new(value));
+        }
+    }
+    if let Some(port) = input.api_server_port {
+        sets.push(format!("api_server_port = ?{}", params.len() + 1));
+        params.push(Box::new(port));
+    }
+    if let Some(ref shortcut) = input.capture_shortcut {
+        sets.push(format!("capture_shortcut = ?{}", params.len() + 1));
+        params.push(Box::new(shortcut.trim().to_string()));
+    }
 
     if sets.is_empty() {
-        return fetch_settings(&db).map_err(String::from);
+        return db.settings().get().map_err(String::from);
     }
 
     let sql = format!("UPDATE settings SET {} WHERE id = 1", sets.join(", "));
@@ -78,20 +337,43 @@ pub fn update_settings(
     })
     .map_err(|e| AppError::Database(e).to_string())?;
 
-    fetch_settings(&db).map_err(String::from)
+    if let Some(encoding) = input
+        .embedding_encoding
+        .as_deref()
+        .and_then(VectorEncoding::parse)
+    {
+        if encoding.as_str() != current.embedding_encoding {
+            db.embeddings().reencode(encoding)?;
+        }
+    }
+    if input
+        .embed_references
+        .is_some_and(|embed| embed != current.embed_references)
+    {
+        db.embeddings().mark_all_stale()?;
+    }
+
+    let settings = db.settings().get().map_err(String::from)?;
+    if input.capture_shortcut.is_some() {
+        register_capture_shortcut(&app, &settings.capture_shortcut)?;
+    }
+    if input.api_server_enabled.is_some() || input.api_server_port.is_some() {
+        server.apply(app, &settings).await?;
+    }
+    Ok(settings)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::db::Database;
-    use crate::models::Settings;
+    use crate::models::{AiFeature, Settings, UpdateSettingsInput};
 
     fn setup_db() -> Database {
         Database::new_in_memory().unwrap()
     }
 
     fn get_settings(db: &Database) -> Settings {
-        super::fetch_settings(db).unwrap()
+        db.settings().get().unwrap()
     }
 
     #[test]
@@ -105,6 +387,11 @@ mod tests {
         assert_eq!(settings.embedding_model, "nomic-embed-text");
         assert_eq!(settings.search_limit, 10);
         assert!(settings.data_path.is_none());
+        assert_eq!(settings.trash_retention_days, 30);
+        assert!(!settings.detect_language_with_llm);
+        assert_eq!(settings.chat_relevance_threshold, 0.3);
+        assert_eq!(settings.chat_top_k, 5);
+        assert_eq!(settings.chat_context_tokens, 3000);
     }
 
     #[test]
@@ -191,4 +478,112 @@ mod tests {
             .unwrap();
         assert_eq!(count, 1);
     }
+
+    #[test]
+    fn test_model_for_falls_back_to_llm_model() {
+        let settings = Settings {
+            tags_model: Some("qwen2.5:0.5b".to_string()),
+            chat_model: Some(String::new()),
+            ..Settings::default()
+        };
+
+        assert_eq!(settings.model_for(AiFeature::Tags), "qwen2.5:0.5b");
+        assert_eq!(settings.model_for(AiFeature::Chat), "qwen2.5-coder:7b");
+        assert_eq!(settings.model_for(AiFeature::Summary), "qwen2.5-coder:7b");
+        assert_eq!(settings.model_for(AiFeature::Language), "qwen2.5-coder:7b");
+    }
+
+    #[test]
+    fn test_model_available_matches_latest_tag() {
+        let installed = vec!["llama3:latest".to_string(), "qwen2.5:0.5b".to_string()];
+
+        assert!(super::model_available(&installed, "llama3"));
+        assert!(super::model_available(&installed, "qwen2.5:0.5b"));
+        assert!(!super::model_available(&installed, "qwen2.5"));
+        assert!(!super::model_available(&installed, "mistral"));
+    }
+
+    #[test]
+    fn test_validate_generation_options() {
+        let input = |value: serde_json::Value| -> UpdateSettingsInput {
+            serde_json::from_value(value).unwrap()
+        };
+
+        assert!(super::validate_generation_options(&input(serde_json::json!({
+            "temperature": 0.2, "topP": 0.9, "numCtx": 8192, "numPredict": -1, "keepAlive": "10m"
+        })))
+        .is_ok());
+        assert!(super::validate_generation_options(&input(
+            serde_json::json!({ "temperature": 3.0 })
+        ))
+        .is_err());
+        assert!(
+            super::validate_generation_options(&input(serde_json::json!({ "topP": 0.0 }))).is_err()
+        );
+        assert!(
+            super::validate_generation_options(&input(serde_json::json!({ "numPredict": 0 })))
+                .is_err()
+        );
+        assert!(super::validate_generation_options(&input(
+            serde_json::json!({ "keepAlive": "5 min" })
+        ))
+        .is_err());
+        assert!(super::validate_generation_options(&input(
+            serde_json::json!({ "keepAlive": "-1" })
+        ))
+        .is_ok());
+    }
+
+    #[test]
+    fn test_validate_connection_options() {
+        let input = |value: serde_json::Value| -> UpdateSettingsInput {
+            serde_json::from_value(value).unwrap()
+        };
+
+        assert!(super::validate_connection_options(&input(serde_json::json!({
+            "ollamaConnectTimeoutSecs": 2, "ollamaGenerateTimeoutSecs": 900, "ollamaMaxRetries": 0
+        })))
+        .is_ok());
+        assert!(super::validate_connection_options(&input(
+            serde_json::json!({ "ollamaRequestTimeoutSecs": 0 })
+        ))
+        .is_err());
+        assert!(super::validate_connection_options(&input(
+            serde_json::json!({ "ollamaMaxRetries": 10 })
+        ))
+        .is_err());
+        assert!(super::validate_connection_options(&input(
+            serde_json::json!({ "embeddingBatchSize": 0 })
+        ))
+        .is_err());
+        assert!(super::validate_connection_options(&input(
+            serde_json::json!({ "embeddingEncoding": "int8" })
+        ))
+        .is_ok());
+        assert!(super::validate_connection_options(&input(
+            serde_json::json!({ "embeddingEncoding": "f64" })
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_changed_models_skips_unchanged_and_cleared() {
+        let current = Settings {
+            chat_model: Some("llama3:70b".to_string()),
+            ..Settings::default()
+        };
+        let input: UpdateSettingsInput = serde_json::from_value(serde_json::json!({
+            "llmModel": "qwen2.5-coder:7b",
+            "embeddingModel": " mxbai-embed-large ",
+            "chatModel": "llama3:70b",
+            "tagsModel": "qwen2.5:0.5b",
+            "summaryModel": ""
+        }))
+        .unwrap();
+
+        assert_eq!(
+            super::changed_models(&input, &current),
+            vec!["mxbai-embed-large", "qwen2.5:0.5b"]
+        );
+    }
 }
@@ -2,111 +2,102 @@ use tauri::State;
 use uuid::Uuid;
 
 use crate::ai::embedding;
-use crate::db::Database;
+use crate::ai::language as ai_language;
+use crate::ai::ollama::OllamaHttp;
+use crate::commands::duplicate::check_duplicates_internal;
+use crate::commands::snippet_file::{insert_file, set_first_file};
+use crate::db::journal::snapshot_snippets;
+use crate::db::{Database, Journal};
 use crate::errors::AppError;
+use crate::language;
 use crate::models::{
-    CreateSnippetInput, Settings, Snippet, SnippetFilter, SnippetSummary, Tag, UpdateSnippetInput,
+    CreateSnippetInput, InboxCounts, Snippet, SnippetFileInput, SnippetFilter, SnippetStatus,
+    SnippetSummary, UpdateSnippetInput,
 };
 
-fn get_settings_internal(db: &Database) -> Settings {
-    db.with_connection(|conn| {
-        conn.query_row(
-            "SELECT theme, ollama_base_url, llm_model, embedding_model, search_limit, data_path
-             FROM settings WHERE id = 1",
-            [],
-            |row| {
-                Ok(Settings {
-                    theme: row.get(0)?,
-                    ollama_base_url: row.get(1)?,
-                    llm_model: row.get(2)?,
-                    embedding_model: row.get(3)?,
-                    search_limit: row.get(4)?,
-                    data_path: row.get(5)?,
-                })
-            },
-        )
-    })
-    .unwrap_or_default()
-}
-
-fn fetch_tags_for_snippet(db: &Database, snippet_id: &str) -> Result<Vec<Tag>, AppError> {
-    db.with_connection(|conn| {
-        let mut stmt = conn.prepare(
-            "SELECT t.id, t.name FROM tags t
-             INNER JOIN snippet_tags st ON st.tag_id = t.id
-             WHERE st.snippet_id = ?1",
-        )?;
-        let tags = stmt
-            .query_map([snippet_id], |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(tags)
-    })
-    .map_err(AppError::from)
+/// Embedding of a new snippet computed before it is saved, by the duplicate check
+pub(crate) enum DraftEmbedding {
+    /// Not computed; the saved snippet is embedded
+    Pending,
+    /// Embedding of `text`, reused when the saved snippet embeds the same text
+    Ready { text: String, vector: Vec<f32> },
+    /// Ollama failed on the draft, so the saved snippet is left for the next reindex
+    Unavailable,
 }
 
-fn fetch_snippet_by_id(db: &Database, id: &str) -> Result<Snippet, AppError> {
-    let snippet = db.with_connection(|conn| {
-        conn.query_row(
-            "SELECT id, title, problem, solution, code, code_language, reference_url, created_at, updated_at, is_favorite, is_deleted, deleted_at, last_accessed_at
-             FROM snippets WHERE id = ?1",
-            [id],
-            |row| {
-                Ok(Snippet {
-                    id: row.get(0)?,
-                    title: row.get(1)?,
-                    problem: row.get(2)?,
-                    solution: row.get(3)?,
-                    code: row.get(4)?,
-                    code_language: row.get(5)?,
-                    reference_url: row.get(6)?,
-                    tags: vec![],
-                    created_at: row.get(7)?,
-                    updated_at: row.get(8)?,
-                    is_favorite: row.get(9)?,
-                    is_deleted: row.get(10)?,
-                    deleted_at: row.get(11)?,
-                    last_accessed_at: row.get(12)?,
-                })
-            },
-        )
-    }).map_err(|e| match e {
-        rusqlite::Error::QueryReturnedNoRows => {
-            AppError::NotFound(format!("Snippet with id '{id}' not found"))
+impl DraftEmbedding {
+    pub fn vector(&self) -> Option<&[f32]> {
+        match self {
+            Self::Ready { vector, .. } => Some(vector),
+            _ => None,
         }
-        other => AppError::Database(other),
-    })?;
-
-    let tags = fetch_tags_for_snippet(db, &snippet.id)?;
-    Ok(Snippet { tags, ..snippet })
+    }
 }
 
-#[tauri::command]
-pub async fn create_snippet(
-    db: State<'_, Database>,
-    input: CreateSnippetInput,
-) -> Result<Snippet, String> {
+/// Insert a snippet, embed it (best-effort) and record it in the journal
+pub(crate) async fn create_snippet_internal(
+    db: &Database,
+    journal: &Journal,
+    http: &OllamaHttp,
+    mut input: CreateSnippetInput,
+    draft_embedding: DraftEmbedding,
+) -> Result<Snippet, AppError> {
     let id = Uuid::new_v4().to_string();
+    let settings = db.settings().get_or_default();
+    let ollama = http.client(&settings);
+
+    // Canonicalize typed languages and detect missing ones
+    input.code_language = ai_language::resolve(
+        db,
+        &ollama,
+        &settings,
+        input.code_language.as_deref(),
+        input.code.as_deref().unwrap_or_default(),
+        None,
+    )
+    .await;
+    for file in input.files.iter_mut() {
+        file.language = ai_language::resolve(
+            db,
+            &ollama,
+            &settings,
+            file.language.as_deref(),
+            &file.content,
+            file.filename.as_deref(),
+        )
+        .await;
+    }
 
-    db.with_connection(|conn| {
+    db.with_transaction(|conn| {
         conn.execute(
-            "INSERT INTO snippets (id, title, problem, solution, code, code_language, reference_url)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO snippets (id, title, problem, solution, code_language, reference_url,
+                                   source_app, captured_at, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             rusqlite::params![
                 id,
                 input.title,
                 input.problem,
                 input.solution,
-                input.code,
                 input.code_language,
                 input.reference_url,
+                input.source_app,
+                input.captured_at,
+                input.status,
             ],
         )?;
 
+        if let Some(code) = input.code.as_deref().filter(|c| !c.is_empty()) {
+            let first = SnippetFileInput {
+                filename: None,
+                language: input.code_language.clone(),
+                content: code.to_string(),
+            };
+            insert_file(conn, &id, &first)?;
+        }
+        for file in &input.files {
+            insert_file(conn, &id, file)?;
+        }
+
         for tag_id in &input.tag_ids {
             conn.execute(
                 "INSERT OR IGNORE INTO snippet_tags (snippet_id, tag_id) VALUES (?1, ?2)",
@@ -115,37 +106,81 @@ pub async fn create_snippet(
         }
 
         Ok(())
-    })
-    .map_err(|e| AppError::Database(e).to_string())?;
+    })?;
 
-    let snippet = fetch_snippet_by_id(&db, &id).map_err(String::from)?;
+    let snippet = db.snippets().get(&id)?;
 
     // Best-effort embedding: silently skip if Ollama is unavailable
-    let settings = get_settings_internal(&db);
-    let _ = embedding::embed_snippet(
-        &db,
-        &snippet,
-        &settings.embedding_model,
-        &settings.ollama_base_url,
-    )
-    .await;
+    let _ = match draft_embedding {
+        DraftEmbedding::Ready { text, vector }
+            if text == embedding::document_text(db, &snippet) =>
+        {
+            db.embeddings()
+                .save_one(&id, &vector, &settings.embedding_model)
+                .map_err(String::from)
+        }
+        DraftEmbedding::Unavailable => Ok(()),
+        _ => embedding::embed_snippet(db, &ollama, &snippet, &settings.embedding_model).await,
+    };
+
+    journal.record_snippets(db, "Create snippet", vec![(id, None)]);
 
     Ok(snippet)
 }
 
+#[tauri::command]
+pub async fn create_snippet(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    input: CreateSnippetInput,
+) -> Result<Snippet, String> {
+    let draft_embedding = ensure_not_duplicate(&db, &http, &input)
+        .await
+        .map_err(String::from)?;
+    create_snippet_internal(&db, &journal, &http, input, draft_embedding)
+        .await
+        .map_err(String::from)
+}
+
+/// Refuse a new snippet that closely matches existing ones, unless the input opts out.
+/// Returns the draft's embedding for `create_snippet_internal` to reuse.
+pub(crate) async fn ensure_not_duplicate(
+    db: &Database,
+    http: &OllamaHttp,
+    input: &CreateSnippetInput,
+) -> Result<DraftEmbedding, AppError> {
+    if input.allow_duplicate {
+        return Ok(DraftEmbedding::Pending);
+    }
+    let check = check_duplicates_internal(db, http, input).await?;
+    if check.duplicates.is_empty() {
+        return Ok(check.embedding);
+    }
+    let titles: Vec<String> = check
+        .duplicates
+        .iter()
+        .map(|d| format!("'{}'", d.snippet.title))
+        .collect();
+    Err(AppError::Duplicate(titles.join(", ")))
+}
+
 #[tauri::command]
 pub fn get_snippet(db: State<'_, Database>, id: String) -> Result<Snippet, String> {
-    // Update last_accessed_at
+    get_snippet_internal(&db, &id).map_err(String::from)
+}
+
+/// Fetch a snippet for display, marking it as accessed
+pub(crate) fn get_snippet_internal(db: &Database, id: &str) -> Result<Snippet, AppError> {
     db.with_connection(|conn| {
         conn.execute(
             "UPDATE snippets SET last_accessed_at = CURRENT_TIMESTAMP WHERE id = ?1",
-            [&id],
+            [id],
         )?;
         Ok(())
-    })
-    .map_err(|e: rusqlite::Error| AppError::Database(e).to_string())?;
+    })?;
 
-    fetch_snippet_by_id(&db, &id).map_err(String::from)
+    db.snippets().get(id)
 }
 
 #[tauri::command]
@@ -153,105 +188,54 @@ pub fn list_snippets(
     db: State<'_, Database>,
     filter: Option<SnippetFilter>,
 ) -> Result<Vec<SnippetSummary>, String> {
-    list_snippets_internal(&db, filter)
-}
-
-fn list_snippets_internal(
-    db: &Database,
-    filter: Option<SnippetFilter>,
-) -> Result<Vec<SnippetSummary>, String> {
-    let filter = filter.unwrap_or_default();
-
-    let summaries = db
-        .with_connection(|conn| {
-            let mut sql = String::from(
-                "SELECT id, title, problem, code_language, SUBSTR(code, 1, 200), created_at, is_favorite, is_deleted, deleted_at, last_accessed_at 
-                 FROM snippets WHERE 1=1",
-            );
-            let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
-
-            if let Some(ref lang) = filter.language {
-                sql.push_str(" AND LOWER(code_language) = LOWER(?)");
-                params.push(Box::new(lang.clone()));
-            }
-
-            if let Some(ref search) = filter.search {
-                sql.push_str(" AND (title LIKE ? OR problem LIKE ?)");
-                let pattern = format!("%{search}%");
-                params.push(Box::new(pattern.clone()));
-                params.push(Box::new(pattern));
-            }
-
-            // Filter logic
-            if filter.trash_only.unwrap_or(false) {
-                sql.push_str(" AND is_deleted = 1");
-            } else {
-                if !filter.include_deleted.unwrap_or(false) {
-                    sql.push_str(" AND is_deleted = 0");
-                }
-                // Only apply favorites filter if not looking at trash (unless specifically combined, but usually mutually exclusive)
-                if filter.favorites_only.unwrap_or(false) {
-                    sql.push_str(" AND is_favorite = 1");
-                }
-            }
-
-            // Sort logic
-            if filter.recent_first.unwrap_or(false) {
-                sql.push_str(" ORDER BY last_accessed_at DESC");
-            } else {
-                sql.push_str(" ORDER BY created_at DESC");
-            }
-
-            let mut stmt = conn.prepare(&sql)?;
-            let param_refs: Vec<&dyn rusqlite::types::ToSql> =
-                params.iter().map(|p| p.as_ref()).collect();
-
-            let rows = stmt
-                .query_map(param_refs.as_slice(), |row| {
-                    Ok(SnippetSummary {
-                        id: row.get(0)?,
-                        title: row.get(1)?,
-                        problem: row.get(2)?,
-                        code_language: row.get(3)?,
-                        code_preview: row.get(4)?,
-                        tags: vec![],
-                        created_at: row.get(5)?,
-                        is_favorite: row.get(6)?,
-                        is_deleted: row.get(7)?,
-                        deleted_at: row.get(8)?,
-                        last_accessed_at: row.get(9)?,
-                    })
-                })?
-                .collect::<Result<Vec<_>, _>>()?;
-
-            Ok(rows)
-        })
-        .map_err(|e| AppError::Database(e).to_string())?;
-
-    // Fetch tags for each summary
-    let mut result = Vec::with_capacity(summaries.len());
-    for summary in summaries {
-        let tags = fetch_tags_for_snippet(db, &summary.id).map_err(String::from)?;
-        result.push(SnippetSummary { tags, ..summary });
-    }
-
-    Ok(result)
+    db.snippets()
+        .list(&filter.unwrap_or_default())
+        .map_err(String::from)
 }
 
 #[tauri::command]
 pub async fn update_snippet(
     db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
     id: String,
     input: UpdateSnippetInput,
 ) -> Result<Snippet, String> {
+    update_snippet_internal(&db, &journal, &http, id, input)
+        .await
+        .map_err(String::from)
+}
+
+/// Apply a partial update, re-embed changed content (best-effort) and record it in the journal
+pub(crate) async fn update_snippet_internal(
+    db: &Database,
+    journal: &Journal,
+    http: &OllamaHttp,
+    id: String,
+    input: UpdateSnippetInput,
+) -> Result<Snippet, AppError> {
     // Verify snippet exists
-    fetch_snippet_by_id(&db, &id).map_err(String::from)?;
+    let current = db.snippets().get(&id)?;
+    let before = snapshot_snippets(db, std::slice::from_ref(&id))?;
+
+    // Canonicalize a typed language, blank meaning none; detect one when new code
+    // arrives without any
+    let mut input = input;
+    if let Some(ref language) = input.code_language {
+        input.code_language = Some(language::canonicalize(language).unwrap_or_default());
+    } else if let (Some(code), None) = (input.code.as_deref(), current.code_language.as_deref()) {
+        let settings = db.settings().get_or_default();
+        input.code_language =
+            ai_language::resolve(db, &http.client(&settings), &settings, None, code, None).await;
+    }
 
     // Check if content fields changed (triggers re-embedding)
-    let needs_reembed =
-        input.title.is_some() || input.problem.is_some() || input.solution.is_some();
+    let needs_reembed = input.title.is_some()
+        || input.problem.is_some()
+        || input.solution.is_some()
+        || input.code.is_some();
 
-    db.with_connection(|conn| {
+    db.with_transaction(|conn| {
         let mut sets = vec![];
         let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
 
@@ -267,18 +251,20 @@ pub async fn update_snippet(
             sets.push("solution = ?");
             params.push(Box::new(solution.clone()));
         }
-        if let Some(ref code) = input.code {
-            sets.push("code = ?");
-            params.push(Box::new(code.clone()));
-        }
         if let Some(ref code_language) = input.code_language {
             sets.push("code_language = ?");
-            params.push(Box::new(code_language.clone()));
+            params.push(Box::new(
+                Some(code_language.clone()).filter(|l| !l.is_empty()),
+            ));
         }
         if let Some(ref reference_url) = input.reference_url {
             sets.push("reference_url = ?");
             params.push(Box::new(reference_url.clone()));
         }
+        if let Some(status) = input.status {
+            sets.push("status = ?");
+            params.push(Box::new(status));
+        }
 
         if !sets.is_empty() {
             sets.push("updated_at = CURRENT_TIMESTAMP");
@@ -291,6 +277,11 @@ pub async fn update_snippet(
             conn.execute(&sql, param_refs.as_slice())?;
         }
 
+        if let Some(ref code) = input.code {
+            let language = input.code_language.as_deref().filter(|l| !l.is_empty());
+            set_first_file(conn, &id, code, language)?;
+        }
+
         // Update tags if provided
         if let Some(ref tag_ids) = input.tag_ids {
             conn.execute("DELETE FROM snippet_tags WHERE snippet_id = ?1", [&id])?;
@@ -303,45 +294,67 @@ pub async fn update_snippet(
         }
 
         Ok(())
-    })
-    .map_err(|e| AppError::Database(e).to_string())?;
+    })?;
 
-    let snippet = fetch_snippet_by_id(&db, &id).map_err(String::from)?;
+    let snippet = db.snippets().get(&id)?;
 
     // Re-embed if content fields changed
     if needs_reembed {
-        let settings = get_settings_internal(&db);
+        let settings = db.settings().get_or_default();
         let _ = embedding::embed_snippet(
-            &db,
+            db,
+            &http.client(&settings),
             &snippet,
             &settings.embedding_model,
-            &settings.ollama_base_url,
         )
         .await;
     }
 
+    journal.record_snippets(db, "Update snippet", before);
+
     Ok(snippet)
 }
 
 #[tauri::command]
-pub fn delete_snippet(db: State<'_, Database>, id: String) -> Result<(), String> {
+pub fn delete_snippet(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    id: String,
+) -> Result<(), String> {
+    delete_snippet_internal(&db, &journal, &id).map_err(String::from)
+}
+
+/// Move a snippet to the trash
+pub(crate) fn delete_snippet_internal(
+    db: &Database,
+    journal: &Journal,
+    id: &str,
+) -> Result<(), AppError> {
     // Verify snippet exists
-    fetch_snippet_by_id(&db, &id).map_err(String::from)?;
+    db.snippets().get(id)?;
+    let before = snapshot_snippets(db, &[id.to_string()])?;
 
     // Soft delete
     db.with_connection(|conn| {
         conn.execute(
             "UPDATE snippets SET is_deleted = 1, deleted_at = CURRENT_TIMESTAMP WHERE id = ?1",
-            [&id],
+            [id],
         )?;
         Ok(())
-    })
-    .map_err(|e| AppError::Database(e).to_string())
+    })?;
+
+    journal.record_snippets(db, "Delete snippet", before);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn toggle_favorite(db: State<'_, Database>, id: String) -> Result<Snippet, String> {
-    fetch_snippet_by_id(&db, &id).map_err(String::from)?;
+pub fn toggle_favorite(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    id: String,
+) -> Result<Snippet, String> {
+    db.snippets().get(&id).map_err(String::from)?;
+    let before = snapshot_snippets(&db, std::slice::from_ref(&id)).map_err(String::from)?;
 
     db.with_connection(|conn| {
         conn.execute(
@@ -352,12 +365,48 @@ pub fn toggle_favorite(db: State<'_, Database>, id: String) -> Result<Snippet, S
     })
     .map_err(|e| AppError::Database(e).to_string())?;
 
-    fetch_snippet_by_id(&db, &id).map_err(String::from)
+    journal.record_snippets(&db, "Toggle favorite", before);
+
+    db.snippets().get(&id).map_err(String::from)
+}
+
+/// Move a snippet out of the main list, search and chat without trashing it; set the
+/// status back with `update_snippet` to restore it
+#[tauri::command]
+pub fn archive_snippet(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    id: String,
+) -> Result<Snippet, String> {
+    archive_snippet_internal(&db, &journal, &id).map_err(String::from)
+}
+
+pub(crate) fn archive_snippet_internal(
+    db: &Database,
+    journal: &Journal,
+    id: &str,
+) -> Result<Snippet, AppError> {
+    db.snippets().get(id)?;
+    let before = snapshot_snippets(db, &[id.to_string()])?;
+    db.snippets().set_status(id, SnippetStatus::Archived)?;
+    journal.record_snippets(db, "Archive snippet", before);
+
+    db.snippets().get(id)
 }
 
 #[tauri::command]
-pub fn restore_snippet(db: State<'_, Database>, id: String) -> Result<Snippet, String> {
-    fetch_snippet_by_id(&db, &id).map_err(String::from)?;
+pub fn get_inbox_counts(db: State<'_, Database>) -> Result<InboxCounts, String> {
+    db.snippets().counts().map_err(String::from)
+}
+
+#[tauri::command]
+pub fn restore_snippet(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    id: String,
+) -> Result<Snippet, String> {
+    db.snippets().get(&id).map_err(String::from)?;
+    let before = snapshot_snippets(&db, std::slice::from_ref(&id)).map_err(String::from)?;
 
     db.with_connection(|conn| {
         conn.execute(
@@ -368,18 +417,28 @@ pub fn restore_snippet(db: State<'_, Database>, id: String) -> Result<Snippet, S
     })
     .map_err(|e| AppError::Database(e).to_string())?;
 
-    fetch_snippet_by_id(&db, &id).map_err(String::from)
+    journal.record_snippets(&db, "Restore snippet", before);
+
+    db.snippets().get(&id).map_err(String::from)
 }
 
 #[tauri::command]
-pub fn permanent_delete_snippet(db: State<'_, Database>, id: String) -> Result<(), String> {
-    fetch_snippet_by_id(&db, &id).map_err(String::from)?;
+pub fn permanent_delete_snippet(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    id: String,
+) -> Result<(), String> {
+    db.snippets().get(&id).map_err(String::from)?;
+    let before = snapshot_snippets(&db, std::slice::from_ref(&id)).map_err(String::from)?;
 
     db.with_connection(|conn| {
         conn.execute("DELETE FROM snippets WHERE id = ?1", [&id])?;
         Ok(())
     })
-    .map_err(|e| AppError::Database(e).to_string())
+    .map_err(|e| AppError::Database(e).to_string())?;
+
+    journal.record_snippets(&db, "Permanently delete snippet", before);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -430,11 +489,11 @@ mod tests {
 
     // Helper to reuse fetch logic without tauri::State
     fn fetch_snippet(db: &Database, id: &str) -> Result<Snippet, AppError> {
-        super::fetch_snippet_by_id(db, id)
+        db.snippets().get(id)
     }
 
     fn fetch_tags(db: &Database, snippet_id: &str) -> Vec<Tag> {
-        super::fetch_tags_for_snippet(db, snippet_id).unwrap()
+        db.tags().for_snippet(snippet_id).unwrap()
     }
 
     // ===== create_snippet tests =====
@@ -448,16 +507,14 @@ mod tests {
         let id = uuid::Uuid::new_v4().to_string();
         db.with_connection(|conn| {
             conn.execute(
-                "INSERT INTO snippets (id, title, problem, solution, code, code_language)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                rusqlite::params![
-                    id,
-                    "Test Title",
-                    "Test Problem",
-                    "Test Solution",
-                    "fn main() {}",
-                    "rust"
-                ],
+                "INSERT INTO snippets (id, title, problem, solution, code_language)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![id, "Test Title", "Test Problem", "Test Solution", "rust"],
+            )?;
+            conn.execute(
+                "INSERT INTO snippet_files (id, snippet_id, language, content, position)
+                 VALUES ('f1', ?1, 'rust', 'fn main() {}', 0)",
+                [&id],
             )?;
             conn.execute(
                 "INSERT INTO snippet_tags (snippet_id, tag_id) VALUES (?1, ?2)",
@@ -575,6 +632,7 @@ mod tests {
                             id: row.get(0)?,
                             title: row.get(1)?,
                             problem: row.get(2)?,
+                            summary: None,
                             code_language: row.get(3)?,
                             code_preview: None,
                             tags: vec![],
@@ -583,6 +641,7 @@ mod tests {
                             is_deleted: row.get(6)?,
                             deleted_at: row.get(7)?,
                             last_accessed_at: row.get(8)?,
+                            status: SnippetStatus::Active,
                         })
                     })?
                     .collect::<Result<Vec<_>, _>>()?;
@@ -613,6 +672,7 @@ mod tests {
                             id: row.get(0)?,
                             title: row.get(1)?,
                             problem: row.get(2)?,
+                            summary: None,
                             code_language: row.get(3)?,
                             code_preview: None,
                             tags: vec![],
@@ -621,6 +681,7 @@ mod tests {
                             is_deleted: row.get(6)?,
                             deleted_at: row.get(7)?,
                             last_accessed_at: row.get(8)?,
+                            status: SnippetStatus::Active,
                         })
                     })?
                     .collect::<Result<Vec<_>, _>>()?;
@@ -658,6 +719,7 @@ mod tests {
                             id: row.get(0)?,
                             title: row.get(1)?,
                             problem: row.get(2)?,
+                            summary: None,
                             code_language: row.get(3)?,
                             code_preview: None,
                             tags: vec![],
@@ -666,6 +728,7 @@ mod tests {
                             is_deleted: row.get(6)?,
                             deleted_at: row.get(7)?,
                             last_accessed_at: row.get(8)?,
+                            status: SnippetStatus::Active,
                         })
                     })?
                     .collect::<Result<Vec<_>, _>>()?;
@@ -701,6 +764,49 @@ mod tests {
         assert_eq!(snippet.title, "New Title");
     }
 
+    #[tokio::test]
+    async fn test_update_snippet_canonicalizes_language_and_clears_blank() {
+        // Given
+        let db = setup_db();
+        let id = create_test_snippet(&db, "Title", &[]);
+        let (journal, http) = (
+            crate::db::Journal::default(),
+            crate::ai::ollama::OllamaHttp::default(),
+        );
+        let input = |language: &str| UpdateSnippetInput {
+            title: None,
+            problem: None,
+            solution: None,
+            code: None,
+            code_language: Some(language.to_string()),
+            reference_url: None,
+            tag_ids: None,
+            status: None,
+        };
+
+        // When
+        let aliased = super::update_snippet_internal(&db, &journal, &http, id.clone(), input("Py"))
+            .await
+            .unwrap();
+        let blank = super::update_snippet_internal(&db, &journal, &http, id.clone(), input("  "))
+            .await
+            .unwrap();
+
+        // Then
+        assert_eq!(aliased.code_language.as_deref(), Some("python"));
+        assert_eq!(blank.code_language, None);
+        let stored: Option<String> = db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT code_language FROM snippets WHERE id = ?1",
+                    [&id],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap();
+        assert_eq!(stored, None);
+    }
+
     // ===== delete_snippet tests =====
 
     #[test]
@@ -791,6 +897,40 @@ mod tests {
         assert!(snippet.deleted_at.is_none());
     }
 
+    #[test]
+    fn test_archive_snippet_leaves_main_list_and_can_be_undone() {
+        // Given
+        let db = setup_db();
+        let journal = crate::db::Journal::default();
+        let id = create_test_snippet(&db, "Old approach", &[]);
+        db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE snippets SET updated_at = '2020-01-01 00:00:00' WHERE id = ?1",
+                [&id],
+            )
+        })
+        .unwrap();
+
+        // When
+        let archived = super::archive_snippet_internal(&db, &journal, &id).unwrap();
+
+        // Then
+        assert_eq!(archived.status, SnippetStatus::Archived);
+        assert_ne!(archived.updated_at, "2020-01-01 00:00:00");
+        assert!(!archived.is_deleted);
+        assert!(db
+            .snippets()
+            .list(&SnippetFilter::default())
+            .unwrap()
+            .is_empty());
+
+        journal.undo(&db).unwrap();
+        assert_eq!(
+            fetch_snippet(&db, &id).unwrap().status,
+            SnippetStatus::Active
+        );
+    }
+
     #[test]
     fn test_list_snippets_filter_favorites() {
         // Given
@@ -806,14 +946,13 @@ mod tests {
         .unwrap();
 
         // When
-        let result = super::list_snippets_internal(
-            &db,
-            Some(SnippetFilter {
+        let result = db
+            .snippets()
+            .list(&SnippetFilter {
                 favorites_only: Some(true),
                 ..Default::default()
-            }),
-        )
-        .unwrap();
+            })
+            .unwrap();
 
         // Then
         assert_eq!(result.len(), 1);
@@ -835,19 +974,18 @@ mod tests {
         .unwrap();
 
         // When - list active (default)
-        let result_active = super::list_snippets_internal(&db, None).unwrap();
+        let result_active = db.snippets().list(&SnippetFilter::default()).unwrap();
         assert_eq!(result_active.len(), 1);
         assert_eq!(result_active[0].title, "Active");
 
         // When - list trash
-        let result_trash = super::list_snippets_internal(
-            &db,
-            Some(SnippetFilter {
+        let result_trash = db
+            .snippets()
+            .list(&SnippetFilter {
                 trash_only: Some(true),
                 ..Default::default()
-            }),
-        )
-        .unwrap();
+            })
+            .unwrap();
         assert_eq!(result_trash.len(), 1);
         assert_eq!(result_trash[0].title, "Deleted");
     }
@@ -874,18 +1012,49 @@ mod tests {
         .unwrap();
 
         // When
-        let result = super::list_snippets_internal(
-            &db,
-            Some(SnippetFilter {
+        let result = db
+            .snippets()
+            .list(&SnippetFilter {
                 recent_first: Some(true),
                 ..Default::default()
-            }),
-        )
-        .unwrap();
+            })
+            .unwrap();
 
         // Then
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].title, "New Access");
         assert_eq!(result[1].title, "Old Access");
     }
+
+    #[test]
+    fn test_list_snippets_search_and_preview_use_files() {
+        // Given
+        let db = setup_db();
+        let id = create_test_snippet(&db, "Deploy", &[]);
+        create_test_snippet(&db, "Unrelated", &[]);
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippet_files (id, snippet_id, filename, content, position)
+                 VALUES ('f2', ?1, 'compose.yml', 'services: {}', 1),
+                        ('f1', ?1, 'Dockerfile', 'FROM rust:1', 0)",
+                [&id],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        // When - the term only appears in the second file
+        let result = db
+            .snippets()
+            .list(&SnippetFilter {
+                search: Some("services".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Then - the preview comes from the first file
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].title, "Deploy");
+        assert_eq!(result[0].code_preview.as_deref(), Some("FROM rust:1"));
+    }
 }
@@ -0,0 +1,514 @@
+use rusqlite::{Connection, OptionalExtension};
+use tauri::State;
+use uuid::Uuid;
+
+use crate::ai::embedding;
+use crate::ai::language as ai_language;
+use crate::ai::ollama::OllamaHttp;
+use crate::db::journal::{snapshot_snippets, SnippetState};
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::language;
+use crate::models::{Snippet, SnippetFileInput, UpdateSnippetFileInput};
+
+/// Append a file after the snippet's existing files
+pub(crate) fn insert_file(
+    conn: &Connection,
+    snippet_id: &str,
+    input: &SnippetFileInput,
+) -> Result<String, rusqlite::Error> {
+    let id = Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO snippet_files (id, snippet_id, filename, language, content, position)
+         VALUES (?1, ?2, ?3, ?4, ?5,
+                 (SELECT COALESCE(MAX(position) + 1, 0) FROM snippet_files WHERE snippet_id = ?2))",
+        rusqlite::params![
+            id,
+            snippet_id,
+            input.filename,
+            input.language,
+            input.content
+        ],
+    )?;
+    Ok(id)
+}
+
+/// Renumber positions so they are contiguous from 0
+fn compact_positions(conn: &Connection, snippet_id: &str) -> Result<(), rusqlite::Error> {
+    let mut stmt =
+        conn.prepare("SELECT id FROM snippet_files WHERE snippet_id = ?1 ORDER BY position, id")?;
+    let ids = stmt
+        .query_map([snippet_id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    for (position, id) in ids.iter().enumerate() {
+        conn.execute(
+            "UPDATE snippet_files SET position = ?1 WHERE id = ?2",
+            rusqlite::params![position as i32, id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Replace the content of the first file, creating it if needed.
+/// An empty `code` removes the first file.
+pub(crate) fn set_first_file(
+    conn: &Connection,
+    snippet_id: &str,
+    code: &str,
+    language: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    let first_id: Option<String> = conn
+        .query_row(
+            "SELECT id FROM snippet_files WHERE snippet_id = ?1 ORDER BY position, id LIMIT 1",
+            [snippet_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match (first_id, code.is_empty()) {
+        (Some(id), true) => {
+            conn.execute("DELETE FROM snippet_files WHERE id = ?1", [&id])?;
+            compact_positions(conn, snippet_id)?;
+        }
+        (Some(id), false) => {
+            conn.execute(
+                "UPDATE snippet_files
+                 SET content = ?1, language = COALESCE(?2, language), updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?3",
+                rusqlite::params![code, language, id],
+            )?;
+        }
+        (None, true) => {}
+        (None, false) => {
+            let input = SnippetFileInput {
+                filename: None,
+                language: language.map(String::from),
+                content: code.to_string(),
+            };
+            insert_file(conn, snippet_id, &input)?;
+        }
+    }
+    Ok(())
+}
+
+fn touch_snippet(conn: &Connection, snippet_id: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE snippets SET updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+        [snippet_id],
+    )?;
+    Ok(())
+}
+
+fn snippet_id_for_file(db: &Database, file_id: &str) -> Result<String, AppError> {
+    db.with_connection(|conn| {
+        conn.query_row(
+            "SELECT snippet_id FROM snippet_files WHERE id = ?1",
+            [file_id],
+            |row| row.get(0),
+        )
+    })
+    .map_err(|e| match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            AppError::NotFound(format!("Snippet file with id '{file_id}' not found"))
+        }
+        other => AppError::Database(other),
+    })
+}
+
+fn add_file_internal(
+    db: &Database,
+    snippet_id: &str,
+    input: &SnippetFileInput,
+) -> Result<String, AppError> {
+    db.snippets().get(snippet_id)?;
+    db.with_transaction(|conn| {
+        let id = insert_file(conn, snippet_id, input)?;
+        touch_snippet(conn, snippet_id)?;
+        Ok(id)
+    })
+    .map_err(AppError::from)
+}
+
+fn update_file_internal(
+    db: &Database,
+    file_id: &str,
+    input: &UpdateSnippetFileInput,
+) -> Result<String, AppError> {
+    let snippet_id = snippet_id_for_file(db, file_id)?;
+
+    let mut sets = vec![];
+    let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
+
+    if let Some(ref filename) = input.filename {
+        sets.push("filename = ?");
+        params.push(Box::new(filename.clone()));
+    }
+    if let Some(ref language) = input.language {
+        sets.push("language = ?");
+        params.push(Box::new(Some(language.clone()).filter(|l| !l.is_empty())));
+    }
+    if let Some(ref content) = input.content {
+        sets.push("content = ?");
+        params.push(Box::new(content.clone()));
+    }
+
+    if sets.is_empty() {
+        return Ok(snippet_id);
+    }
+
+    sets.push("updated_at = CURRENT_TIMESTAMP");
+    params.push(Box::new(file_id.to_string()));
+
+    db.with_transaction(|conn| {
+        let sql = format!("UPDATE snippet_files SET {} WHERE id = ?", sets.join(", "));
+        let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+            params.iter().map(|p| p.as_ref()).collect();
+        conn.execute(&sql, param_refs.as_slice())?;
+        touch_snippet(conn, &snippet_id)
+    })?;
+
+    Ok(snippet_id)
+}
+
+fn reorder_files_internal(
+    db: &Database,
+    snippet_id: &str,
+    file_ids: &[String],
+) -> Result<(), AppError> {
+    let mut current: Vec<String> = db
+        .snippets()
+        .files(snippet_id)?
+        .into_iter()
+        .map(|f| f.id)
+        .collect();
+    current.sort();
+    let mut requested = file_ids.to_vec();
+    requested.sort();
+    if current != requested {
+        return Err(AppError::Validation(
+            "File order must list every file of the snippet exactly once".to_string(),
+        ));
+    }
+
+    db.with_transaction(|conn| {
+        for (position, id) in file_ids.iter().enumerate() {
+            conn.execute(
+                "UPDATE snippet_files SET position = ?1 WHERE id = ?2",
+                rusqlite::params![position as i32, id],
+            )?;
+        }
+        touch_snippet(conn, snippet_id)
+    })
+    .map_err(AppError::from)
+}
+
+fn remove_file_internal(db: &Database, file_id: &str) -> Result<String, AppError> {
+    let snippet_id = snippet_id_for_file(db, file_id)?;
+    db.with_transaction(|conn| {
+        conn.execute("DELETE FROM snippet_files WHERE id = ?1", [file_id])?;
+        compact_positions(conn, &snippet_id)?;
+        touch_snippet(conn, &snippet_id)
+    })?;
+    Ok(snippet_id)
+}
+
+/// Re-embed the snippet (best-effort), record the change and return the fresh snippet
+async fn finish_file_change(
+    db: &Database,
+    journal: &Journal,
+    http: &OllamaHttp,
+    label: &str,
+    snippet_id: &str,
+    before: Vec<(String, Option<SnippetState>)>,
+) -> Result<Snippet, String> {
+    let snippet = db.snippets().get(snippet_id).map_err(String::from)?;
+
+    let settings = db.settings().get_or_default();
+    let _ = embedding::embed_snippet(
+        db,
+        &http.client(&settings),
+        &snippet,
+        &settings.embedding_model,
+    )
+    .await;
+
+    journal.record_snippets(db, label, before);
+
+    Ok(snippet)
+}
+
+#[tauri::command]
+pub async fn add_snippet_file(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    snippet_id: String,
+    mut input: SnippetFileInput,
+) -> Result<Snippet, String> {
+    let settings = db.settings().get_or_default();
+    input.language = ai_language::resolve(
+        &db,
+        &http.client(&settings),
+        &settings,
+        input.language.as_deref(),
+        &input.content,
+        input.filename.as_deref(),
+    )
+    .await;
+
+    let before = snapshot_snippets(&db, std::slice::from_ref(&snippet_id)).map_err(String::from)?;
+    add_file_internal(&db, &snippet_id, &input).map_err(String::from)?;
+    finish_file_change(&db, &journal, &http, "Add file", &snippet_id, before).await
+}
+
+#[tauri::command]
+pub async fn update_snippet_file(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    id: String,
+    mut input: UpdateSnippetFileInput,
+) -> Result<Snippet, String> {
+    let snippet_id = snippet_id_for_file(&db, &id).map_err(String::from)?;
+
+    // Canonicalize a typed language, blank meaning none; detect one for new content in an
+    // unlabelled file
+    if let Some(ref language) = input.language {
+        input.language = Some(language::canonicalize(language).unwrap_or_default());
+    } else if let Some(ref content) = input.content {
+        let files = db.snippets().files(&snippet_id).map_err(String::from)?;
+        let file = files.iter().find(|f| f.id == id);
+        if let Some(file) = file.filter(|f| f.language.is_none()) {
+            let settings = db.settings().get_or_default();
+            let filename = input.filename.as_deref().or(file.filename.as_deref());
+            input.language = ai_language::resolve(
+                &db,
+                &http.client(&settings),
+                &settings,
+                None,
+                content,
+                filename,
+            )
+            .await;
+        }
+    }
+    let before = snapshot_snippets(&db, std::slice::from_ref(&snippet_id)).map_err(String::from)?;
+    update_file_internal(&db, &id, &input).map_err(String::from)?;
+    finish_file_change(&db, &journal, &http, "Edit file", &snippet_id, before).await
+}
+
+#[tauri::command]
+pub async fn reorder_snippet_files(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    snippet_id: String,
+    file_ids: Vec<String>,
+) -> Result<Snippet, String> {
+    let before = snapshot_snippets(&db, std::slice::from_ref(&snippet_id)).map_err(String::from)?;
+    reorder_files_internal(&db, &snippet_id, &file_ids).map_err(String::from)?;
+    finish_file_change(&db, &journal, &http, "Reorder files", &snippet_id, before).await
+}
+
+#[tauri::command]
+pub async fn remove_snippet_file(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    id: String,
+) -> Result<Snippet, String> {
+    let snippet_id = snippet_id_for_file(&db, &id).map_err(String::from)?;
+    let before = snapshot_snippets(&db, std::slice::from_ref(&snippet_id)).map_err(String::from)?;
+    remove_file_internal(&db, &id).map_err(String::from)?;
+    finish_file_change(&db, &journal, &http, "Remove file", &snippet_id, before).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Deploy', 'Problem')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    fn file_input(filename: &str, content: &str) -> SnippetFileInput {
+        SnippetFileInput {
+            filename: Some(filename.to_string()),
+            language: None,
+            content: content.to_string(),
+        }
+    }
+
+    fn filenames(db: &Database) -> Vec<String> {
+        db.snippets()
+            .files("s1")
+            .unwrap()
+            .into_iter()
+            .map(|f| f.filename.unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn test_add_files_appends_in_order() {
+        // Given
+        let db = setup_db();
+
+        // When
+        add_file_internal(&db, "s1", &file_input("Dockerfile", "FROM rust:1")).unwrap();
+        add_file_internal(&db, "s1", &file_input("compose.yml", "services: {}")).unwrap();
+
+        // Then
+        let files = db.snippets().files("s1").unwrap();
+        assert_eq!(filenames(&db), vec!["Dockerfile", "compose.yml"]);
+        assert_eq!(files[1].position, 1);
+
+        // The first file backs the legacy `code` field
+        let snippet = db.snippets().get("s1").unwrap();
+        assert_eq!(snippet.code.as_deref(), Some("FROM rust:1"));
+    }
+
+    #[test]
+    fn test_add_file_to_missing_snippet_fails() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let result = add_file_internal(&db, "missing", &file_input("a.sh", "ls"));
+
+        // Then
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_update_file() {
+        // Given
+        let db = setup_db();
+        let id = add_file_internal(&db, "s1", &file_input("run.sh", "ls")).unwrap();
+
+        // When
+        update_file_internal(
+            &db,
+            &id,
+            &UpdateSnippetFileInput {
+                filename: None,
+                language: Some("bash".to_string()),
+                content: Some("ls -la".to_string()),
+            },
+        )
+        .unwrap();
+
+        // Then
+        let file = &db.snippets().files("s1").unwrap()[0];
+        assert_eq!(file.filename.as_deref(), Some("run.sh"));
+        assert_eq!(file.language.as_deref(), Some("bash"));
+        assert_eq!(file.content, "ls -la");
+    }
+
+    #[test]
+    fn test_update_file_with_empty_language_clears_it() {
+        // Given
+        let db = setup_db();
+        let input = SnippetFileInput {
+            language: Some("bash".to_string()),
+            ..file_input("run.sh", "ls")
+        };
+        let id = add_file_internal(&db, "s1", &input).unwrap();
+
+        // When
+        update_file_internal(
+            &db,
+            &id,
+            &UpdateSnippetFileInput {
+                filename: None,
+                language: Some(String::new()),
+                content: None,
+            },
+        )
+        .unwrap();
+
+        // Then
+        assert_eq!(db.snippets().files("s1").unwrap()[0].language, None);
+    }
+
+    #[test]
+    fn test_reorder_files() {
+        // Given
+        let db = setup_db();
+        let a = add_file_internal(&db, "s1", &file_input("a", "1")).unwrap();
+        let b = add_file_internal(&db, "s1", &file_input("b", "2")).unwrap();
+        let c = add_file_internal(&db, "s1", &file_input("c", "3")).unwrap();
+
+        // When
+        reorder_files_internal(&db, "s1", &[c, a, b]).unwrap();
+
+        // Then
+        assert_eq!(filenames(&db), vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_reorder_rejects_incomplete_list() {
+        // Given
+        let db = setup_db();
+        let a = add_file_internal(&db, "s1", &file_input("a", "1")).unwrap();
+        add_file_internal(&db, "s1", &file_input("b", "2")).unwrap();
+
+        // When
+        let result = reorder_files_internal(&db, "s1", &[a]);
+
+        // Then
+        assert!(matches!(result, Err(AppError::Validation(_))));
+        assert_eq!(filenames(&db), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_remove_file_compacts_positions() {
+        // Given
+        let db = setup_db();
+        let a = add_file_internal(&db, "s1", &file_input("a", "1")).unwrap();
+        add_file_internal(&db, "s1", &file_input("b", "2")).unwrap();
+
+        // When
+        remove_file_internal(&db, &a).unwrap();
+
+        // Then
+        let files = db.snippets().files("s1").unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].filename.as_deref(), Some("b"));
+        assert_eq!(files[0].position, 0);
+    }
+
+    #[test]
+    fn test_set_first_file_creates_updates_and_removes() {
+        // Given
+        let db = setup_db();
+
+        // When / Then: creates the first file
+        db.with_connection(|conn| set_first_file(conn, "s1", "echo hi", Some("bash")))
+            .unwrap();
+        let files = db.snippets().files("s1").unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].language.as_deref(), Some("bash"));
+
+        // Updates content, keeping the language when none is given
+        db.with_connection(|conn| set_first_file(conn, "s1", "echo bye", None))
+            .unwrap();
+        let files = db.snippets().files("s1").unwrap();
+        assert_eq!(files[0].content, "echo bye");
+        assert_eq!(files[0].language.as_deref(), Some("bash"));
+
+        // An empty string removes it
+        db.with_connection(|conn| set_first_file(conn, "s1", "", None))
+            .unwrap();
+        assert!(db.snippets().files("s1").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+use tauri::State;
+
+use crate::ai::ollama::{GenerateOptions, OllamaHttp};
+use crate::ai::{prompts, summary};
+use crate::commands::prompt::render_prompt;
+use crate::db::journal::snapshot_snippets;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::models::{AiFeature, BulkItemResult, Snippet};
+
+fn save_summary(db: &Database, id: &str, text: &str) -> Result<(), AppError> {
+    db.with_connection(|conn| {
+        conn.execute(
+            "UPDATE snippets SET summary = ?1 WHERE id = ?2",
+            rusqlite::params![text, id],
+        )?;
+        Ok(())
+    })
+    .map_err(AppError::from)
+}
+
+/// Snippets outside the trash that have no summary yet, oldest first
+fn missing_summary_ids(db: &Database) -> Result<Vec<String>, AppError> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM snippets
+             WHERE is_deleted = 0 AND (summary IS NULL OR TRIM(summary) = '')
+             ORDER BY created_at, id",
+        )?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(ids)
+    })
+    .map_err(AppError::from)
+}
+
+/// Suggest a title for a snippet; the snippet itself is not changed
+#[tauri::command]
+pub async fn generate_title(
+    db: State<'_, Database>,
+    http: State<'_, OllamaHttp>,
+    id: String,
+) -> Result<String, String> {
+    let snippet = db.snippets().get(&id).map_err(String::from)?;
+    let settings = db.settings().get_or_default();
+    let prompt = render_prompt(
+        &db,
+        prompts::GENERATE_TITLE,
+        &prompts::snippet_variables(&snippet),
+    );
+    summary::generate_title(
+        &http.client(&settings),
+        &prompt,
+        settings.model_for(AiFeature::Summary),
+        &GenerateOptions::from_settings(&settings),
+    )
+    .await
+}
+
+/// Generate and store a summary for a snippet
+#[tauri::command]
+pub async fn summarize_snippet(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    id: String,
+) -> Result<Snippet, String> {
+    let snippet = db.snippets().get(&id).map_err(String::from)?;
+    let settings = db.settings().get_or_default();
+    let prompt = render_prompt(
+        &db,
+        prompts::SUMMARIZE_SNIPPET,
+        &prompts::snippet_variables(&snippet),
+    );
+    let text = summary::generate_summary(
+        &http.client(&settings),
+        &prompt,
+        settings.model_for(AiFeature::Summary),
+        &GenerateOptions::from_settings(&settings),
+    )
+    .await?;
+
+    let before = snapshot_snippets(&db, std::slice::from_ref(&id)).map_err(String::from)?;
+    save_summary(&db, &id, &text).map_err(String::from)?;
+    journal.record_snippets(&db, "Summarize snippet", before);
+
+    db.snippets().get(&id).map_err(String::from)
+}
+
+/// Summarize every snippet that has no summary yet. Failures are reported per snippet;
+/// the job stops at the first one that leaves Ollama unreachable, without attempting
+/// the remaining snippets.
+#[tauri::command]
+pub async fn summarize_missing_snippets(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+) -> Result<Vec<BulkItemResult>, String> {
+    summarize_missing_internal(&db, &journal, &http).await
+}
+
+async fn summarize_missing_internal(
+    db: &Database,
+    journal: &Journal,
+    http: &OllamaHttp,
+) -> Result<Vec<BulkItemResult>, String> {
+    let ids = missing_summary_ids(db).map_err(String::from)?;
+    let settings = db.settings().get_or_default();
+    let options = GenerateOptions::from_settings(&settings);
+    let ollama = http.client(&settings);
+
+    let mut results = Vec::with_capacity(ids.len());
+    let mut before = vec![];
+    for (index, id) in ids.iter().enumerate() {
+        let generated = match db.snippets().get(id) {
+            Ok(snippet) => {
+                let prompt = render_prompt(
+                    db,
+                    prompts::SUMMARIZE_SNIPPET,
+                    &prompts::snippet_variables(&snippet),
+                );
+                summary::generate_summary(
+                    &ollama,
+                    &prompt,
+                    settings.model_for(AiFeature::Summary),
+                    &options,
+                )
+                .await
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        let unreachable = generated.is_err() && !ollama.check_connection().await.unwrap_or(false);
+
+        // Snapshot right before saving, so the journal only holds snippets this job changed
+        let outcome = match generated {
+            Ok(text) => snapshot_snippets(db, std::slice::from_ref(id))
+                .and_then(|snapshot| save_summary(db, id, &text).map(|()| before.extend(snapshot)))
+                .map_err(String::from),
+            Err(e) => Err(e),
+        };
+
+        results.push(BulkItemResult {
+            id: id.clone(),
+            success: outcome.is_ok(),
+            changed: outcome.is_ok(),
+            error: outcome.err(),
+        });
+        if unreachable {
+            // Report the rest as not attempted rather than leaving them out
+            results.extend(ids[index + 1..].iter().map(|id| BulkItemResult {
+                id: id.clone(),
+                success: false,
+                changed: false,
+                error: Some("Ollama unreachable".to_string()),
+            }));
+            break;
+        }
+    }
+
+    journal.record_snippets(
+        db,
+        &format!("Summarize {} snippet(s)", before.len()),
+        before,
+    );
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_summary_ids_skips_summarized_and_trashed() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem, created_at) VALUES ('a', 'a', 'p', '2026-01-01');
+                 INSERT INTO snippets (id, title, problem, created_at, summary) VALUES ('b', 'b', 'p', '2026-01-02', 'Done');
+                 INSERT INTO snippets (id, title, problem, created_at, summary) VALUES ('c', 'c', 'p', '2026-01-03', '  ');
+                 INSERT INTO snippets (id, title, problem, created_at, is_deleted) VALUES ('d', 'd', 'p', '2026-01-04', 1);",
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        // When
+        let ids = missing_summary_ids(&db).unwrap();
+
+        // Then
+        assert_eq!(ids, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_save_summary() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('a', 'a', 'p')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        // When
+        save_summary(&db, "a", "Short summary").unwrap();
+
+        // Then
+        let snippet = db.snippets().get("a").unwrap();
+        assert_eq!(snippet.summary.as_deref(), Some("Short summary"));
+        assert!(missing_summary_ids(&db).unwrap().is_empty());
+    }
+
+    fn missing_summaries_db(base_url: &str) -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem, created_at) VALUES
+                    ('a', 'a', 'p', '2026-01-01'),
+                    ('b', 'b', 'p', '2026-01-02'),
+                    ('c', 'c', 'p', '2026-01-03');
+                 UPDATE settings SET ollama_max_retries = 0;",
+            )?;
+            conn.execute("UPDATE settings SET ollama_base_url = ?1", [base_url])?;
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    fn response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// Stand-in Ollama answering one canned response per connection, in order
+    async fn serve(responses: Vec<String>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = [0u8; 8192];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    fn summaries(db: &Database) -> Vec<Option<String>> {
+        ["a", "b", "c"]
+            .iter()
+            .map(|id| db.snippets().get(id).unwrap().summary)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_summarize_missing_records_only_summarized_snippets() {
+        // Given - the model fails on 'b' while Ollama stays reachable
+        let base = serve(vec![
+            response("200 OK", r#"{"response":"Summary a"}"#),
+            response("500 Internal Server Error", "{}"),
+            response("200 OK", r#"{"models":[]}"#),
+            response("200 OK", r#"{"response":"Summary c"}"#),
+        ])
+        .await;
+        let (db, journal, http) = (
+            missing_summaries_db(&base),
+            Journal::default(),
+            OllamaHttp::default(),
+        );
+
+        // When
+        let results = summarize_missing_internal(&db, &journal, &http)
+            .await
+            .unwrap();
+
+        // Then
+        let changed: Vec<bool> = results.iter().map(|r| r.changed).collect();
+        assert_eq!(changed, vec![true, false, true]);
+        assert_eq!(
+            summaries(&db),
+            vec![
+                Some("Summary a".to_string()),
+                None,
+                Some("Summary c".to_string())
+            ]
+        );
+        assert_eq!(
+            journal.undo(&db).unwrap().as_deref(),
+            Some("Summarize 2 snippet(s)")
+        );
+        assert_eq!(summaries(&db), vec![None, None, None]);
+    }
+
+    #[tokio::test]
+    async fn test_summarize_missing_stops_when_ollama_is_unreachable() {
+        // Given - nothing listens on the discard port
+        let (db, journal, http) = (
+            missing_summaries_db("http://127.0.0.1:9"),
+            Journal::default(),
+            OllamaHttp::default(),
+        );
+
+        // When
+        let results = summarize_missing_internal(&db, &journal, &http)
+            .await
+            .unwrap();
+
+        // Then - the snippets never attempted are still reported
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(|r| !r.success && !r.changed));
+        assert!(results[1..]
+            .iter()
+            .all(|r| r.error.as_deref() == Some("Ollama unreachable")));
+        assert!(journal.state().undo_label.is_none());
+    }
+}
@@ -1,7 +1,7 @@
 use tauri::State;
-use uuid::Uuid;
 
-use crate::db::Database;
+use crate::db::journal::snapshot_tag;
+use crate::db::{Database, Journal};
 use crate::errors::AppError;
 use crate::models::Tag;
 
@@ -21,57 +21,54 @@ fn fetch_all_tags(db: &Database) -> Result<Vec<Tag>, AppError> {
     .map_err(AppError::from)
 }
 
-fn insert_tag(db: &Database, name: &str) -> Result<Tag, AppError> {
-    // Check if tag with same name already exists
-    let existing = db.with_connection(|conn| {
-        conn.query_row(
-            "SELECT id, name FROM tags WHERE name = ?1",
-            [name],
-            |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                })
-            },
-        )
-    });
-
-    if let Ok(tag) = existing {
-        return Ok(tag);
-    }
-
-    let id = Uuid::new_v4().to_string();
-    db.with_connection(|conn| {
-        conn.execute(
-            "INSERT INTO tags (id, name) VALUES (?1, ?2)",
-            rusqlite::params![id, name],
-        )?;
-        Ok(())
-    })
-    .map_err(AppError::from)?;
-
-    Ok(Tag {
-        id,
-        name: name.to_string(),
-    })
-}
-
 #[tauri::command]
 pub fn list_tags(db: State<'_, Database>) -> Result<Vec<Tag>, String> {
     fetch_all_tags(&db).map_err(String::from)
 }
 
 #[tauri::command]
-pub fn create_tag(db: State<'_, Database>, name: String) -> Result<Tag, String> {
+pub fn create_tag(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    name: String,
+) -> Result<Tag, String> {
+    create_tag_internal(&db, &journal, &name).map_err(String::from)
+}
+
+/// Get or create a tag by trimmed name; only a new tag is recorded in the journal
+pub(crate) fn create_tag_internal(
+    db: &Database,
+    journal: &Journal,
+    name: &str,
+) -> Result<Tag, AppError> {
     let trimmed = name.trim().to_string();
     if trimmed.is_empty() {
-        return Err("Tag name cannot be empty".to_string());
+        return Err(AppError::Validation("Tag name cannot be empty".to_string()));
     }
-    insert_tag(&db, &trimmed).map_err(String::from)
+
+    let is_new = db.with_connection(|conn| {
+        conn.query_row(
+            "SELECT NOT EXISTS(SELECT 1 FROM tags WHERE name = ?1)",
+            [&trimmed],
+            |row| row.get::<_, bool>(0),
+        )
+    })?;
+
+    let tag = db.tags().get_or_create(&trimmed)?;
+
+    if is_new {
+        journal.record_tag(db, "Create tag", &tag.id, None);
+    }
+
+    Ok(tag)
 }
 
 #[tauri::command]
-pub fn delete_tag(db: State<'_, Database>, id: String) -> Result<(), String> {
+pub fn delete_tag(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    id: String,
+) -> Result<(), String> {
     // Verify tag exists
     let exists = db
         .with_connection(|conn| {
@@ -87,11 +84,16 @@ pub fn delete_tag(db: State<'_, Database>, id: String) -> Result<(), String> {
         return Err(AppError::NotFound(format!("Tag with id '{id}' not found")).to_string());
     }
 
+    let before = snapshot_tag(&db, &id).map_err(String::from)?;
+
     db.with_connection(|conn| {
         conn.execute("DELETE FROM tags WHERE id = ?1", [&id])?;
         Ok(())
     })
-    .map_err(|e| AppError::Database(e).to_string())
+    .map_err(|e| AppError::Database(e).to_string())?;
+
+    journal.record_tag(&db, "Delete tag", &id, before);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -158,7 +160,7 @@ mod tests {
         let db = setup_db();
 
         // When
-        let tag = super::insert_tag(&db, "rust").unwrap();
+        let tag = db.tags().get_or_create("rust").unwrap();
 
         // Then
         assert_eq!(tag.name, "rust");
@@ -173,10 +175,10 @@ mod tests {
     fn test_create_tag_duplicate_returns_existing() {
         // Given
         let db = setup_db();
-        let first = super::insert_tag(&db, "rust").unwrap();
+        let first = db.tags().get_or_create("rust").unwrap();
 
         // When - create same tag again
-        let second = super::insert_tag(&db, "rust").unwrap();
+        let second = db.tags().get_or_create("rust").unwrap();
 
         // Then - should return the same tag, not create a duplicate
         assert_eq!(first.id, second.id);
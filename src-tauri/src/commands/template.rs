@@ -0,0 +1,556 @@
+use std::collections::HashMap;
+
+use rusqlite::Connection;
+use tauri::State;
+use uuid::Uuid;
+
+use crate::ai::ollama::OllamaHttp;
+use crate::clipboard;
+use crate::commands::snippet::{create_snippet_internal, DraftEmbedding};
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::models::{
+    CreateSnippetInput, CreateTemplateInput, Snippet, SnippetTemplate, Tag, UpdateTemplateInput,
+};
+use crate::placeholders;
+
+fn fetch_template_tags(db: &Database, template_id: &str) -> Result<Vec<Tag>, AppError> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name FROM tags t
+             INNER JOIN snippet_template_tags tt ON tt.tag_id = t.id
+             WHERE tt.template_id = ?1
+             ORDER BY t.name ASC",
+        )?;
+        let tags = stmt
+            .query_map([template_id], |row| {
+                Ok(Tag {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tags)
+    })
+    .map_err(AppError::from)
+}
+
+fn row_to_template(row: &rusqlite::Row) -> Result<SnippetTemplate, rusqlite::Error> {
+    Ok(SnippetTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        title: row.get(3)?,
+        problem: row.get(4)?,
+        solution: row.get(5)?,
+        code: row.get(6)?,
+        code_language: row.get(7)?,
+        reference_url: row.get(8)?,
+        tags: vec![],
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+fn fetch_template_by_id(db: &Database, id: &str) -> Result<SnippetTemplate, AppError> {
+    let template = db
+        .with_connection(|conn| {
+            conn.query_row(
+                "SELECT id, name, description, title, problem, solution, code, code_language, reference_url, created_at, updated_at
+                 FROM snippet_templates WHERE id = ?1",
+                [id],
+                row_to_template,
+            )
+        })
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                AppError::NotFound(format!("Template with id '{id}' not found"))
+            }
+            other => AppError::Database(other),
+        })?;
+
+    let tags = fetch_template_tags(db, &template.id)?;
+    Ok(SnippetTemplate { tags, ..template })
+}
+
+fn fetch_all_templates(db: &Database) -> Result<Vec<SnippetTemplate>, AppError> {
+    let templates = db.with_connection(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, description, title, problem, solution, code, code_language, reference_url, created_at, updated_at
+             FROM snippet_templates ORDER BY name ASC",
+        )?;
+        let templates = stmt
+            .query_map([], row_to_template)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(templates)
+    })?;
+
+    let mut result = Vec::with_capacity(templates.len());
+    for template in templates {
+        let tags = fetch_template_tags(db, &template.id)?;
+        result.push(SnippetTemplate { tags, ..template });
+    }
+    Ok(result)
+}
+
+fn ensure_unique_name(db: &Database, name: &str, exclude_id: &str) -> Result<String, AppError> {
+    let trimmed = name.trim().to_string();
+    if trimmed.is_empty() {
+        return Err(AppError::Validation(
+            "Template name cannot be empty".to_string(),
+        ));
+    }
+
+    let taken = db.with_connection(|conn| {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM snippet_templates WHERE name = ?1 AND id != ?2)",
+            [&trimmed, exclude_id],
+            |row| row.get::<_, bool>(0),
+        )
+    })?;
+    if taken {
+        return Err(AppError::Validation(format!(
+            "A template named '{trimmed}' already exists"
+        )));
+    }
+    Ok(trimmed)
+}
+
+fn set_template_tags(
+    conn: &Connection,
+    template_id: &str,
+    tag_ids: &[String],
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM snippet_template_tags WHERE template_id = ?1",
+        [template_id],
+    )?;
+    for tag_id in tag_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO snippet_template_tags (template_id, tag_id) VALUES (?1, ?2)",
+            rusqlite::params![template_id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_template(
+    db: &Database,
+    input: &CreateTemplateInput,
+) -> Result<SnippetTemplate, AppError> {
+    let name = ensure_unique_name(db, &input.name, "")?;
+    let id = Uuid::new_v4().to_string();
+
+    db.with_transaction(|conn| {
+        conn.execute(
+            "INSERT INTO snippet_templates (id, name, description, title, problem, solution, code, code_language, reference_url)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            rusqlite::params![
+                id,
+                name,
+                input.description,
+                input.title,
+                input.problem,
+                input.solution,
+                input.code,
+                input.code_language,
+                input.reference_url,
+            ],
+        )?;
+        set_template_tags(conn, &id, &input.tag_ids)
+    })?;
+
+    fetch_template_by_id(db, &id)
+}
+
+fn update_template_internal(
+    db: &Database,
+    id: &str,
+    input: &UpdateTemplateInput,
+) -> Result<SnippetTemplate, AppError> {
+    fetch_template_by_id(db, id)?;
+
+    let mut sets = vec![];
+    let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
+
+    if let Some(ref name) = input.name {
+        sets.push("name = ?");
+        params.push(Box::new(ensure_unique_name(db, name, id)?));
+    }
+    if let Some(ref description) = input.description {
+        sets.push("description = ?");
+        params.push(Box::new(description.clone()));
+    }
+    if let Some(ref title) = input.title {
+        sets.push("title = ?");
+        params.push(Box::new(title.clone()));
+    }
+    if let Some(ref problem) = input.problem {
+        sets.push("problem = ?");
+        params.push(Box::new(problem.clone()));
+    }
+    if let Some(ref solution) = input.solution {
+        sets.push("solution = ?");
+        params.push(Box::new(solution.clone()));
+    }
+    if let Some(ref code) = input.code {
+        sets.push("code = ?");
+        params.push(Box::new(code.clone()));
+    }
+    if let Some(ref code_language) = input.code_language {
+        sets.push("code_language = ?");
+        params.push(Box::new(code_language.clone()));
+    }
+    if let Some(ref reference_url) = input.reference_url {
+        sets.push("reference_url = ?");
+        params.push(Box::new(reference_url.clone()));
+    }
+
+    db.with_transaction(|conn| {
+        if !sets.is_empty() {
+            sets.push("updated_at = CURRENT_TIMESTAMP");
+            params.push(Box::new(id.to_string()));
+
+            let sql = format!(
+                "UPDATE snippet_templates SET {} WHERE id = ?",
+                sets.join(", ")
+            );
+            let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                params.iter().map(|p| p.as_ref()).collect();
+            conn.execute(&sql, param_refs.as_slice())?;
+        }
+
+        if let Some(ref tag_ids) = input.tag_ids {
+            set_template_tags(conn, id, tag_ids)?;
+        }
+
+        Ok(())
+    })?;
+
+    fetch_template_by_id(db, id)
+}
+
+/// Built-in placeholders. The clipboard is only read when a template uses it.
+fn builtin_placeholder(name: &str) -> Option<String> {
+    let now = chrono::Local::now();
+    match name {
+        "date" => Some(now.format("%Y-%m-%d").to_string()),
+        "time" => Some(now.format("%H:%M").to_string()),
+        "datetime" => Some(now.format("%Y-%m-%d %H:%M").to_string()),
+        "clipboard" => Some(clipboard::read_text().unwrap_or_default()),
+        _ => None,
+    }
+}
+
+/// Expand placeholders in every text field of a template.
+/// Caller-supplied variables take precedence over built-ins.
+fn render_template<F>(
+    template: &SnippetTemplate,
+    variables: &HashMap<String, String>,
+    builtin: F,
+) -> CreateSnippetInput
+where
+    F: Fn(&str) -> Option<String>,
+{
+    // Resolve each built-in once so e.g. the clipboard is read a single time
+    let mut resolved: HashMap<String, Option<String>> = HashMap::new();
+    let mut expand = |text: &str| {
+        placeholders::render(text, |name| {
+            if let Some(value) = variables.get(name) {
+                return Some(value.clone());
+            }
+            resolved
+                .entry(name.to_string())
+                .or_insert_with(|| builtin(name))
+                .clone()
+        })
+    };
+    let mut expand_optional = |text: &Option<String>| {
+        text.as_deref()
+            .map(&mut expand)
+            .filter(|value| !value.trim().is_empty())
+    };
+
+    let solution = expand_optional(&template.solution);
+    let code = expand_optional(&template.code);
+    let reference_url = expand_optional(&template.reference_url);
+
+    let title = expand(&template.title);
+    let title = if title.trim().is_empty() {
+        template.name.clone()
+    } else {
+        title
+    };
+
+    CreateSnippetInput {
+        title,
+        problem: expand(&template.problem),
+        solution,
+        code,
+        code_language: template.code_language.clone(),
+        reference_url,
+        tag_ids: template.tags.iter().map(|t| t.id.clone()).collect(),
+        files: vec![],
+        allow_duplicate: false,
+        source_app: None,
+        captured_at: None,
+        status: Default::default(),
+    }
+}
+
+#[tauri::command]
+pub fn list_snippet_templates(db: State<'_, Database>) -> Result<Vec<SnippetTemplate>, String> {
+    fetch_all_templates(&db).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn get_snippet_template(
+    db: State<'_, Database>,
+    id: String,
+) -> Result<SnippetTemplate, String> {
+    fetch_template_by_id(&db, &id).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn create_snippet_template(
+    db: State<'_, Database>,
+    input: CreateTemplateInput,
+) -> Result<SnippetTemplate, String> {
+    insert_template(&db, &input).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn update_snippet_template(
+    db: State<'_, Database>,
+    id: String,
+    input: UpdateTemplateInput,
+) -> Result<SnippetTemplate, String> {
+    update_template_internal(&db, &id, &input).map_err(String::from)
+}
+
+#[tauri::command]
+pub fn delete_snippet_template(db: State<'_, Database>, id: String) -> Result<(), String> {
+    fetch_template_by_id(&db, &id).map_err(String::from)?;
+
+    db.with_connection(|conn| {
+        conn.execute("DELETE FROM snippet_templates WHERE id = ?1", [&id])?;
+        Ok(())
+    })
+    .map_err(|e| AppError::Database(e).to_string())
+}
+
+/// Expand a template into form values without saving anything
+#[tauri::command]
+pub fn render_snippet_template(
+    db: State<'_, Database>,
+    id: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<CreateSnippetInput, String> {
+    let template = fetch_template_by_id(&db, &id).map_err(String::from)?;
+    Ok(render_template(
+        &template,
+        &variables.unwrap_or_default(),
+        builtin_placeholder,
+    ))
+}
+
+#[tauri::command]
+pub async fn create_snippet_from_template(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+    http: State<'_, OllamaHttp>,
+    id: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<Snippet, String> {
+    let template = fetch_template_by_id(&db, &id).map_err(String::from)?;
+    let input = render_template(
+        &template,
+        &variables.unwrap_or_default(),
+        builtin_placeholder,
+    );
+    create_snippet_internal(&db, &journal, &http, input, DraftEmbedding::Pending)
+        .await
+        .map_err(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute("INSERT INTO tags (id, name) VALUES ('tag-ops', 'ops')", [])?;
+            conn.execute("INSERT INTO tags (id, name) VALUES ('tag-k8s', 'k8s')", [])?;
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    fn sample_input(name: &str) -> CreateTemplateInput {
+        CreateTemplateInput {
+            name: name.to_string(),
+            description: Some("Kubernetes fixes".to_string()),
+            title: "{{service}} outage on {{date}}".to_string(),
+            problem: "Pod logs:\n{{clipboard}}".to_string(),
+            solution: Some("{{missing}}".to_string()),
+            code: Some("".to_string()),
+            code_language: Some("yaml".to_string()),
+            reference_url: None,
+            tag_ids: vec!["tag-ops".to_string(), "tag-k8s".to_string()],
+        }
+    }
+
+    fn fixed_builtin(name: &str) -> Option<String> {
+        match name {
+            "date" => Some("2026-02-09".to_string()),
+            "clipboard" => Some("CrashLoopBackOff".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_default_templates_are_seeded() {
+        let db = setup_db();
+        let names: Vec<String> = fetch_all_templates(&db)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+
+        assert_eq!(
+            names,
+            vec!["CLI one-liner", "Config recipe", "Incident postmortem"]
+        );
+    }
+
+    #[test]
+    fn test_create_template_with_tags() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let template = insert_template(&db, &sample_input("K8s incident")).unwrap();
+
+        // Then
+        assert_eq!(template.name, "K8s incident");
+        assert_eq!(template.code_language.as_deref(), Some("yaml"));
+        let tag_names: Vec<&str> = template.tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(tag_names, vec!["k8s", "ops"]);
+    }
+
+    #[test]
+    fn test_create_template_rejects_duplicate_name() {
+        // Given
+        let db = setup_db();
+        insert_template(&db, &sample_input("K8s incident")).unwrap();
+
+        // When
+        let result = insert_template(&db, &sample_input("  K8s incident "));
+
+        // Then
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_update_template_fields_and_tags() {
+        // Given
+        let db = setup_db();
+        let template = insert_template(&db, &sample_input("K8s incident")).unwrap();
+
+        // When
+        let updated = update_template_internal(
+            &db,
+            &template.id,
+            &UpdateTemplateInput {
+                name: Some("K8s postmortem".to_string()),
+                description: None,
+                title: None,
+                problem: None,
+                solution: None,
+                code: None,
+                code_language: Some("bash".to_string()),
+                reference_url: None,
+                tag_ids: Some(vec!["tag-ops".to_string()]),
+            },
+        )
+        .unwrap();
+
+        // Then
+        assert_eq!(updated.name, "K8s postmortem");
+        assert_eq!(updated.code_language.as_deref(), Some("bash"));
+        assert_eq!(updated.title, template.title);
+        assert_eq!(updated.tags.len(), 1);
+    }
+
+    #[test]
+    fn test_delete_tag_removes_it_from_templates() {
+        // Given
+        let db = setup_db();
+        let template = insert_template(&db, &sample_input("K8s incident")).unwrap();
+
+        // When
+        db.with_connection(|conn| {
+            conn.execute("DELETE FROM tags WHERE id = 'tag-k8s'", [])?;
+            Ok(())
+        })
+        .unwrap();
+
+        // Then
+        let template = fetch_template_by_id(&db, &template.id).unwrap();
+        assert_eq!(template.tags.len(), 1);
+        assert_eq!(template.tags[0].id, "tag-ops");
+    }
+
+    #[test]
+    fn test_render_template_expands_placeholders() {
+        // Given
+        let db = setup_db();
+        let template = insert_template(&db, &sample_input("K8s incident")).unwrap();
+        let variables = HashMap::from([("service".to_string(), "billing".to_string())]);
+
+        // When
+        let input = render_template(&template, &variables, fixed_builtin);
+
+        // Then
+        assert_eq!(input.title, "billing outage on 2026-02-09");
+        assert_eq!(input.problem, "Pod logs:\nCrashLoopBackOff");
+        // Unknown placeholders are kept, empty optional fields are dropped
+        assert_eq!(input.solution.as_deref(), Some("{{missing}}"));
+        assert!(input.code.is_none());
+        assert_eq!(input.code_language.as_deref(), Some("yaml"));
+        assert_eq!(input.tag_ids.len(), 2);
+    }
+
+    #[test]
+    fn test_render_variables_override_builtins() {
+        // Given
+        let db = setup_db();
+        let template = insert_template(&db, &sample_input("K8s incident")).unwrap();
+        let variables = HashMap::from([("date".to_string(), "yesterday".to_string())]);
+
+        // When
+        let input = render_template(&template, &variables, fixed_builtin);
+
+        // Then
+        assert_eq!(input.title, "{{service}} outage on yesterday");
+    }
+
+    #[test]
+    fn test_render_falls_back_to_template_name_for_blank_title() {
+        // Given
+        let db = setup_db();
+        let template = fetch_template_by_id(&db, "template-cli-one-liner").unwrap();
+
+        // When
+        let input = render_template(&template, &HashMap::new(), fixed_builtin);
+
+        // Then
+        assert_eq!(input.title, "CLI one-liner");
+        assert_eq!(input.code.as_deref(), Some("CrashLoopBackOff"));
+        assert_eq!(input.code_language.as_deref(), Some("bash"));
+    }
+}
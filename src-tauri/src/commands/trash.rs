@@ -0,0 +1,255 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::db::journal::snapshot_snippets;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+
+/// How often the background task looks for expired trash
+const PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Event emitted with the error message when the scheduled trash purge fails
+pub const TRASH_PURGE_FAILED_EVENT: &str = "trash-purge-failed";
+
+fn collect_ids(
+    db: &Database,
+    sql: &str,
+    params: &[&dyn rusqlite::types::ToSql],
+) -> Result<Vec<String>, AppError> {
+    db.with_connection(|conn| {
+        let mut stmt = conn.prepare(sql)?;
+        let ids = stmt
+            .query_map(params, |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(ids)
+    })
+    .map_err(AppError::from)
+}
+
+/// Permanently delete snippets that have been in the trash longer than `retention_days`.
+/// A retention of 0 keeps trash forever.
+pub(crate) fn purge_expired_trash(
+    db: &Database,
+    retention_days: i32,
+) -> Result<Vec<String>, AppError> {
+    if retention_days <= 0 {
+        return Ok(vec![]);
+    }
+
+    let cutoff = format!("-{retention_days} days");
+    collect_ids(
+        db,
+        "DELETE FROM snippets
+         WHERE is_deleted = 1 AND deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1)
+         RETURNING id",
+        &[&cutoff],
+    )
+}
+
+fn trashed_ids(db: &Database) -> Result<Vec<String>, AppError> {
+    collect_ids(db, "SELECT id FROM snippets WHERE is_deleted = 1", &[])
+}
+
+fn empty_trash_internal(db: &Database) -> Result<Vec<String>, AppError> {
+    collect_ids(
+        db,
+        "DELETE FROM snippets WHERE is_deleted = 1 RETURNING id",
+        &[],
+    )
+}
+
+fn restore_all_internal(db: &Database) -> Result<Vec<String>, AppError> {
+    collect_ids(
+        db,
+        "UPDATE snippets SET is_deleted = 0, deleted_at = NULL WHERE is_deleted = 1 RETURNING id",
+        &[],
+    )
+}
+
+fn run_scheduled_purge(db: &Database) -> Result<Vec<String>, AppError> {
+    let retention_days = db.with_connection(|conn| {
+        conn.query_row(
+            "SELECT trash_retention_days FROM settings WHERE id = 1",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+    })?;
+    purge_expired_trash(db, retention_days)
+}
+
+/// Purge expired trash at startup and periodically for the lifetime of the app; failures
+/// are emitted as `TRASH_PURGE_FAILED_EVENT`
+pub fn spawn_trash_purge_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_scheduled_purge(&app.state::<Database>()) {
+                let _ = app.emit(TRASH_PURGE_FAILED_EVENT, e.to_string());
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn empty_trash(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+) -> Result<Vec<String>, String> {
+    let ids = trashed_ids(&db).map_err(String::from)?;
+    let before = snapshot_snippets(&db, &ids).map_err(String::from)?;
+    let removed = empty_trash_internal(&db).map_err(String::from)?;
+
+    journal.record_snippets(&db, "Empty trash", before);
+
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn restore_all_from_trash(
+    db: State<'_, Database>,
+    journal: State<'_, Journal>,
+) -> Result<Vec<String>, String> {
+    let ids = trashed_ids(&db).map_err(String::from)?;
+    let before = snapshot_snippets(&db, &ids).map_err(String::from)?;
+    let restored = restore_all_internal(&db).map_err(String::from)?;
+
+    journal.record_snippets(&db, "Restore all from trash", before);
+
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::db::Database;
+
+    fn setup_db() -> Database {
+        Database::new_in_memory().unwrap()
+    }
+
+    // Helper: insert a snippet, optionally trashed `days_ago` days in the past
+    fn insert_snippet(db: &Database, id: &str, trashed_days_ago: Option<i32>) {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES (?1, ?1, 'problem')",
+                [id],
+            )?;
+            if let Some(days) = trashed_days_ago {
+                conn.execute(
+                    "UPDATE snippets SET is_deleted = 1, deleted_at = datetime('now', ?2) WHERE id = ?1",
+                    rusqlite::params![id, format!("-{days} days")],
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    fn snippet_ids(db: &Database) -> Vec<String> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare("SELECT id FROM snippets ORDER BY id")?;
+            let ids = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<Vec<String>, _>>()?;
+            Ok(ids)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_purge_removes_only_expired_trash() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "active", None);
+        insert_snippet(&db, "recent", Some(1));
+        insert_snippet(&db, "expired", Some(40));
+
+        // When
+        let purged = super::purge_expired_trash(&db, 30).unwrap();
+
+        // Then
+        assert_eq!(purged, vec!["expired".to_string()]);
+        assert_eq!(snippet_ids(&db), vec!["active", "recent"]);
+    }
+
+    #[test]
+    fn test_purge_with_zero_retention_keeps_trash() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "old", Some(365));
+
+        // When
+        let purged = super::purge_expired_trash(&db, 0).unwrap();
+
+        // Then
+        assert!(purged.is_empty());
+        assert_eq!(snippet_ids(&db), vec!["old"]);
+    }
+
+    #[test]
+    fn test_purge_cascades_embeddings() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "expired", Some(40));
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO embeddings (snippet_id, embedding, embedding_model) VALUES ('expired', x'00000000', 'test')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        // When
+        super::purge_expired_trash(&db, 30).unwrap();
+
+        // Then
+        let count: i32 = db
+            .with_connection(|conn| {
+                conn.query_row("SELECT COUNT(*) FROM embeddings", [], |row| row.get(0))
+            })
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_empty_trash() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "active", None);
+        insert_snippet(&db, "trashed-1", Some(1));
+        insert_snippet(&db, "trashed-2", Some(2));
+
+        // When
+        let mut removed = super::empty_trash_internal(&db).unwrap();
+        removed.sort();
+
+        // Then
+        assert_eq!(removed, vec!["trashed-1", "trashed-2"]);
+        assert_eq!(snippet_ids(&db), vec!["active"]);
+    }
+
+    #[test]
+    fn test_restore_all_from_trash() {
+        // Given
+        let db = setup_db();
+        insert_snippet(&db, "active", None);
+        insert_snippet(&db, "trashed", Some(3));
+
+        // When
+        let restored = super::restore_all_internal(&db).unwrap();
+
+        // Then
+        assert_eq!(restored, vec!["trashed".to_string()]);
+        let still_deleted: i32 = db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM snippets WHERE is_deleted = 1 OR deleted_at IS NOT NULL",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap();
+        assert_eq!(still_deleted, 0);
+    }
+}
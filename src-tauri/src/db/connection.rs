@@ -1,15 +1,28 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, TransactionBehavior};
 use std::path::PathBuf;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::db::migrations;
 
+/// How long a write waits for another process (the CLI or MCP server next to the app)
+/// to finish its own before failing with "database is locked"
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Database wrapper for thread-safe SQLite access
 pub struct Database {
     conn: Mutex<Connection>,
 }
 
 impl Database {
+    /// Where the desktop app and the CLI keep their shared database
+    pub fn default_path() -> PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("recallsnippet")
+            .join("recallsnippet.db")
+    }
+
     /// Create a new database connection at the specified path
     pub fn new(db_path: PathBuf) -> Result<Self, rusqlite::Error> {
         // Ensure parent directory exists
@@ -19,7 +32,9 @@ impl Database {
 
         let conn = Connection::open(&db_path)?;
 
-        // Enable foreign keys
+        // Enable foreign keys; WAL lets other processes read while one writes
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
 
         let db = Self {
@@ -47,10 +62,13 @@ impl Database {
         Ok(db)
     }
 
-    /// Run all migrations
+    /// Run all migrations, holding the write lock so a second process opening the same
+    /// file waits and then finds them applied
     fn run_migrations(&self) -> Result<(), rusqlite::Error> {
-        let conn = self.conn.lock().unwrap();
-        migrations::run_all(&conn)
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        migrations::run_all(&tx)?;
+        tx.commit()
     }
 
     /// Execute a function with a connection reference
@@ -61,6 +79,20 @@ impl Database {
         let conn = self.conn.lock().unwrap();
         f(&conn)
     }
+
+    /// Execute a function inside a transaction, committing only if it succeeds. The write
+    /// lock is taken up front: a transaction that reads first and then finds another
+    /// process has written in between would fail at once instead of waiting.
+    pub fn with_transaction<F, T>(&self, f: F) -> Result<T, rusqlite::Error>
+    where
+        F: FnOnce(&Connection) -> Result<T, rusqlite::Error>,
+    {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let result = f(&tx)?;
+        tx.commit()?;
+        Ok(result)
+    }
 }
 
 #[cfg(test)]
@@ -108,4 +140,77 @@ mod tests {
 
         assert_eq!(fk_enabled, 1);
     }
+
+    #[test]
+    fn test_transaction_rolls_back_on_error() {
+        let db = Database::new_in_memory().unwrap();
+
+        let result: Result<(), rusqlite::Error> = db.with_transaction(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Test', 'Problem')",
+                [],
+            )?;
+            // Violates the NOT NULL constraint on title
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s2', NULL, 'Problem')",
+                [],
+            )?;
+            Ok(())
+        });
+        assert!(result.is_err());
+
+        let count: i32 = db
+            .with_connection(|conn| {
+                conn.query_row("SELECT COUNT(*) FROM snippets", [], |row| row.get(0))
+            })
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_two_connections_to_one_file_wait_for_each_other() {
+        // Given
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recallsnippet.db");
+        let app = Database::new(path.clone()).unwrap();
+        let cli = Database::new(path).unwrap();
+        let insert = |db: &Database, id: &str| {
+            db.with_connection(|conn| {
+                conn.execute(
+                    "INSERT INTO snippets (id, title, problem) VALUES (?1, 'Test', 'Problem')",
+                    [id],
+                )
+            })
+        };
+
+        // When
+        let second = std::thread::scope(|scope| {
+            let writer = app
+                .with_transaction(|conn| {
+                    conn.execute(
+                        "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Test', 'Problem')",
+                        [],
+                    )?;
+                    // The other connection writes while this transaction holds the lock
+                    let writer = scope.spawn(|| insert(&cli, "s2"));
+                    std::thread::sleep(Duration::from_millis(200));
+                    Ok(writer)
+                })
+                .unwrap();
+            writer.join().unwrap()
+        });
+
+        // Then
+        assert_eq!(second.unwrap(), 1);
+        let journal_mode: String = cli
+            .with_connection(|conn| conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)))
+            .unwrap();
+        assert_eq!(journal_mode, "wal");
+        let count: i32 = cli
+            .with_connection(|conn| {
+                conn.query_row("SELECT COUNT(*) FROM snippets", [], |row| row.get(0))
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+    }
 }
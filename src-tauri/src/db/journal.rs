@@ -0,0 +1,865 @@
+use rusqlite::{Connection, OptionalExtension};
+use std::sync::Mutex;
+
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::HistoryState;
+
+/// Maximum number of operations kept per session
+const MAX_ENTRIES: usize = 100;
+
+/// A snippet file row: id, filename, language, content, position
+type FileState = (String, Option<String>, Option<String>, String, i32);
+
+/// A link touching a snippet: id, source id, target id, link type, created at
+type LinkState = (String, String, String, String, String);
+/// An archived reference page: id, url, title, content, fetched at, created at
+type ReferenceState = (String, String, Option<String>, String, String, String);
+/// (embedding, embedding_model, dimensions, norm, encoding, stale)
+type EmbeddingState = (Vec<u8>, String, Option<i64>, Option<f64>, String, bool);
+
+/// Complete state of a snippet row, including its files, tag links, snippet links, archived
+/// references and embedding
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnippetState {
+    title: String,
+    problem: String,
+    solution: Option<String>,
+    summary: Option<String>,
+    code_language: Option<String>,
+    reference_url: Option<String>,
+    created_at: String,
+    updated_at: String,
+    is_favorite: bool,
+    is_deleted: bool,
+    deleted_at: Option<String>,
+    last_accessed_at: Option<String>,
+    source_app: Option<String>,
+    captured_at: Option<String>,
+    status: String,
+    files: Vec<FileState>,
+    links: Vec<LinkState>,
+    references: Vec<ReferenceState>,
+    tag_ids: Vec<String>,
+    embedding: Option<EmbeddingState>,
+}
+
+/// State of a tag and the snippets it is attached to
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagState {
+    name: String,
+    snippet_ids: Vec<String>,
+}
+
+/// Before/after pair for a single row. `None` means the row does not exist.
+#[derive(Debug, Clone)]
+enum Change {
+    Snippet {
+        id: String,
+        before: Option<Box<SnippetState>>,
+        after: Option<Box<SnippetState>>,
+    },
+    Tag {
+        id: String,
+        before: Option<TagState>,
+        after: Option<TagState>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry {
+    label: String,
+    changes: Vec<Change>,
+}
+
+#[derive(Default)]
+struct Stacks {
+    undo: Vec<JournalEntry>,
+    redo: Vec<JournalEntry>,
+    /// Why the last operation could not be recorded
+    record_error: Option<String>,
+}
+
+/// Session-scoped journal of mutating operations, used for undo/redo
+#[derive(Default)]
+pub struct Journal {
+    stacks: Mutex<Stacks>,
+}
+
+fn read_snippet_state(
+    conn: &Connection,
+    id: &str,
+) -> Result<Option<SnippetState>, rusqlite::Error> {
+    let state = conn
+        .query_row(
+            "SELECT title, problem, solution, code_language, reference_url, created_at, updated_at,
+                    is_favorite, is_deleted, deleted_at, last_accessed_at, summary, source_app,
+                    captured_at, status
+             FROM snippets WHERE id = ?1",
+            [id],
+            |row| {
+                Ok(SnippetState {
+                    title: row.get(0)?,
+                    problem: row.get(1)?,
+                    solution: row.get(2)?,
+                    code_language: row.get(3)?,
+                    reference_url: row.get(4)?,
+                    created_at: row.get(5)?,
+                    updated_at: row.get(6)?,
+                    is_favorite: row.get(7)?,
+                    is_deleted: row.get(8)?,
+                    deleted_at: row.get(9)?,
+                    last_accessed_at: row.get(10)?,
+                    summary: row.get(11)?,
+                    source_app: row.get(12)?,
+                    captured_at: row.get(13)?,
+                    status: row.get(14)?,
+                    files: vec![],
+                    links: vec![],
+                    references: vec![],
+                    tag_ids: vec![],
+                    embedding: None,
+                })
+            },
+        )
+        .optional()?;
+
+    let Some(state) = state else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT id, filename, language, content, position FROM snippet_files
+         WHERE snippet_id = ?1 ORDER BY position, id",
+    )?;
+    let files = stmt
+        .query_map([id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?
+        .collect::<Result<Vec<FileState>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, source_id, target_id, link_type, created_at FROM snippet_links
+         WHERE source_id = ?1 OR target_id = ?1 ORDER BY id",
+    )?;
+    let links = stmt
+        .query_map([id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?
+        .collect::<Result<Vec<LinkState>, _>>()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, url, title, content, fetched_at, created_at FROM snippet_references
+         WHERE snippet_id = ?1 ORDER BY created_at, rowid",
+    )?;
+    let references = stmt
+        .query_map([id], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })?
+        .collect::<Result<Vec<ReferenceState>, _>>()?;
+
+    let mut stmt =
+        conn.prepare("SELECT tag_id FROM snippet_tags WHERE snippet_id = ?1 ORDER BY tag_id")?;
+    let tag_ids = stmt
+        .query_map([id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    let embedding = conn
+        .query_row(
+            "SELECT embedding, embedding_model, dimensions, norm, encoding, stale
+             FROM embeddings WHERE snippet_id = ?1",
+            [id],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                ))
+            },
+        )
+        .optional()?;
+
+    Ok(Some(SnippetState {
+        files,
+        links,
+        references,
+        tag_ids,
+        embedding,
+        ..state
+    }))
+}
+
+fn read_tag_state(conn: &Connection, id: &str) -> Result<Option<TagState>, rusqlite::Error> {
+    let name: Option<String> = conn
+        .query_row("SELECT name FROM tags WHERE id = ?1", [id], |row| {
+            row.get(0)
+        })
+        .optional()?;
+
+    let Some(name) = name else {
+        return Ok(None);
+    };
+
+    let mut stmt =
+        conn.prepare("SELECT snippet_id FROM snippet_tags WHERE tag_id = ?1 ORDER BY snippet_id")?;
+    let snippet_ids = stmt
+        .query_map([id], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    Ok(Some(TagState { name, snippet_ids }))
+}
+
+fn write_snippet_state(
+    conn: &Connection,
+    id: &str,
+    state: Option<&SnippetState>,
+) -> Result<(), rusqlite::Error> {
+    let Some(state) = state else {
+        conn.execute("DELETE FROM snippets WHERE id = ?1", [id])?;
+        return Ok(());
+    };
+
+    // Upsert rather than INSERT OR REPLACE, which would cascade-delete dependent rows
+    conn.execute(
+        "INSERT INTO snippets (id, title, problem, solution, code_language, reference_url,
+                               created_at, updated_at, is_favorite, is_deleted, deleted_at, last_accessed_at,
+                               summary, source_app, captured_at, status)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
+         ON CONFLICT(id) DO UPDATE SET
+            title = excluded.title, problem = excluded.problem, solution = excluded.solution,
+            summary = excluded.summary, code_language = excluded.code_language,
+            reference_url = excluded.reference_url, created_at = excluded.created_at,
+            updated_at = excluded.updated_at, is_favorite = excluded.is_favorite,
+            is_deleted = excluded.is_deleted, deleted_at = excluded.deleted_at,
+            last_accessed_at = excluded.last_accessed_at, source_app = excluded.source_app,
+            captured_at = excluded.captured_at, status = excluded.status",
+        rusqlite::params![
+            id,
+            state.title,
+            state.problem,
+            state.solution,
+            state.code_language,
+            state.reference_url,
+            state.created_at,
+            state.updated_at,
+            state.is_favorite,
+            state.is_deleted,
+            state.deleted_at,
+            state.last_accessed_at,
+            state.summary,
+            state.source_app,
+            state.captured_at,
+            state.status,
+        ],
+    )?;
+
+    conn.execute("DELETE FROM snippet_files WHERE snippet_id = ?1", [id])?;
+    for (file_id, filename, language, content, position) in &state.files {
+        conn.execute(
+            "INSERT INTO snippet_files (id, snippet_id, filename, language, content, position)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![file_id, id, filename, language, content, position],
+        )?;
+    }
+
+    conn.execute(
+        "DELETE FROM snippet_links WHERE source_id = ?1 OR target_id = ?1",
+        [id],
+    )?;
+    for (link_id, source_id, target_id, link_type, created_at) in &state.links {
+        // Skip links whose other end no longer exists
+        conn.execute(
+            "INSERT OR IGNORE INTO snippet_links (id, source_id, target_id, link_type, created_at)
+             SELECT ?1, ?2, ?3, ?4, ?5
+             WHERE EXISTS (SELECT 1 FROM snippets WHERE id = ?2)
+               AND EXISTS (SELECT 1 FROM snippets WHERE id = ?3)",
+            rusqlite::params![link_id, source_id, target_id, link_type, created_at],
+        )?;
+    }
+
+    conn.execute(
+        "DELETE FROM snippet_references WHERE snippet_id = ?1",
+        [id],
+    )?;
+    for (reference_id, url, title, content, fetched_at, created_at) in &state.references {
+        conn.execute(
+            "INSERT INTO snippet_references (id, snippet_id, url, title, content, fetched_at, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![reference_id, id, url, title, content, fetched_at, created_at],
+        )?;
+    }
+
+    conn.execute("DELETE FROM snippet_tags WHERE snippet_id = ?1", [id])?;
+    for tag_id in &state.tag_ids {
+        // Skip tags that no longer exist
+        conn.execute(
+            "INSERT OR IGNORE INTO snippet_tags (snippet_id, tag_id)
+             SELECT ?1, id FROM tags WHERE id = ?2",
+            rusqlite::params![id, tag_id],
+        )?;
+    }
+
+    match &state.embedding {
+        Some((blob, model, dimensions, norm, encoding, stale)) => {
+            conn.execute(
+                "INSERT OR REPLACE INTO embeddings
+                    (snippet_id, embedding, embedding_model, dimensions, norm, encoding, stale,
+                     created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, datetime('now'))",
+                rusqlite::params![id, blob, model, dimensions, norm, encoding, stale],
+            )?;
+        }
+        None => {
+            conn.execute("DELETE FROM embeddings WHERE snippet_id = ?1", [id])?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_tag_state(
+    conn: &Connection,
+    id: &str,
+    state: Option<&TagState>,
+) -> Result<(), rusqlite::Error> {
+    let Some(state) = state else {
+        conn.execute("DELETE FROM tags WHERE id = ?1", [id])?;
+        return Ok(());
+    };
+
+    conn.execute(
+        "INSERT INTO tags (id, name) VALUES (?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name",
+        rusqlite::params![id, state.name],
+    )?;
+
+    conn.execute("DELETE FROM snippet_tags WHERE tag_id = ?1", [id])?;
+    for snippet_id in &state.snippet_ids {
+        // Skip snippets that no longer exist
+        conn.execute(
+            "INSERT OR IGNORE INTO snippet_tags (snippet_id, tag_id)
+             SELECT id, ?2 FROM snippets WHERE id = ?1",
+            rusqlite::params![snippet_id, id],
+        )?;
+    }
+
+    Ok(())
+}
+
+impl Change {
+    fn is_noop(&self) -> bool {
+        match self {
+            Change::Snippet { before, after, .. } => before == after,
+            Change::Tag { before, after, .. } => before == after,
+        }
+    }
+
+    fn apply(&self, conn: &Connection, forward: bool) -> Result<(), rusqlite::Error> {
+        match self {
+            Change::Snippet { id, before, after } => {
+                let target = if forward { after } else { before };
+                write_snippet_state(conn, id, target.as_deref())
+            }
+            Change::Tag { id, before, after } => {
+                let target = if forward { after } else { before };
+                write_tag_state(conn, id, target.as_ref())
+            }
+        }
+    }
+}
+
+/// Capture the current state of the given snippets before an operation runs
+pub fn snapshot_snippets(
+    db: &Database,
+    ids: &[String],
+) -> Result<Vec<(String, Option<SnippetState>)>, AppError> {
+    db.with_connection(|conn| {
+        ids.iter()
+            .map(|id| Ok((id.clone(), read_snippet_state(conn, id)?)))
+            .collect()
+    })
+    .map_err(AppError::from)
+}
+
+/// Capture the current state of a tag before an operation runs
+pub fn snapshot_tag(db: &Database, id: &str) -> Result<Option<TagState>, AppError> {
+    db.with_connection(|conn| read_tag_state(conn, id))
+        .map_err(AppError::from)
+}
+
+impl Journal {
+    fn push(&self, label: &str, changes: Vec<Change>) {
+        let changes: Vec<Change> = changes.into_iter().filter(|c| !c.is_noop()).collect();
+        if changes.is_empty() {
+            return;
+        }
+
+        let mut stacks = self.stacks.lock().unwrap();
+        stacks.undo.push(JournalEntry {
+            label: label.to_string(),
+            changes,
+        });
+        if stacks.undo.len() > MAX_ENTRIES {
+            stacks.undo.remove(0);
+        }
+        stacks.redo.clear();
+        stacks.record_error = None;
+    }
+
+    /// The operation already ran, so failing to record it only costs its undo entry. Redo
+    /// is dropped as it would be after a recorded operation, and the failure is reported in
+    /// the history state.
+    fn push_or_report(&self, label: &str, changes: Result<Vec<Change>, AppError>) {
+        match changes {
+            Ok(changes) => self.push(label, changes),
+            Err(e) => {
+                let mut stacks = self.stacks.lock().unwrap();
+                stacks.redo.clear();
+                stacks.record_error = Some(format!("Could not record \"{label}\" for undo: {e}"));
+            }
+        }
+    }
+
+    /// Record an operation on snippets, given their snapshots from before it ran
+    pub fn record_snippets(
+        &self,
+        db: &Database,
+        label: &str,
+        before: Vec<(String, Option<SnippetState>)>,
+    ) {
+        let changes = db.with_connection(|conn| {
+            before
+                .into_iter()
+                .map(|(id, before)| {
+                    let after = read_snippet_state(conn, &id)?;
+                    Ok(Change::Snippet {
+                        id,
+                        before: before.map(Box::new),
+                        after: after.map(Box::new),
+                    })
+                })
+                .collect::<Result<Vec<_>, rusqlite::Error>>()
+        });
+
+        self.push_or_report(label, changes.map_err(AppError::from));
+    }
+
+    /// Record an operation on a tag, given its snapshot from before it ran
+    pub fn record_tag(&self, db: &Database, label: &str, id: &str, before: Option<TagState>) {
+        let changes = snapshot_tag(db, id).map(|after| {
+            vec![Change::Tag {
+                id: id.to_string(),
+                before,
+                after,
+            }]
+        });
+        self.push_or_report(label, changes);
+    }
+
+    /// Revert the most recent operation, returning its label
+    pub fn undo(&self, db: &Database) -> Result<Option<String>, AppError> {
+        let mut stacks = self.stacks.lock().unwrap();
+        let Some(entry) = stacks.undo.pop() else {
+            return Ok(None);
+        };
+
+        let result = db.with_transaction(|conn| {
+            for change in entry.changes.iter().rev() {
+                change.apply(conn, false)?;
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {
+                let label = entry.label.clone();
+                stacks.redo.push(entry);
+                Ok(Some(label))
+            }
+            Err(e) => {
+                stacks.undo.push(entry);
+                Err(AppError::from(e))
+            }
+        }
+    }
+
+    /// Re-apply the most recently undone operation, returning its label
+    pub fn redo(&self, db: &Database) -> Result<Option<String>, AppError> {
+        let mut stacks = self.stacks.lock().unwrap();
+        let Some(entry) = stacks.redo.pop() else {
+            return Ok(None);
+        };
+
+        let result = db.with_transaction(|conn| {
+            for change in &entry.changes {
+                change.apply(conn, true)?;
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {
+                let label = entry.label.clone();
+                stacks.undo.push(entry);
+                Ok(Some(label))
+            }
+            Err(e) => {
+                stacks.redo.push(entry);
+                Err(AppError::from(e))
+            }
+        }
+    }
+
+    pub fn state(&self) -> HistoryState {
+        let stacks = self.stacks.lock().unwrap();
+        HistoryState {
+            undo_label: stacks.undo.last().map(|e| e.label.clone()),
+            redo_label: stacks.redo.last().map(|e| e.label.clone()),
+            record_error: stacks.record_error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute("INSERT INTO tags (id, name) VALUES ('t1', 'rust')", [])?;
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Original', 'Problem')",
+                [],
+            )?;
+            conn.execute(
+                "INSERT INTO snippet_files (id, snippet_id, content, position) VALUES ('f1', 's1', 'fn main() {}', 0)",
+                [],
+            )?;
+            conn.execute(
+                "INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('s1', 't1')",
+                [],
+            )?;
+            conn.execute(
+                "INSERT INTO embeddings (snippet_id, embedding, embedding_model) VALUES ('s1', x'0000803f', 'test')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        db
+    }
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn count(db: &Database, sql: &str) -> i32 {
+        db.with_connection(|conn| conn.query_row(sql, [], |row| row.get(0)))
+            .unwrap()
+    }
+
+    fn title(db: &Database, id: &str) -> String {
+        db.with_connection(|conn| {
+            conn.query_row("SELECT title FROM snippets WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_undo_and_redo_update() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+        db.with_connection(|conn| {
+            conn.execute("UPDATE snippets SET title = 'Changed' WHERE id = 's1'", [])?;
+            Ok(())
+        })
+        .unwrap();
+        journal.record_snippets(&db, "Update snippet", before);
+
+        // When
+        let undone = journal.undo(&db).unwrap();
+
+        // Then
+        assert_eq!(undone.as_deref(), Some("Update snippet"));
+        assert_eq!(title(&db, "s1"), "Original");
+        assert_eq!(
+            journal.state().redo_label.as_deref(),
+            Some("Update snippet")
+        );
+
+        // Redo re-applies the change
+        journal.redo(&db).unwrap();
+        assert_eq!(title(&db, "s1"), "Changed");
+        assert!(journal.state().redo_label.is_none());
+    }
+
+    #[test]
+    fn test_undo_permanent_delete_restores_files_tags_and_embedding() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+        db.with_connection(|conn| {
+            conn.execute("DELETE FROM snippets WHERE id = 's1'", [])?;
+            Ok(())
+        })
+        .unwrap();
+        journal.record_snippets(&db, "Permanently delete snippet", before);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM snippets"), 0);
+
+        // When
+        journal.undo(&db).unwrap();
+
+        // Then
+        assert_eq!(title(&db, "s1"), "Original");
+        assert_eq!(
+            count(
+                &db,
+                "SELECT COUNT(*) FROM snippet_files WHERE id = 'f1' AND snippet_id = 's1'"
+            ),
+            1
+        );
+        assert_eq!(
+            count(
+                &db,
+                "SELECT COUNT(*) FROM snippet_tags WHERE snippet_id = 's1'"
+            ),
+            1
+        );
+        assert_eq!(
+            count(
+                &db,
+                "SELECT COUNT(*) FROM embeddings WHERE snippet_id = 's1'"
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn test_undo_create_removes_snippet() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s2', 'New', 'Problem')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        journal.record_snippets(&db, "Create snippet", vec![("s2".to_string(), None)]);
+
+        // When
+        journal.undo(&db).unwrap();
+
+        // Then
+        assert_eq!(
+            count(&db, "SELECT COUNT(*) FROM snippets WHERE id = 's2'"),
+            0
+        );
+
+        // Redo recreates it with the same id
+        journal.redo(&db).unwrap();
+        assert_eq!(title(&db, "s2"), "New");
+    }
+
+    #[test]
+    fn test_undo_permanent_delete_restores_snippet_links() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s2', 'Newer', 'Problem')",
+                [],
+            )?;
+            conn.execute(
+                "INSERT INTO snippet_links (id, source_id, target_id, link_type) VALUES ('l1', 's2', 's1', 'supersedes')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+        db.with_connection(|conn| {
+            conn.execute("DELETE FROM snippets WHERE id = 's1'", [])?;
+            Ok(())
+        })
+        .unwrap();
+        journal.record_snippets(&db, "Permanently delete snippet", before);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM snippet_links"), 0);
+
+        // When
+        journal.undo(&db).unwrap();
+
+        // Then - the backlink from s2 is back
+        assert_eq!(
+            count(
+                &db,
+                "SELECT COUNT(*) FROM snippet_links WHERE id = 'l1' AND source_id = 's2'"
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn test_undo_permanent_delete_restores_archived_references() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        let reference = db
+            .references()
+            .save("s1", "https://a.test/port", Some("Port in use"), "Run lsof")
+            .unwrap();
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+        db.with_connection(|conn| {
+            conn.execute("DELETE FROM snippets WHERE id = 's1'", [])?;
+            Ok(())
+        })
+        .unwrap();
+        journal.record_snippets(&db, "Permanently delete snippet", before);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM snippet_references"), 0);
+
+        // When
+        journal.undo(&db).unwrap();
+
+        // Then
+        assert_eq!(db.references().for_snippet("s1").unwrap(), vec![reference]);
+
+        // Redo removes them again along with the snippet
+        journal.redo(&db).unwrap();
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM snippet_references"), 0);
+    }
+
+    #[test]
+    fn test_undo_tag_delete_relinks_snippets() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        let before = snapshot_tag(&db, "t1").unwrap();
+        db.with_connection(|conn| {
+            conn.execute("DELETE FROM tags WHERE id = 't1'", [])?;
+            Ok(())
+        })
+        .unwrap();
+        journal.record_tag(&db, "Delete tag", "t1", before);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM snippet_tags"), 0);
+
+        // When
+        journal.undo(&db).unwrap();
+
+        // Then
+        assert_eq!(
+            count(&db, "SELECT COUNT(*) FROM tags WHERE name = 'rust'"),
+            1
+        );
+        assert_eq!(
+            count(
+                &db,
+                "SELECT COUNT(*) FROM snippet_tags WHERE snippet_id = 's1'"
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn test_noop_operations_are_not_recorded() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+
+        // When - nothing changes between snapshot and record
+        journal.record_snippets(&db, "Nothing", before);
+
+        // Then
+        assert!(journal.state().undo_label.is_none());
+        assert_eq!(journal.undo(&db).unwrap(), None);
+    }
+
+    #[test]
+    fn test_new_operation_clears_redo() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        for new_title in ["First", "Second"] {
+            let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+            db.with_connection(|conn| {
+                conn.execute(
+                    "UPDATE snippets SET title = ?1 WHERE id = 's1'",
+                    [new_title],
+                )?;
+                Ok(())
+            })
+            .unwrap();
+            journal.record_snippets(&db, new_title, before);
+        }
+        journal.undo(&db).unwrap();
+        assert_eq!(journal.state().redo_label.as_deref(), Some("Second"));
+
+        // When
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+        db.with_connection(|conn| {
+            conn.execute("UPDATE snippets SET is_favorite = 1 WHERE id = 's1'", [])?;
+            Ok(())
+        })
+        .unwrap();
+        journal.record_snippets(&db, "Favorite", before);
+
+        // Then
+        let state = journal.state();
+        assert_eq!(state.undo_label.as_deref(), Some("Favorite"));
+        assert!(state.redo_label.is_none());
+        assert!(state.record_error.is_none());
+    }
+
+    #[test]
+    fn test_failed_recording_is_reported_without_stale_redo() {
+        // Given
+        let db = setup_db();
+        let journal = Journal::default();
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+        db.with_connection(|conn| {
+            conn.execute("UPDATE snippets SET title = 'First' WHERE id = 's1'", [])
+        })
+        .unwrap();
+        journal.record_snippets(&db, "First", before);
+        journal.undo(&db).unwrap();
+
+        // When - the change is saved but its after-state cannot be read
+        let before = snapshot_snippets(&db, &ids(&["s1"])).unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "UPDATE snippets SET title = 'Second' WHERE id = 's1';
+                 DROP TABLE snippet_references;",
+            )
+        })
+        .unwrap();
+        journal.record_snippets(&db, "Second", before);
+
+        // Then
+        let state = journal.state();
+        assert!(state.undo_label.is_none());
+        assert!(state.redo_label.is_none());
+        assert!(state
+            .record_error
+            .unwrap()
+            .starts_with("Could not record \"Second\" for undo"));
+    }
+}
@@ -1,4 +1,5 @@
 use rusqlite::Connection;
+use uuid::Uuid;
 
 /// Run all database migrations
 pub fn run_all(conn: &Connection) -> Result<(), rusqlite::Error> {
@@ -15,6 +16,24 @@ pub fn run_all(conn: &Connection) -> Result<(), rusqlite::Error> {
     run_migration(conn, "001_initial_schema", create_initial_schema)?;
     run_migration(conn, "002_settings_table", create_settings_table)?;
     run_migration(conn, "003_add_snippet_metadata", add_snippet_metadata)?;
+    run_migration(conn, "004_trash_retention", add_trash_retention)?;
+    run_migration(conn, "005_snippet_templates", create_snippet_templates)?;
+    run_migration(conn, "006_snippet_files", create_snippet_files)?;
+    run_migration(conn, "007_snippet_links", create_snippet_links)?;
+    run_migration(conn, "008_normalize_languages", normalize_languages)?;
+    run_migration(conn, "009_snippet_summaries", add_snippet_summaries)?;
+    run_migration(conn, "010_chat_context_settings", add_chat_context_settings)?;
+    run_migration(conn, "011_prompt_templates", create_prompt_templates)?;
+    run_migration(conn, "012_generation_settings", add_generation_settings)?;
+    run_migration(conn, "013_ollama_client_settings", add_ollama_client_settings)?;
+    run_migration(conn, "014_embedding_batch_settings", add_embedding_batch_settings)?;
+    run_migration(conn, "015_embedding_vector_metadata", add_embedding_vector_metadata)?;
+    run_migration(conn, "016_api_server_settings", add_api_server_settings)?;
+    run_migration(conn, "017_mcp_settings", add_mcp_settings)?;
+    run_migration(conn, "018_quick_capture", add_quick_capture)?;
+    run_migration(conn, "019_snippet_status", add_snippet_status)?;
+    run_migration(conn, "020_snippet_references", create_snippet_references)?;
+    run_migration(conn, "021_stale_embeddings", add_stale_embeddings)?;
 
     Ok(())
 }
@@ -127,9 +146,422 @@ fn add_snippet_metadata(conn: &Connection) -> Result<(), rusqlite::Error> {
     Ok(())
 }
 
+fn add_trash_retention(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE settings ADD COLUMN trash_retention_days INTEGER NOT NULL DEFAULT 30;
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn create_snippet_templates(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE snippet_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            description TEXT,
+            title TEXT NOT NULL DEFAULT '',
+            problem TEXT NOT NULL DEFAULT '',
+            solution TEXT,
+            code TEXT,
+            code_language TEXT,
+            reference_url TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        -- Default tags applied to snippets created from a template
+        CREATE TABLE snippet_template_tags (
+            template_id TEXT REFERENCES snippet_templates(id) ON DELETE CASCADE,
+            tag_id TEXT REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (template_id, tag_id)
+        );
+
+        INSERT INTO snippet_templates (id, name, description, title, problem, solution) VALUES (
+            'template-postmortem',
+            'Incident postmortem',
+            'What broke, why, and how it was fixed',
+            'Postmortem {{date}}: ',
+            '## What happened' || char(10) || char(10) || '## Impact' || char(10) || char(10) || '## Root cause' || char(10),
+            '## Resolution' || char(10) || char(10) || '## Follow-ups' || char(10)
+        );
+
+        INSERT INTO snippet_templates (id, name, description, problem, code, code_language) VALUES (
+            'template-cli-one-liner',
+            'CLI one-liner',
+            'A shell command worth remembering',
+            'What I needed to do: ',
+            '{{clipboard}}',
+            'bash'
+        );
+
+        INSERT INTO snippet_templates (id, name, description, problem, solution, code, code_language) VALUES (
+            'template-config-recipe',
+            'Config recipe',
+            'A working configuration and where it goes',
+            'Configuration needed for: ',
+            'File location: ',
+            '{{clipboard}}',
+            'yaml'
+        );
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn create_snippet_files(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE snippet_files (
+            id TEXT PRIMARY KEY,
+            snippet_id TEXT NOT NULL REFERENCES snippets(id) ON DELETE CASCADE,
+            filename TEXT,
+            language TEXT,
+            content TEXT NOT NULL,
+            position INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE INDEX idx_snippet_files_snippet ON snippet_files(snippet_id, position);
+        "#
+    )?;
+
+    // Move each snippet's single code block into its first file
+    let mut stmt = conn.prepare(
+        "SELECT id, code, code_language FROM snippets WHERE code IS NOT NULL AND code != ''",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+
+    for (snippet_id, code, language) in rows {
+        conn.execute(
+            "INSERT INTO snippet_files (id, snippet_id, language, content, position)
+             VALUES (?1, ?2, ?3, ?4, 0)",
+            rusqlite::params![Uuid::new_v4().to_string(), snippet_id, language, code],
+        )?;
+    }
+
+    conn.execute_batch("ALTER TABLE snippets DROP COLUMN code;")?;
+
+    Ok(())
+}
+
+fn create_snippet_links(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE snippet_links (
+            id TEXT PRIMARY KEY,
+            source_id TEXT NOT NULL REFERENCES snippets(id) ON DELETE CASCADE,
+            target_id TEXT NOT NULL REFERENCES snippets(id) ON DELETE CASCADE,
+            link_type TEXT NOT NULL CHECK (link_type IN ('supersedes', 'see_also', 'caused_by')),
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (source_id, target_id, link_type)
+        );
+
+        CREATE INDEX idx_snippet_links_source ON snippet_links(source_id);
+        CREATE INDEX idx_snippet_links_target ON snippet_links(target_id);
+        "#
+    )?;
+
+    Ok(())
+}
+
+/// Language ids with the other values that mean them, frozen as the language registry
+/// stood when `008_normalize_languages` was written
+const LANGUAGE_ALIASES_008: &[(&str, &[&str])] = &[
+    ("bash", &["shell", "sh", "zsh", "shellscript"]),
+    ("powershell", &["ps1", "pwsh", "posh", "psm1"]),
+    ("python", &["py", "python3", "pyw"]),
+    ("javascript", &["js", "node", "nodejs", "jsx", "mjs", "cjs"]),
+    ("typescript", &["ts", "tsx", "mts", "cts"]),
+    ("rust", &["rs"]),
+    ("go", &["golang"]),
+    ("java", &[]),
+    ("kotlin", &["kt", "kts"]),
+    ("csharp", &["c#", "cs", "dotnet"]),
+    ("c", &["h"]),
+    ("cpp", &["c++", "cxx", "hpp", "cc", "hh"]),
+    ("ruby", &["rb", "rake", "gemfile"]),
+    ("php", &[]),
+    ("swift", &[]),
+    ("sql", &["postgres", "postgresql", "mysql", "sqlite", "plsql"]),
+    ("dockerfile", &["docker", "containerfile"]),
+    ("yaml", &["yml"]),
+    ("json", &["jsonc"]),
+    ("toml", &[]),
+    ("xml", &["svg", "xsd", "csproj"]),
+    ("html", &["htm", "xhtml"]),
+    ("css", &["scss", "sass", "less"]),
+    ("markdown", &["md"]),
+    ("makefile", &["make", "mk"]),
+];
+
+/// `language::canonicalize` as of migration 008
+fn canonical_language_008(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let key = trimmed.trim_start_matches('.').to_lowercase();
+    let id = LANGUAGE_ALIASES_008
+        .iter()
+        .find(|(id, aliases)| *id == key || aliases.contains(&key.as_str()))
+        .map(|(id, _)| id.to_string());
+    Some(id.unwrap_or_else(|| trimmed.to_lowercase()))
+}
+
+fn normalize_languages(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "ALTER TABLE settings ADD COLUMN detect_language_with_llm BOOLEAN NOT NULL DEFAULT 0;",
+    )?;
+
+    // Rewrite free-form language values to their canonical registry id
+    for (table, column) in [
+        ("snippets", "code_language"),
+        ("snippet_files", "language"),
+        ("snippet_templates", "code_language"),
+    ] {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT DISTINCT {column} FROM {table} WHERE {column} IS NOT NULL"
+        ))?;
+        let values = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+
+        for value in values {
+            let canonical = canonical_language_008(&value);
+            if canonical.as_deref() != Some(value.as_str()) {
+                conn.execute(
+                    &format!("UPDATE {table} SET {column} = ?1 WHERE {column} = ?2"),
+                    rusqlite::params![canonical, value],
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn add_snippet_summaries(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch("ALTER TABLE snippets ADD COLUMN summary TEXT;")?;
+
+    Ok(())
+}
+
+fn add_chat_context_settings(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE settings ADD COLUMN chat_relevance_threshold REAL NOT NULL DEFAULT 0.3;
+        ALTER TABLE settings ADD COLUMN chat_top_k INTEGER NOT NULL DEFAULT 5;
+        ALTER TABLE settings ADD COLUMN chat_context_tokens INTEGER NOT NULL DEFAULT 3000;
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn create_prompt_templates(conn: &Connection) -> Result<(), rusqlite::Error> {
+    // Only edited prompts are stored; the defaults live in ai::prompts
+    conn.execute_batch(
+        r#"
+        CREATE TABLE prompt_templates (
+            key TEXT PRIMARY KEY,
+            template TEXT NOT NULL,
+            updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn add_generation_settings(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE settings ADD COLUMN chat_model TEXT;
+        ALTER TABLE settings ADD COLUMN solution_model TEXT;
+        ALTER TABLE settings ADD COLUMN tags_model TEXT;
+        ALTER TABLE settings ADD COLUMN summary_model TEXT;
+        ALTER TABLE settings ADD COLUMN language_model TEXT;
+        ALTER TABLE settings ADD COLUMN temperature REAL;
+        ALTER TABLE settings ADD COLUMN top_p REAL;
+        ALTER TABLE settings ADD COLUMN seed INTEGER;
+        ALTER TABLE settings ADD COLUMN num_ctx INTEGER;
+        ALTER TABLE settings ADD COLUMN num_predict INTEGER;
+        ALTER TABLE settings ADD COLUMN keep_alive TEXT;
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn add_ollama_client_settings(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE settings ADD COLUMN ollama_connect_timeout_secs INTEGER NOT NULL DEFAULT 5;
+        ALTER TABLE settings ADD COLUMN ollama_request_timeout_secs INTEGER NOT NULL DEFAULT 30;
+        ALTER TABLE settings ADD COLUMN ollama_generate_timeout_secs INTEGER NOT NULL DEFAULT 120;
+        ALTER TABLE settings ADD COLUMN ollama_max_retries INTEGER NOT NULL DEFAULT 2;
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn add_embedding_batch_settings(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE settings ADD COLUMN embedding_batch_size INTEGER NOT NULL DEFAULT 32;
+        ALTER TABLE settings ADD COLUMN embedding_concurrency INTEGER NOT NULL DEFAULT 2;
+        "#
+    )?;
+
+    Ok(())
+}
+
+/// Unit-length copy of a little-endian f32 vector blob with its dimension and original
+/// length, frozen as `ai::vector` stood when `015_embedding_vector_metadata` was written
+fn normalize_f32_blob_015(blob: &[u8]) -> (Vec<u8>, usize, f64) {
+    let raw: Vec<f32> = blob
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+    let norm = raw
+        .iter()
+        .map(|x| (*x as f64) * (*x as f64))
+        .sum::<f64>()
+        .sqrt();
+    let normalized = raw
+        .iter()
+        .map(|x| if norm == 0.0 { *x } else { (*x as f64 / norm) as f32 })
+        .flat_map(f32::to_le_bytes)
+        .collect();
+    (normalized, raw.len(), norm)
+}
+
+fn add_embedding_vector_metadata(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE embeddings ADD COLUMN dimensions INTEGER;
+        ALTER TABLE embeddings ADD COLUMN norm REAL;
+        ALTER TABLE embeddings ADD COLUMN encoding TEXT NOT NULL DEFAULT 'f32';
+        ALTER TABLE settings ADD COLUMN embedding_encoding TEXT NOT NULL DEFAULT 'f32';
+        "#
+    )?;
+
+    // Existing rows are raw f32 vectors; store them unit-length so scoring is a dot product
+    let mut stmt = conn.prepare("SELECT snippet_id, embedding FROM embeddings")?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+
+    for (snippet_id, blob) in rows {
+        let (normalized, dimensions, norm) = normalize_f32_blob_015(&blob);
+        conn.execute(
+            "UPDATE embeddings SET embedding = ?1, dimensions = ?2, norm = ?3 WHERE snippet_id = ?4",
+            rusqlite::params![normalized, dimensions as i64, norm, snippet_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn add_api_server_settings(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE settings ADD COLUMN api_server_enabled BOOLEAN NOT NULL DEFAULT 0;
+        ALTER TABLE settings ADD COLUMN api_server_port INTEGER NOT NULL DEFAULT 47811;
+        ALTER TABLE settings ADD COLUMN api_token TEXT NOT NULL DEFAULT '';
+        UPDATE settings SET api_token = lower(hex(randomblob(24)));
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn add_mcp_settings(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch("ALTER TABLE settings ADD COLUMN mcp_read_only BOOLEAN NOT NULL DEFAULT 1;")?;
+
+    Ok(())
+}
+
+fn add_quick_capture(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE snippets ADD COLUMN source_app TEXT;
+        ALTER TABLE snippets ADD COLUMN captured_at DATETIME;
+        ALTER TABLE settings ADD COLUMN capture_shortcut TEXT NOT NULL DEFAULT 'CmdOrCtrl+Shift+Alt+S';
+        ALTER TABLE settings ADD COLUMN capture_window_title BOOLEAN NOT NULL DEFAULT 1;
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn add_snippet_status(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE snippets ADD COLUMN status TEXT NOT NULL DEFAULT 'active'
+            CHECK (status IN ('draft', 'active', 'archived'));
+        CREATE INDEX IF NOT EXISTS idx_snippets_status ON snippets(status);
+        "#
+    )?;
+
+    Ok(())
+}
+
+fn create_snippet_references(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE snippet_references (
+            id TEXT PRIMARY KEY,
+            snippet_id TEXT NOT NULL REFERENCES snippets(id) ON DELETE CASCADE,
+            url TEXT NOT NULL,
+            title TEXT,
+            content TEXT NOT NULL,
+            fetched_at DATETIME NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (snippet_id, url)
+        );
+
+        CREATE INDEX idx_snippet_references_snippet ON snippet_references(snippet_id);
+
+        ALTER TABLE settings ADD COLUMN embed_references BOOLEAN NOT NULL DEFAULT 0;
+        "#
+    )?;
+
+    Ok(())
+}
+
+/// Embeddings whose text changed without the snippet changing, e.g. when archived pages
+/// start or stop being embedded; the next reindex picks them up
+fn add_stale_embeddings(conn: &Connection) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(
+        "ALTER TABLE embeddings ADD COLUMN stale BOOLEAN NOT NULL DEFAULT 0;",
+    )?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ai::vector;
 
     #[test]
     fn test_migrations_are_idempotent() {
@@ -140,14 +572,14 @@ mod tests {
         run_all(&conn).unwrap();
         run_all(&conn).unwrap();
 
-        // Should have exactly three migrations recorded
+        // Should have every migration recorded exactly once
         let count: i32 = conn.query_row(
             "SELECT COUNT(*) FROM migrations",
             [],
             |row| row.get(0),
         ).unwrap();
 
-        assert_eq!(count, 3);
+        assert_eq!(count, 21);
     }
 
     #[test]
@@ -182,4 +614,138 @@ mod tests {
 
         assert_eq!(count, 0);
     }
+
+    #[test]
+    fn test_snippet_files_migration_moves_code() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = ON;").unwrap();
+        conn.execute_batch(
+            "CREATE TABLE migrations (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, applied_at DATETIME DEFAULT CURRENT_TIMESTAMP);",
+        ).unwrap();
+        run_migration(&conn, "001_initial_schema", create_initial_schema).unwrap();
+        run_migration(&conn, "002_settings_table", create_settings_table).unwrap();
+        run_migration(&conn, "003_add_snippet_metadata", add_snippet_metadata).unwrap();
+
+        // Snippets created before multi-file support
+        conn.execute(
+            "INSERT INTO snippets (id, title, problem, code, code_language) VALUES ('s1', 'Docker', 'Problem', 'FROM rust:1', 'dockerfile')",
+            [],
+        ).unwrap();
+        conn.execute(
+            "INSERT INTO snippets (id, title, problem) VALUES ('s2', 'No code', 'Problem')",
+            [],
+        ).unwrap();
+
+        run_all(&conn).unwrap();
+
+        let (content, language, position): (String, String, i32) = conn.query_row(
+            "SELECT content, language, position FROM snippet_files WHERE snippet_id = 's1'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).unwrap();
+        assert_eq!(content, "FROM rust:1");
+        assert_eq!(language, "dockerfile");
+        assert_eq!(position, 0);
+
+        let file_count: i32 = conn.query_row(
+            "SELECT COUNT(*) FROM snippet_files",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(file_count, 1);
+
+        // The old column is gone
+        let has_code_column: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('snippets') WHERE name = 'code')",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert!(!has_code_column);
+    }
+
+    #[test]
+    fn test_normalize_languages_migration() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE migrations (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, applied_at DATETIME DEFAULT CURRENT_TIMESTAMP);",
+        ).unwrap();
+        for (name, migration) in [
+            ("001_initial_schema", create_initial_schema as fn(&Connection) -> Result<(), rusqlite::Error>),
+            ("002_settings_table", create_settings_table),
+            ("003_add_snippet_metadata", add_snippet_metadata),
+            ("004_trash_retention", add_trash_retention),
+            ("005_snippet_templates", create_snippet_templates),
+            ("006_snippet_files", create_snippet_files),
+        ] {
+            run_migration(&conn, name, migration).unwrap();
+        }
+
+        // Values typed by hand before the registry existed
+        conn.execute_batch(
+            "INSERT INTO snippets (id, title, problem, code_language) VALUES
+                ('s1', 'a', 'p', 'js'), ('s2', 'b', 'p', 'JavaScript'),
+                ('s3', 'c', 'p', '  '), ('s4', 'd', 'p', 'Nix');
+             INSERT INTO snippet_files (id, snippet_id, language, content) VALUES ('f1', 's1', 'yml', 'a: 1');",
+        ).unwrap();
+
+        run_all(&conn).unwrap();
+
+        let mut stmt = conn
+            .prepare("SELECT code_language FROM snippets ORDER BY id")
+            .unwrap();
+        let languages: Vec<Option<String>> = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            languages,
+            vec![
+                Some("javascript".to_string()),
+                Some("javascript".to_string()),
+                None,
+                Some("nix".to_string()),
+            ]
+        );
+
+        let file_language: String = conn
+            .query_row("SELECT language FROM snippet_files WHERE id = 'f1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(file_language, "yaml");
+    }
+
+    #[test]
+    fn test_embedding_metadata_migration_normalizes_vectors() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE migrations (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, applied_at DATETIME DEFAULT CURRENT_TIMESTAMP);",
+        ).unwrap();
+        run_migration(&conn, "001_initial_schema", create_initial_schema).unwrap();
+        run_migration(&conn, "002_settings_table", create_settings_table).unwrap();
+        run_migration(&conn, "003_add_snippet_metadata", add_snippet_metadata).unwrap();
+
+        // Given a raw vector of length 5 saved before the metadata columns existed
+        let raw: Vec<u8> = [3.0_f32, 4.0].iter().flat_map(|f| f.to_le_bytes()).collect();
+        conn.execute_batch("INSERT INTO snippets (id, title, problem) VALUES ('s1', 't', 'p');").unwrap();
+        conn.execute(
+            "INSERT INTO embeddings (snippet_id, embedding, embedding_model) VALUES ('s1', ?1, 'test')",
+            [raw],
+        ).unwrap();
+
+        // When the remaining migrations run
+        run_all(&conn).unwrap();
+
+        // Then it is stored unit-length with its dimension and original norm
+        let (blob, dimensions, norm, encoding): (Vec<u8>, i64, f64, String) = conn
+            .query_row(
+                "SELECT embedding, dimensions, norm, encoding FROM embeddings WHERE snippet_id = 's1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(vector::decode(&blob, vector::VectorEncoding::F32), vec![0.6, 0.8]);
+        assert_eq!(dimensions, 2);
+        assert!((norm - 5.0).abs() < 1e-9);
+        assert_eq!(encoding, "f32");
+    }
 }
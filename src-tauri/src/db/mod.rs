@@ -1,4 +1,7 @@
 pub mod connection;
+pub mod journal;
 pub mod migrations;
+pub mod repo;
 
 pub use connection::Database;
+pub use journal::Journal;
@@ -0,0 +1,609 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::ai::vector::{self, VectorEncoding};
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::Tag;
+
+/// A decoded vector and the model that produced it
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredEmbedding {
+    pub model: String,
+    /// Unit-length
+    pub vector: Vec<f32>,
+}
+
+/// Vectors of the snippets carrying one tag
+#[derive(Debug, Clone)]
+pub struct TagVectors {
+    pub tag: Tag,
+    pub vectors: Vec<Vec<f32>>,
+}
+
+/// Which stored vectors [`EmbeddingRepo::rank`] compares against
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RankScope<'s> {
+    /// Only vectors from this model
+    pub model: Option<&'s str>,
+    /// Leave this snippet out, e.g. the one being compared
+    pub exclude: Option<&'s str>,
+    /// Also rank snippets in the trash
+    pub include_deleted: bool,
+    /// Also rank drafts and archived snippets
+    pub include_inactive: bool,
+}
+
+/// Dimension of the vectors already stored for `model`, if any
+fn model_dimensions(conn: &Connection, model: &str) -> Result<Option<usize>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT dimensions FROM embeddings
+         WHERE embedding_model = ?1 AND dimensions IS NOT NULL
+         LIMIT 1",
+        [model],
+        |row| row.get::<_, i64>(0),
+    )
+    .optional()
+    .map(|d| d.map(|d| d as usize))
+}
+
+pub struct EmbeddingRepo<'a> {
+    db: &'a Database,
+}
+
+impl<'a> EmbeddingRepo<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    pub fn save_one(
+        &self,
+        snippet_id: &str,
+        embedding: &[f32],
+        model: &str,
+    ) -> Result<(), AppError> {
+        self.save(&[(snippet_id.to_string(), embedding.to_vec())], model)
+    }
+
+    /// Save many vectors in one transaction. Vectors are stored unit-length in the
+    /// configured encoding, and every vector must have the dimension already stored
+    /// for `model`.
+    pub fn save(&self, embeddings: &[(String, Vec<f32>)], model: &str) -> Result<(), AppError> {
+        self.db.with_transaction(|conn| {
+            let expected =
+                model_dimensions(conn, model)?.or_else(|| embeddings.first().map(|(_, e)| e.len()));
+            if let Some((snippet_id, embedding)) = embeddings
+                .iter()
+                .find(|(_, e)| e.is_empty() || Some(e.len()) != expected)
+            {
+                return Ok(Err(AppError::Validation(format!(
+                    "Embedding for {snippet_id} has {} dimensions, but {model} vectors have {}",
+                    embedding.len(),
+                    expected.unwrap_or_default()
+                ))));
+            }
+
+            let encoding: String = conn.query_row(
+                "SELECT embedding_encoding FROM settings WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )?;
+            let encoding = VectorEncoding::parse(&encoding).unwrap_or(VectorEncoding::F32);
+            let mut stmt = conn.prepare(
+                "INSERT OR REPLACE INTO embeddings
+                    (snippet_id, embedding, embedding_model, dimensions, norm, encoding, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+            )?;
+            for (snippet_id, embedding) in embeddings {
+                stmt.execute(rusqlite::params![
+                    snippet_id,
+                    vector::encode(&vector::normalize(embedding), encoding),
+                    model,
+                    embedding.len() as i64,
+                    vector::norm(embedding),
+                    encoding.as_str()
+                ])?;
+            }
+            Ok(Ok(()))
+        })?
+    }
+
+    /// Rewrite every stored vector in `encoding`, e.g. after the storage setting changes
+    pub fn reencode(&self, encoding: VectorEncoding) -> Result<usize, AppError> {
+        let count = self.db.with_transaction(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT snippet_id, embedding, encoding FROM embeddings WHERE encoding != ?1",
+            )?;
+            let rows = stmt
+                .query_map([encoding.as_str()], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, Vec<u8>>(1)?,
+                        row.get::<_, String>(2)?,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            drop(stmt);
+
+            for (snippet_id, blob, stored) in &rows {
+                let decoded = vector::decode_stored(blob, stored);
+                conn.execute(
+                    "UPDATE embeddings SET embedding = ?1, encoding = ?2 WHERE snippet_id = ?3",
+                    rusqlite::params![
+                        vector::encode(&decoded, encoding),
+                        encoding.as_str(),
+                        snippet_id
+                    ],
+                )?;
+            }
+            Ok(rows.len())
+        })?;
+        Ok(count)
+    }
+
+    /// Flag every stored vector for re-embedding, e.g. after the embedded text changes.
+    /// Stale vectors keep serving search until the next reindex replaces them.
+    pub fn mark_all_stale(&self) -> Result<usize, AppError> {
+        let count = self
+            .db
+            .with_connection(|conn| conn.execute("UPDATE embeddings SET stale = 1", []))?;
+        Ok(count)
+    }
+
+    /// The stored vector of a snippet, if it has been embedded
+    pub fn get(&self, snippet_id: &str) -> Result<Option<StoredEmbedding>, AppError> {
+        let row: Option<(Vec<u8>, String, String)> = self.db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT embedding, embedding_model, encoding FROM embeddings WHERE snippet_id = ?1",
+                [snippet_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+        })?;
+        Ok(row.map(|(blob, model, encoding)| StoredEmbedding {
+            model,
+            vector: vector::decode_stored(&blob, &encoding),
+        }))
+    }
+
+    /// `model` vectors of every snippet outside the trash, by snippet id
+    pub fn outside_trash(&self, model: &str) -> Result<HashMap<String, Vec<f32>>, AppError> {
+        let rows: Vec<(String, Vec<u8>, String)> = self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT e.snippet_id, e.embedding, e.encoding FROM embeddings e
+                 INNER JOIN snippets s ON s.id = e.snippet_id
+                 WHERE s.is_deleted = 0 AND e.embedding_model = ?1",
+            )?;
+            let rows = stmt
+                .query_map([model], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, blob, encoding)| (id, vector::decode_stored(&blob, &encoding)))
+            .collect())
+    }
+
+    /// `model` vectors of the snippets outside the trash, grouped by the tags they carry
+    pub fn by_tag(&self, model: &str) -> Result<Vec<TagVectors>, AppError> {
+        let rows: Vec<(Tag, Vec<u8>, String)> = self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT t.id, t.name, e.embedding, e.encoding FROM tags t
+                 INNER JOIN snippet_tags st ON st.tag_id = t.id
+                 INNER JOIN snippets s ON s.id = st.snippet_id
+                 INNER JOIN embeddings e ON e.snippet_id = s.id
+                 WHERE s.is_deleted = 0 AND e.embedding_model = ?1
+                 ORDER BY t.id",
+            )?;
+            let rows = stmt
+                .query_map([model], |row| {
+                    let tag = Tag {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                    };
+                    Ok((tag, row.get(2)?, row.get(3)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        let mut groups: Vec<TagVectors> = vec![];
+        for (tag, blob, encoding) in rows {
+            let decoded = vector::decode_stored(&blob, &encoding);
+            match groups.last_mut() {
+                Some(group) if group.tag.id == tag.id => group.vectors.push(decoded),
+                _ => groups.push(TagVectors {
+                    tag,
+                    vectors: vec![decoded],
+                }),
+            }
+        }
+        Ok(groups)
+    }
+
+    /// Snippets in `scope` ranked by cosine similarity to `query`, best first. Only
+    /// vectors with the query's dimension are compared.
+    pub fn rank(&self, query: &[f32], scope: RankScope) -> Result<Vec<(String, f64)>, AppError> {
+        let query = vector::normalize(query);
+        let rows: Vec<(String, Vec<u8>, String)> = self.db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT e.snippet_id, e.embedding, e.encoding FROM embeddings e
+                 INNER JOIN snippets s ON s.id = e.snippet_id
+                 WHERE e.dimensions = ?1
+                   AND (?2 OR s.is_deleted = 0)
+                   AND (?3 IS NULL OR e.embedding_model = ?3)
+                   AND (?4 IS NULL OR e.snippet_id != ?4)
+                   AND (?5 OR s.status = 'active')",
+            )?;
+            let rows = stmt
+                .query_map(
+                    rusqlite::params![
+                        query.len() as i64,
+                        scope.include_deleted,
+                        scope.model,
+                        scope.exclude,
+                        scope.include_inactive
+                    ],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        // Stored vectors are unit-length, so the dot product is the cosine
+        let mut scored: Vec<(String, f64)> = rows
+            .into_iter()
+            .map(|(id, blob, encoding)| {
+                let score = vector::dot(&query, &vector::decode_stored(&blob, &encoding));
+                (id, score)
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(scored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_read_embedding() {
+        let db = Database::new_in_memory().unwrap();
+
+        // Insert a snippet first
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Test', 'Problem')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        // Save embedding
+        let embedding = vec![0.1_f32, 0.2, 0.3, 0.4];
+        db.embeddings()
+            .save_one("s1", &embedding, "test-model")
+            .unwrap();
+
+        // Verify it was saved unit-length with its metadata
+        let (stored_model, stored_blob, dimensions, norm, encoding): (
+            String,
+            Vec<u8>,
+            i64,
+            f64,
+            String,
+        ) = db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT embedding_model, embedding, dimensions, norm, encoding
+                     FROM embeddings WHERE snippet_id = 's1'",
+                    [],
+                    |row| {
+                        Ok((
+                            row.get(0)?,
+                            row.get(1)?,
+                            row.get(2)?,
+                            row.get(3)?,
+                            row.get(4)?,
+                        ))
+                    },
+                )
+            })
+            .unwrap();
+
+        assert_eq!(stored_model, "test-model");
+        assert_eq!(dimensions, 4);
+        assert!((norm - vector::norm(&embedding)).abs() < 1e-9);
+        assert_eq!(encoding, "f32");
+        assert_eq!(
+            vector::decode(&stored_blob, VectorEncoding::F32),
+            vector::normalize(&embedding)
+        );
+    }
+
+    #[test]
+    fn test_save_embedding_rejects_wrong_dimensions() {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'A', 'p'), ('s2', 'B', 'p');",
+            )
+        })
+        .unwrap();
+
+        // Given a 3-dimensional vector stored for the model
+        db.embeddings()
+            .save_one("s1", &[1.0, 0.0, 0.0], "test-model")
+            .unwrap();
+
+        // When saving a 2-dimensional vector for the same model
+        let err = db
+            .embeddings()
+            .save_one("s2", &[1.0, 0.0], "test-model")
+            .unwrap_err()
+            .to_string();
+
+        // Then it is rejected and nothing is written
+        assert!(err.contains("has 2 dimensions"), "{err}");
+        assert!(err.contains("have 3"), "{err}");
+        let count: i32 = db
+            .with_connection(|conn| {
+                conn.query_row("SELECT COUNT(*) FROM embeddings", [], |row| row.get(0))
+            })
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // A batch that disagrees with itself is rejected too
+        assert!(db
+            .embeddings()
+            .save(
+                &[
+                    ("s1".to_string(), vec![1.0, 0.0]),
+                    ("s2".to_string(), vec![1.0, 0.0, 0.0]),
+                ],
+                "other-model",
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_save_embedding_uses_configured_encoding_and_reencodes() {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'A', 'p');
+                 UPDATE settings SET embedding_encoding = 'int8' WHERE id = 1;",
+            )
+        })
+        .unwrap();
+        let embedding: Vec<f32> = (0..64).map(|i| (i as f32 - 32.0) / 10.0).collect();
+        let stored = |db: &Database| -> (Vec<u8>, String) {
+            db.with_connection(|conn| {
+                conn.query_row(
+                    "SELECT embedding, encoding FROM embeddings WHERE snippet_id = 's1'",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+            })
+            .unwrap()
+        };
+
+        // When saved with int8 storage configured
+        db.embeddings()
+            .save_one("s1", &embedding, "test-model")
+            .unwrap();
+
+        // Then it takes one byte per dimension plus the scale
+        let (blob, encoding) = stored(&db);
+        assert_eq!(encoding, "int8");
+        assert_eq!(blob.len(), 4 + 64);
+
+        // When re-encoded as f16
+        assert_eq!(db.embeddings().reencode(VectorEncoding::F16).unwrap(), 1);
+
+        // Then it still matches the original direction
+        let (blob, encoding) = stored(&db);
+        assert_eq!(encoding, "f16");
+        assert_eq!(blob.len(), 2 * 64);
+        let decoded = vector::decode(&blob, VectorEncoding::F16);
+        assert!((vector::dot(&decoded, &vector::normalize(&embedding)) - 1.0).abs() < 1e-2);
+        assert_eq!(db.embeddings().reencode(VectorEncoding::F16).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_save_embedding_replaces_existing() {
+        let db = Database::new_in_memory().unwrap();
+
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Test', 'Problem')",
+                [],
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        // Save first embedding
+        db.embeddings()
+            .save_one("s1", &[1.0, 2.0], "model-v1")
+            .unwrap();
+
+        // Replace with new embedding
+        db.embeddings()
+            .save_one("s1", &[3.0, 4.0], "model-v2")
+            .unwrap();
+
+        // Should have only one row
+        let count: i32 = db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM embeddings WHERE snippet_id = 's1'",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // Should be the new one
+        let model: String = db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT embedding_model FROM embeddings WHERE snippet_id = 's1'",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .unwrap();
+        assert_eq!(model, "model-v2");
+    }
+
+    #[test]
+    fn test_rank_scores_by_cosine_within_scope() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES
+                    ('docker', 'a', 'p'), ('pizza', 'b', 'p'), ('trashed', 'c', 'p'), ('small', 'd', 'p');
+                 UPDATE snippets SET is_deleted = 1 WHERE id = 'trashed';",
+            )
+        })
+        .unwrap();
+        let repo = db.embeddings();
+        repo.save_one("docker", &[0.9, 0.1, 0.0], "test").unwrap();
+        repo.save_one("pizza", &[0.0, 0.1, 0.9], "test").unwrap();
+        repo.save_one("trashed", &[0.8, 0.2, 0.0], "other").unwrap();
+        repo.save_one("small", &[1.0, 0.0], "tiny").unwrap();
+        let query = [0.8_f32, 0.2, 0.0];
+
+        // When
+        let active = repo.rank(&query, RankScope::default()).unwrap();
+        let all = repo
+            .rank(
+                &query,
+                RankScope {
+                    include_deleted: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let same_model = repo
+            .rank(
+                &query,
+                RankScope {
+                    model: Some("test"),
+                    exclude: Some("docker"),
+                    include_deleted: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Then
+        let ids = |ranked: &[(String, f64)]| -> Vec<String> {
+            ranked.iter().map(|(id, _)| id.clone()).collect()
+        };
+        assert_eq!(ids(&active), vec!["docker", "pizza"]);
+        assert!((active[0].1 - vector::cosine(&query, &[0.9, 0.1, 0.0])).abs() < 1e-6);
+        assert_eq!(ids(&all), vec!["trashed", "docker", "pizza"]);
+        assert_eq!(ids(&same_model), vec!["pizza"]);
+    }
+
+    #[test]
+    fn test_rank_skips_drafts_and_archived_unless_asked() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem, status) VALUES
+                    ('active', 'a', 'p', 'active'), ('draft', 'b', 'p', 'draft'),
+                    ('archived', 'c', 'p', 'archived');",
+            )
+        })
+        .unwrap();
+        let repo = db.embeddings();
+        for id in ["active", "draft", "archived"] {
+            repo.save_one(id, &[1.0, 0.0], "test").unwrap();
+        }
+
+        // When
+        let default = repo.rank(&[1.0, 0.0], RankScope::default()).unwrap();
+        let all = repo
+            .rank(
+                &[1.0, 0.0],
+                RankScope {
+                    include_inactive: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Then
+        assert_eq!(default.len(), 1);
+        assert_eq!(default[0].0, "active");
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn test_outside_trash_and_by_tag_decode_vectors() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'a', 'p'), ('s2', 'b', 'p');
+                 INSERT INTO snippets (id, title, problem, is_deleted) VALUES ('s3', 'c', 'p', 1);
+                 INSERT INTO tags (id, name) VALUES ('t1', 'docker'), ('t2', 'rust');
+                 INSERT INTO snippet_tags (snippet_id, tag_id) VALUES
+                    ('s1', 't1'), ('s2', 't1'), ('s2', 't2'), ('s3', 't2');
+                 UPDATE settings SET embedding_encoding = 'f16';",
+            )
+        })
+        .unwrap();
+        let repo = db.embeddings();
+        repo.save_one("s1", &[1.0, 0.0], "test").unwrap();
+        repo.save_one("s2", &[0.0, 2.0], "test").unwrap();
+        repo.save_one("s3", &[1.0, 1.0], "test").unwrap();
+
+        // When
+        let vectors = repo.outside_trash("test").unwrap();
+        let by_tag = repo.by_tag("test").unwrap();
+
+        // Then
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors["s2"], vec![0.0, 1.0]);
+        let groups: Vec<(&str, usize)> = by_tag
+            .iter()
+            .map(|group| (group.tag.name.as_str(), group.vectors.len()))
+            .collect();
+        assert_eq!(groups, vec![("docker", 2), ("rust", 1)]);
+        assert!(repo.by_tag("other").unwrap().is_empty());
+        assert!(repo.outside_trash("other").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_decodes_stored_vector() {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'a', 'p')",
+                [],
+            )
+        })
+        .unwrap();
+
+        assert_eq!(db.embeddings().get("s1").unwrap(), None);
+        db.embeddings().save_one("s1", &[3.0, 4.0], "test").unwrap();
+        assert_eq!(
+            db.embeddings().get("s1").unwrap(),
+            Some(StoredEmbedding {
+                model: "test".to_string(),
+                vector: vec![0.6, 0.8],
+            })
+        );
+    }
+}
@@ -0,0 +1,38 @@
+//! Typed data access shared by the Tauri commands. Each repository borrows a
+//! [`Database`], so the core can be used and tested without Tauri `State`.
+
+pub mod embedding;
+pub mod reference;
+pub mod settings;
+pub mod snippet;
+pub mod tag;
+
+pub use embedding::{EmbeddingRepo, RankScope, StoredEmbedding, TagVectors};
+pub use reference::ReferenceRepo;
+pub use settings::SettingsRepo;
+pub use snippet::SnippetRepo;
+pub use tag::TagRepo;
+
+use super::Database;
+
+impl Database {
+    pub fn settings(&self) -> SettingsRepo<'_> {
+        SettingsRepo::new(self)
+    }
+
+    pub fn snippets(&self) -> SnippetRepo<'_> {
+        SnippetRepo::new(self)
+    }
+
+    pub fn tags(&self) -> TagRepo<'_> {
+        TagRepo::new(self)
+    }
+
+    pub fn embeddings(&self) -> EmbeddingRepo<'_> {
+        EmbeddingRepo::new(self)
+    }
+
+    pub fn references(&self) -> ReferenceRepo<'_> {
+        ReferenceRepo::new(self)
+    }
+}
@@ -0,0 +1,179 @@
+use rusqlite::{OptionalExtension, Row};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::SnippetReference;
+
+const REFERENCE_COLUMNS: &str = "id, snippet_id, url, title, content, fetched_at, created_at";
+
+fn reference_from_row(row: &Row) -> Result<SnippetReference, rusqlite::Error> {
+    Ok(SnippetReference {
+        id: row.get(0)?,
+        snippet_id: row.get(1)?,
+        url: row.get(2)?,
+        title: row.get(3)?,
+        content: row.get(4)?,
+        fetched_at: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Archived copies of the pages snippets refer to
+pub struct ReferenceRepo<'a> {
+    db: &'a Database,
+}
+
+impl<'a> ReferenceRepo<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    pub fn get(&self, id: &str) -> Result<SnippetReference, AppError> {
+        self.db
+            .with_connection(|conn| {
+                conn.query_row(
+                    &format!("SELECT {REFERENCE_COLUMNS} FROM snippet_references WHERE id = ?1"),
+                    [id],
+                    reference_from_row,
+                )
+                .optional()
+            })?
+            .ok_or_else(|| AppError::NotFound(format!("Reference with id '{id}' not found")))
+    }
+
+    /// References of a snippet in the order they were added
+    pub fn for_snippet(&self, snippet_id: &str) -> Result<Vec<SnippetReference>, AppError> {
+        self.db
+            .with_connection(|conn| {
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {REFERENCE_COLUMNS} FROM snippet_references
+                     WHERE snippet_id = ?1 ORDER BY created_at, rowid"
+                ))?;
+                let references = stmt
+                    .query_map([snippet_id], reference_from_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(references)
+            })
+            .map_err(AppError::from)
+    }
+
+    /// Store a freshly fetched copy of `url`, replacing the previous copy if the
+    /// snippet already refers to it
+    pub fn save(
+        &self,
+        snippet_id: &str,
+        url: &str,
+        title: Option<&str>,
+        content: &str,
+    ) -> Result<SnippetReference, AppError> {
+        let id = Uuid::new_v4().to_string();
+        let reference = self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO snippet_references (id, snippet_id, url, title, content, fetched_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)
+                 ON CONFLICT (snippet_id, url) DO UPDATE SET
+                     title = excluded.title,
+                     content = excluded.content,
+                     fetched_at = excluded.fetched_at",
+                rusqlite::params![id, snippet_id, url, title, content],
+            )?;
+            conn.query_row(
+                &format!(
+                    "SELECT {REFERENCE_COLUMNS} FROM snippet_references
+                     WHERE snippet_id = ?1 AND url = ?2"
+                ),
+                [snippet_id, url],
+                reference_from_row,
+            )
+        })?;
+        Ok(reference)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), AppError> {
+        let deleted = self.db.with_connection(|conn| {
+            conn.execute("DELETE FROM snippet_references WHERE id = ?1", [id])
+        })?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!(
+                "Reference with id '{id}' not found"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'a', 'p'), ('s2', 'b', 'p');",
+            )
+        })
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_save_replaces_the_copy_of_the_same_url() {
+        // Given
+        let db = setup_db();
+        let first = db
+            .references()
+            .save("s1", "https://a.test/x", Some("Old"), "old text")
+            .unwrap();
+        db.references()
+            .save("s1", "https://b.test/y", None, "other")
+            .unwrap();
+        db.references()
+            .save("s2", "https://a.test/x", None, "same url, other snippet")
+            .unwrap();
+
+        // When
+        let refreshed = db
+            .references()
+            .save("s1", "https://a.test/x", Some("New"), "new text")
+            .unwrap();
+
+        // Then
+        assert_eq!(refreshed.id, first.id);
+        assert_eq!(refreshed.title.as_deref(), Some("New"));
+        assert_eq!(refreshed.content, "new text");
+        let urls: Vec<String> = db
+            .references()
+            .for_snippet("s1")
+            .unwrap()
+            .into_iter()
+            .map(|r| r.url)
+            .collect();
+        assert_eq!(urls, vec!["https://a.test/x", "https://b.test/y"]);
+    }
+
+    #[test]
+    fn test_references_go_with_their_snippet() {
+        // Given
+        let db = setup_db();
+        let reference = db
+            .references()
+            .save("s1", "https://a.test/x", None, "text")
+            .unwrap();
+
+        // When
+        db.with_connection(|conn| conn.execute("DELETE FROM snippets WHERE id = 's1'", []))
+            .unwrap();
+
+        // Then
+        assert!(matches!(
+            db.references().get(&reference.id),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            db.references().delete(&reference.id),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,155 @@
+use rusqlite::Row;
+
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::Settings;
+
+/// Columns read by [`settings_from_row`], in order
+const SETTINGS_COLUMNS: &str = "theme, ollama_base_url, llm_model, embedding_model, search_limit,
+    data_path, trash_retention_days, detect_language_with_llm, chat_relevance_threshold,
+    chat_top_k, chat_context_tokens, chat_model, solution_model, tags_model, summary_model,
+    language_model, temperature, top_p, seed, num_ctx, num_predict, keep_alive, ollama_connect_timeout_secs,
+    ollama_request_timeout_secs, ollama_generate_timeout_secs, ollama_max_retries,
+    embedding_batch_size, embedding_concurrency, embedding_encoding, api_server_enabled,
+    api_server_port, api_token, mcp_read_only, capture_shortcut, capture_window_title,
+    embed_references";
+
+fn settings_from_row(row: &Row) -> Result<Settings, rusqlite::Error> {
+    Ok(Settings {
+        theme: row.get(0)?,
+        ollama_base_url: row.get(1)?,
+        llm_model: row.get(2)?,
+        embedding_model: row.get(3)?,
+        search_limit: row.get(4)?,
+        data_path: row.get(5)?,
+        trash_retention_days: row.get(6)?,
+        detect_language_with_llm: row.get(7)?,
+        chat_relevance_threshold: row.get(8)?,
+        chat_top_k: row.get(9)?,
+        chat_context_tokens: row.get(10)?,
+        chat_model: row.get(11)?,
+        solution_model: row.get(12)?,
+        tags_model: row.get(13)?,
+        summary_model: row.get(14)?,
+        language_model: row.get(15)?,
+        temperature: row.get(16)?,
+        top_p: row.get(17)?,
+        seed: row.get(18)?,
+        num_ctx: row.get(19)?,
+        num_predict: row.get(20)?,
+        keep_alive: row.get(21)?,
+        ollama_connect_timeout_secs: row.get(22)?,
+        ollama_request_timeout_secs: row.get(23)?,
+        ollama_generate_timeout_secs: row.get(24)?,
+        ollama_max_retries: row.get(25)?,
+        embedding_batch_size: row.get(26)?,
+        embedding_concurrency: row.get(27)?,
+        embedding_encoding: row.get(28)?,
+        api_server_enabled: row.get(29)?,
+        api_server_port: row.get(30)?,
+        api_token: row.get(31)?,
+        mcp_read_only: row.get(32)?,
+        capture_shortcut: row.get(33)?,
+        capture_window_title: row.get(34)?,
+        embed_references: row.get(35)?,
+    })
+}
+
+/// The single settings row
+pub struct SettingsRepo<'a> {
+    db: &'a Database,
+}
+
+impl<'a> SettingsRepo<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    pub fn get(&self) -> Result<Settings, AppError> {
+        self.db
+            .with_connection(|conn| {
+                conn.query_row(
+                    &format!("SELECT {SETTINGS_COLUMNS} FROM settings WHERE id = 1"),
+                    [],
+                    settings_from_row,
+                )
+            })
+            .map_err(AppError::from)
+    }
+
+    /// Stored settings, or the defaults if they cannot be read. For background work
+    /// that should keep going with sensible values rather than fail.
+    pub fn get_or_default(&self) -> Settings {
+        self.get().unwrap_or_default()
+    }
+
+    /// Replace the API token with a fresh random one; old clients stop authenticating
+    pub fn regenerate_api_token(&self) -> Result<String, AppError> {
+        self.db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "UPDATE settings SET api_token = lower(hex(randomblob(24))) WHERE id = 1
+                     RETURNING api_token",
+                    [],
+                    |row| row.get(0),
+                )
+            })
+            .map_err(AppError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_reads_stored_values() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "UPDATE settings SET theme = 'light', chat_model = 'llama3', embedding_encoding = 'int8' WHERE id = 1",
+            )
+        })
+        .unwrap();
+
+        // When
+        let settings = db.settings().get().unwrap();
+
+        // Then
+        assert_eq!(settings.theme, "light");
+        assert_eq!(settings.chat_model.as_deref(), Some("llama3"));
+        assert_eq!(settings.embedding_encoding, "int8");
+        assert_eq!(settings.search_limit, Settings::default().search_limit);
+    }
+
+    #[test]
+    fn test_get_or_default_falls_back_when_missing() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| conn.execute_batch("DROP TABLE settings"))
+            .unwrap();
+
+        // Then
+        assert!(db.settings().get().is_err());
+        assert_eq!(
+            db.settings().get_or_default().theme,
+            Settings::default().theme
+        );
+    }
+
+    #[test]
+    fn test_api_token_is_generated_and_can_be_replaced() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        let initial = db.settings().get().unwrap().api_token;
+
+        // When
+        let regenerated = db.settings().regenerate_api_token().unwrap();
+
+        // Then
+        assert_eq!(initial.len(), 48);
+        assert_ne!(regenerated, initial);
+        assert_eq!(db.settings().get().unwrap().api_token, regenerated);
+    }
+}
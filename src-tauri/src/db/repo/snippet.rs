@@ -0,0 +1,432 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::Row;
+
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::language;
+use crate::models::{
+    InboxCounts, Snippet, SnippetFile, SnippetFilter, SnippetStatus, SnippetSummary,
+};
+
+impl ToSql for SnippetStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.as_str().into())
+    }
+}
+
+impl FromSql for SnippetStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        SnippetStatus::parse(text)
+            .ok_or_else(|| FromSqlError::Other(format!("Unknown snippet status: {text}").into()))
+    }
+}
+
+/// SQL expression for the first 200 characters of a snippet's first file
+const CODE_PREVIEW_SQL: &str = "(SELECT SUBSTR(f.content, 1, 200) FROM snippet_files f
+      WHERE f.snippet_id = snippets.id ORDER BY f.position, f.id LIMIT 1)";
+
+/// Columns read by [`summary_from_row`], in order
+fn summary_columns() -> String {
+    format!(
+        "id, title, problem, code_language, {CODE_PREVIEW_SQL}, created_at, is_favorite,
+         is_deleted, deleted_at, last_accessed_at, summary, status"
+    )
+}
+
+/// Summary without its tags; see [`SnippetRepo::with_tags`]
+fn summary_from_row(row: &Row) -> Result<SnippetSummary, rusqlite::Error> {
+    Ok(SnippetSummary {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        problem: row.get(2)?,
+        summary: row.get(10)?,
+        code_language: row.get(3)?,
+        code_preview: row.get(4)?,
+        tags: vec![],
+        created_at: row.get(5)?,
+        is_favorite: row.get(6)?,
+        is_deleted: row.get(7)?,
+        deleted_at: row.get(8)?,
+        last_accessed_at: row.get(9)?,
+        status: row.get(11)?,
+    })
+}
+
+pub struct SnippetRepo<'a> {
+    db: &'a Database,
+}
+
+impl<'a> SnippetRepo<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Full snippet with its tags and files
+    pub fn get(&self, id: &str) -> Result<Snippet, AppError> {
+        let snippet = self
+            .db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT id, title, problem, solution, code_language, reference_url, created_at,
+                            updated_at, is_favorite, is_deleted, deleted_at, last_accessed_at, summary,
+                            source_app, captured_at, status
+                     FROM snippets WHERE id = ?1",
+                    [id],
+                    |row| {
+                        Ok(Snippet {
+                            id: row.get(0)?,
+                            title: row.get(1)?,
+                            problem: row.get(2)?,
+                            solution: row.get(3)?,
+                            summary: row.get(12)?,
+                            code: None,
+                            code_language: row.get(4)?,
+                            reference_url: row.get(5)?,
+                            tags: vec![],
+                            files: vec![],
+                            created_at: row.get(6)?,
+                            updated_at: row.get(7)?,
+                            is_favorite: row.get(8)?,
+                            is_deleted: row.get(9)?,
+                            deleted_at: row.get(10)?,
+                            last_accessed_at: row.get(11)?,
+                            source_app: row.get(13)?,
+                            captured_at: row.get(14)?,
+                            status: row.get(15)?,
+                        })
+                    },
+                )
+            })
+            .map_err(|e| not_found(e, id))?;
+
+        let tags = self.db.tags().for_snippet(&snippet.id)?;
+        let files = self.files(&snippet.id)?;
+        Ok(Snippet {
+            tags,
+            code: files.first().map(|f| f.content.clone()),
+            files,
+            ..snippet
+        })
+    }
+
+    /// Files of a snippet in display order
+    pub fn files(&self, snippet_id: &str) -> Result<Vec<SnippetFile>, AppError> {
+        self.db
+            .with_connection(|conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT id, snippet_id, filename, language, content, position FROM snippet_files
+                     WHERE snippet_id = ?1 ORDER BY position, id",
+                )?;
+                let files = stmt
+                    .query_map([snippet_id], |row| {
+                        Ok(SnippetFile {
+                            id: row.get(0)?,
+                            snippet_id: row.get(1)?,
+                            filename: row.get(2)?,
+                            language: row.get(3)?,
+                            content: row.get(4)?,
+                            position: row.get(5)?,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(files)
+            })
+            .map_err(AppError::from)
+    }
+
+    /// Summary of a single snippet, including its tags
+    pub fn summary(&self, id: &str) -> Result<SnippetSummary, AppError> {
+        let summary = self
+            .db
+            .with_connection(|conn| {
+                conn.query_row(
+                    &format!("SELECT {} FROM snippets WHERE id = ?1", summary_columns()),
+                    [id],
+                    summary_from_row,
+                )
+            })
+            .map_err(|e| not_found(e, id))?;
+        self.with_tags(summary)
+    }
+
+    /// Summaries matching `filter`, including their tags
+    pub fn list(&self, filter: &SnippetFilter) -> Result<Vec<SnippetSummary>, AppError> {
+        let summaries = self.db.with_connection(|conn| {
+            let mut sql = format!("SELECT {} FROM snippets WHERE 1=1", summary_columns());
+            let mut params: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
+
+            if let Some(lang) = filter.language.as_deref().and_then(language::canonicalize) {
+                sql.push_str(
+                    " AND (LOWER(code_language) = LOWER(?) OR EXISTS (
+                        SELECT 1 FROM snippet_files f
+                        WHERE f.snippet_id = snippets.id AND LOWER(f.language) = LOWER(?)))",
+                );
+                params.push(Box::new(lang.clone()));
+                params.push(Box::new(lang));
+            }
+
+            if let Some(ref search) = filter.search {
+                sql.push_str(
+                    " AND (title LIKE ? OR problem LIKE ? OR EXISTS (
+                        SELECT 1 FROM snippet_files f
+                        WHERE f.snippet_id = snippets.id AND (f.content LIKE ? OR f.filename LIKE ?)))",
+                );
+                let pattern = format!("%{search}%");
+                for _ in 0..4 {
+                    params.push(Box::new(pattern.clone()));
+                }
+            }
+
+            if let Some(ref tag) = filter.tag {
+                sql.push_str(
+                    " AND EXISTS (
+                        SELECT 1 FROM snippet_tags st INNER JOIN tags t ON t.id = st.tag_id
+                        WHERE st.snippet_id = snippets.id AND t.name = ?)",
+                );
+                params.push(Box::new(tag.clone()));
+            }
+
+            if filter.trash_only.unwrap_or(false) {
+                sql.push_str(" AND is_deleted = 1");
+            } else {
+                if !filter.include_deleted.unwrap_or(false) {
+                    sql.push_str(" AND is_deleted = 0");
+                }
+                // Favorites and trash are separate views
+                if filter.favorites_only.unwrap_or(false) {
+                    sql.push_str(" AND is_favorite = 1");
+                }
+                if !filter.any_status.unwrap_or(false) {
+                    sql.push_str(" AND status = ?");
+                    params.push(Box::new(filter.status.unwrap_or_default()));
+                }
+            }
+
+            if filter.recent_first.unwrap_or(false) {
+                sql.push_str(" ORDER BY last_accessed_at DESC");
+            } else {
+                sql.push_str(" ORDER BY created_at DESC");
+            }
+
+            let mut stmt = conn.prepare(&sql)?;
+            let param_refs: Vec<&dyn rusqlite::types::ToSql> =
+                params.iter().map(|p| p.as_ref()).collect();
+            let rows = stmt
+                .query_map(param_refs.as_slice(), summary_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        summaries
+            .into_iter()
+            .map(|summary| self.with_tags(summary))
+            .collect()
+    }
+
+    /// Drafts waiting for triage and the size of the other views
+    pub fn counts(&self) -> Result<InboxCounts, AppError> {
+        self.db
+            .with_connection(|conn| {
+                conn.query_row(
+                    "SELECT COALESCE(SUM(is_deleted = 0 AND status = 'draft'), 0),
+                            COALESCE(SUM(is_deleted = 0 AND status = 'active'), 0),
+                            COALESCE(SUM(is_deleted = 0 AND status = 'archived'), 0),
+                            COALESCE(SUM(is_deleted = 1), 0)
+                     FROM snippets",
+                    [],
+                    |row| {
+                        Ok(InboxCounts {
+                            drafts: row.get(0)?,
+                            active: row.get(1)?,
+                            archived: row.get(2)?,
+                            trash: row.get(3)?,
+                        })
+                    },
+                )
+            })
+            .map_err(AppError::from)
+    }
+
+    /// Move a snippet between draft, active and archived
+    pub fn set_status(&self, id: &str, status: SnippetStatus) -> Result<(), AppError> {
+        let updated = self.db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE snippets SET status = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+                rusqlite::params![status, id],
+            )
+        })?;
+        if updated == 0 {
+            return Err(AppError::NotFound(format!(
+                "Snippet with id '{id}' not found"
+            )));
+        }
+        Ok(())
+    }
+
+    fn with_tags(&self, summary: SnippetSummary) -> Result<SnippetSummary, AppError> {
+        let tags = self.db.tags().for_snippet(&summary.id)?;
+        Ok(SnippetSummary { tags, ..summary })
+    }
+}
+
+fn not_found(e: rusqlite::Error, id: &str) -> AppError {
+    match e {
+        rusqlite::Error::QueryReturnedNoRows => {
+            AppError::NotFound(format!("Snippet with id '{id}' not found"))
+        }
+        other => AppError::Database(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO tags (id, name) VALUES ('t1', 'docker');
+                 INSERT INTO snippets (id, title, problem, created_at) VALUES
+                    ('s1', 'Compose', 'p', '2026-01-01'), ('s2', 'Kubectl', 'p', '2026-01-02');
+                 INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('s1', 't1');
+                 INSERT INTO snippet_files (id, snippet_id, filename, content, position) VALUES
+                    ('f2', 's1', 'b.yml', 'second', 1), ('f1', 's1', 'a.yml', 'first', 0);",
+            )
+        })
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_get_includes_tags_and_ordered_files() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let snippet = db.snippets().get("s1").unwrap();
+
+        // Then
+        assert_eq!(snippet.tags.len(), 1);
+        let files: Vec<&str> = snippet.files.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(files, vec!["f1", "f2"]);
+        assert_eq!(snippet.code.as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn test_missing_snippet_is_not_found() {
+        let db = setup_db();
+
+        assert!(matches!(
+            db.snippets().get("missing"),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            db.snippets().summary("missing"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_summary_and_list_share_the_row_mapping() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let summary = db.snippets().summary("s1").unwrap();
+        let listed = db.snippets().list(&SnippetFilter::default()).unwrap();
+
+        // Then
+        assert_eq!(summary.code_preview.as_deref(), Some("first"));
+        assert_eq!(summary.tags[0].name, "docker");
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["s2", "s1"]);
+        assert_eq!(listed[1].code_preview, summary.code_preview);
+        assert_eq!(listed[1].tags.len(), 1);
+    }
+
+    #[test]
+    fn test_list_filters_by_tag_name() {
+        // Given
+        let db = setup_db();
+
+        // When
+        let tagged = db
+            .snippets()
+            .list(&SnippetFilter {
+                tag: Some("docker".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        let unknown = db
+            .snippets()
+            .list(&SnippetFilter {
+                tag: Some("nope".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Then
+        let ids: Vec<&str> = tagged.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["s1"]);
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_list_and_counts_by_status() {
+        // Given
+        let db = setup_db();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem, status) VALUES
+                    ('d1', 'Captured', 'p', 'draft'), ('a1', 'Old', 'p', 'archived');
+                 INSERT INTO snippets (id, title, problem, status, is_deleted) VALUES
+                    ('d2', 'Trashed draft', 'p', 'draft', 1);",
+            )
+        })
+        .unwrap();
+        let list = |filter: SnippetFilter| -> Vec<String> {
+            let listed = db.snippets().list(&filter).unwrap();
+            let mut ids: Vec<String> = listed.into_iter().map(|s| s.id).collect();
+            ids.sort();
+            ids
+        };
+
+        // When
+        let main = list(SnippetFilter::default());
+        let inbox = list(SnippetFilter {
+            status: Some(SnippetStatus::Draft),
+            ..Default::default()
+        });
+        let everything = list(SnippetFilter {
+            status: Some(SnippetStatus::Draft),
+            any_status: Some(true),
+            ..Default::default()
+        });
+        let trash = list(SnippetFilter {
+            trash_only: Some(true),
+            ..Default::default()
+        });
+        let counts = db.snippets().counts().unwrap();
+
+        // Then
+        assert_eq!(main, vec!["s1", "s2"]);
+        assert_eq!(inbox, vec!["d1"]);
+        assert_eq!(everything, vec!["a1", "d1", "s1", "s2"]);
+        assert_eq!(trash, vec!["d2"]);
+        assert_eq!(
+            db.snippets().get("a1").unwrap().status,
+            SnippetStatus::Archived
+        );
+        assert_eq!(
+            counts,
+            InboxCounts {
+                drafts: 1,
+                active: 2,
+                archived: 1,
+                trash: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use rusqlite::Row;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::models::Tag;
+
+fn tag_from_row(row: &Row) -> Result<Tag, rusqlite::Error> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+    })
+}
+
+pub struct TagRepo<'a> {
+    db: &'a Database,
+}
+
+impl<'a> TagRepo<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    fn query(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Tag>, AppError> {
+        self.db
+            .with_connection(|conn| {
+                let mut stmt = conn.prepare(sql)?;
+                let tags = stmt
+                    .query_map(params, tag_from_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(tags)
+            })
+            .map_err(AppError::from)
+    }
+
+    /// All tags by name
+    pub fn list(&self) -> Result<Vec<Tag>, AppError> {
+        self.query("SELECT id, name FROM tags ORDER BY name ASC", [])
+    }
+
+    /// All tags, most used first
+    pub fn by_usage(&self) -> Result<Vec<Tag>, AppError> {
+        self.query(
+            "SELECT t.id, t.name FROM tags t
+             LEFT JOIN snippet_tags st ON st.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY COUNT(st.snippet_id) DESC, t.name ASC",
+            [],
+        )
+    }
+
+    /// The tag with this exact name, created if it does not exist yet
+    pub fn get_or_create(&self, name: &str) -> Result<Tag, AppError> {
+        let existing = self.query("SELECT id, name FROM tags WHERE name = ?1", [name])?;
+        if let Some(tag) = existing.into_iter().next() {
+            return Ok(tag);
+        }
+
+        let id = Uuid::new_v4().to_string();
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO tags (id, name) VALUES (?1, ?2)",
+                rusqlite::params![id, name],
+            )?;
+            Ok(())
+        })?;
+        Ok(Tag {
+            id,
+            name: name.to_string(),
+        })
+    }
+
+    /// [`Self::get_or_create`] for each non-blank name, trimmed
+    pub fn get_or_create_all(&self, names: &[String]) -> Result<Vec<Tag>, AppError> {
+        names
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| self.get_or_create(name))
+            .collect()
+    }
+
+    pub fn for_snippet(&self, snippet_id: &str) -> Result<Vec<Tag>, AppError> {
+        self.query(
+            "SELECT t.id, t.name FROM tags t
+             INNER JOIN snippet_tags st ON st.tag_id = t.id
+             WHERE st.snippet_id = ?1",
+            [snippet_id],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(tags: &[Tag]) -> Vec<&str> {
+        tags.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_list_orderings_and_snippet_tags() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO tags (id, name) VALUES ('t1', 'rust'), ('t2', 'docker'), ('t3', 'bash');
+                 INSERT INTO snippets (id, title, problem) VALUES ('s1', 'a', 'p'), ('s2', 'b', 'p');
+                 INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('s1', 't1'), ('s2', 't1'), ('s2', 't3');",
+            )
+        })
+        .unwrap();
+        let tags = db.tags();
+
+        // Then
+        assert_eq!(names(&tags.list().unwrap()), vec!["bash", "docker", "rust"]);
+        assert_eq!(
+            names(&tags.by_usage().unwrap()),
+            vec!["rust", "bash", "docker"]
+        );
+        let mut snippet_tags = names(&tags.for_snippet("s2").unwrap())
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        snippet_tags.sort();
+        assert_eq!(snippet_tags, vec!["bash", "rust"]);
+        assert!(tags.for_snippet("missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_or_create_reuses_existing_name() {
+        // Given
+        let db = Database::new_in_memory().unwrap();
+
+        // When
+        let first = db.tags().get_or_create("rust").unwrap();
+        let second = db.tags().get_or_create("rust").unwrap();
+
+        // Then
+        assert_eq!(first.id, second.id);
+        assert_eq!(db.tags().list().unwrap().len(), 1);
+
+        let all = db
+            .tags()
+            .get_or_create_all(&[" rust ".to_string(), " ".to_string(), "go".to_string()])
+            .unwrap();
+        let names: Vec<&str> = all.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["rust", "go"]);
+        assert_eq!(all[0].id, first.id);
+    }
+}
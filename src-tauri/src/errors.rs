@@ -10,6 +10,12 @@ pub enum AppError {
 
     #[error("Ollama error: {0}")]
     Ollama(String),
+
+    #[error("Invalid input: {0}")]
+    Validation(String),
+
+    #[error("Likely duplicate of {0}")]
+    Duplicate(String),
 }
 
 impl From<AppError> for String {
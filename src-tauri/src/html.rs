@@ -0,0 +1,294 @@
+//! Readable text of fetched web pages, for archiving snippet references.
+//!
+//! A small tag scanner rather than a full parser: it drops scripts, styles and page
+//! chrome, keeps `<pre>` blocks verbatim and prefers the `<main>`/`<article>` content
+//! when the page has one.
+
+/// Title and readable text of a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// Elements whose content is never part of the readable text
+const SKIPPED: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "footer", "aside", "form",
+    "button",
+];
+
+/// Elements that start a new line of text
+const BLOCKS: &[&str] = &[
+    "p", "div", "br", "hr", "li", "ul", "ol", "dl", "dt", "dd", "h1", "h2", "h3", "h4", "h5", "h6",
+    "pre", "table", "tr", "section", "article", "main", "header", "figure",
+];
+
+/// Elements holding the content of the page, preferred over everything around them
+const MAIN: &[&str] = &["main", "article"];
+
+/// Text with HTML whitespace rules applied outside of `<pre>`
+#[derive(Default)]
+struct Text(String);
+
+impl Text {
+    fn push(&mut self, text: &str, preformatted: bool) {
+        if preformatted {
+            self.0.push_str(text);
+            return;
+        }
+        for c in text.chars() {
+            if !c.is_whitespace() {
+                self.0.push(c);
+            } else if !self.0.is_empty() && !self.0.ends_with([' ', '\n']) {
+                self.0.push(' ');
+            }
+        }
+    }
+
+    fn line_break(&mut self) {
+        let len = self.0.trim_end_matches(' ').len();
+        self.0.truncate(len);
+        if !self.0.is_empty() && !self.0.ends_with('\n') {
+            self.0.push('\n');
+        }
+    }
+
+    fn finish(self) -> String {
+        let lines: Vec<&str> = self.0.lines().map(str::trim_end).collect();
+        lines.join("\n").trim_matches('\n').to_string()
+    }
+}
+
+#[derive(Default)]
+struct Extractor {
+    page: Text,
+    main: Text,
+    main_depth: usize,
+    pre_depth: usize,
+}
+
+impl Extractor {
+    fn text(&mut self, raw: &str) {
+        if raw.is_empty() {
+            return;
+        }
+        let text = decode_entities(raw);
+        let preformatted = self.pre_depth > 0;
+        self.page.push(&text, preformatted);
+        if self.main_depth > 0 {
+            self.main.push(&text, preformatted);
+        }
+    }
+
+    fn line_break(&mut self) {
+        self.page.line_break();
+        if self.main_depth > 0 {
+            self.main.line_break();
+        }
+    }
+}
+
+/// Extract the title and readable text of an HTML document
+pub fn extract(html: &str) -> Page {
+    // ASCII lowercasing keeps byte offsets, so positions found in `lower` index `html`
+    let lower = html.to_ascii_lowercase();
+    let mut title = None;
+    let mut extractor = Extractor::default();
+    let mut pos = 0;
+
+    while pos < html.len() {
+        let Some(lt) = html[pos..].find('<').map(|i| pos + i) else {
+            extractor.text(&html[pos..]);
+            break;
+        };
+        extractor.text(&html[pos..lt]);
+        pos = lt;
+
+        let next = lower[pos + 1..].chars().next();
+        if !next.is_some_and(|c| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')) {
+            // A stray `<` in the text, as in `a < b`
+            extractor.text("<");
+            pos += 1;
+            continue;
+        }
+        if lower[pos..].starts_with("<!--") {
+            pos = lower[pos..].find("-->").map_or(html.len(), |i| pos + i + 3);
+            continue;
+        }
+        let Some(end) = lower[pos..].find('>').map(|i| pos + i) else {
+            break;
+        };
+        let inner = &lower[pos + 1..end];
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        pos = end + 1;
+        if !inner.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            // Doctype or processing instruction
+            continue;
+        }
+        let name = inner
+            .split(|c: char| c.is_ascii_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+
+        if !closing && !inner.ends_with('/') && (name == "title" || SKIPPED.contains(&name)) {
+            let content_end = lower[pos..]
+                .find(&format!("</{name}"))
+                .map_or(html.len(), |i| pos + i);
+            if name == "title" && title.is_none() {
+                let mut text = Text::default();
+                text.push(&decode_entities(&html[pos..content_end]), false);
+                title = Some(text.finish()).filter(|title| !title.is_empty());
+            }
+            pos = lower[content_end..]
+                .find('>')
+                .map_or(html.len(), |i| content_end + i + 1);
+            continue;
+        }
+
+        if BLOCKS.contains(&name) {
+            extractor.line_break();
+        }
+        let depth = if MAIN.contains(&name) {
+            Some(&mut extractor.main_depth)
+        } else if name == "pre" {
+            Some(&mut extractor.pre_depth)
+        } else {
+            None
+        };
+        if let Some(depth) = depth {
+            *depth = if closing {
+                depth.saturating_sub(1)
+            } else {
+                *depth + 1
+            };
+        }
+    }
+
+    let main = extractor.main.finish();
+    let text = if main.is_empty() {
+        extractor.page.finish()
+    } else {
+        main
+    };
+    Page { title, text }
+}
+
+/// Replace character references; unknown named references are kept as written
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&len| len <= 10)
+            .and_then(|len| decode_entity(&rest[1..1 + len]).map(|c| (c, len + 2)));
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "copy" => '©',
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_title_and_paragraphs_without_scripts_or_chrome() {
+        // Given
+        let html = r#"<!DOCTYPE html>
+            <html><head>
+              <title>  Fix   "port in use" &ndash; Blog </title>
+              <style>body { color: red }</style>
+              <script>var x = "<p>not text</p>";</script>
+            </head><body>
+              <nav><a href="/">Home</a></nav>
+              <h1>Port in use</h1>
+              <p>Find the process with
+                 <code>lsof</code>, then kill it.</p>
+              <!-- <p>hidden</p> -->
+              <footer>&copy; 2024</footer>
+            </body></html>"#;
+
+        // When
+        let page = extract(html);
+
+        // Then
+        assert_eq!(page.title.as_deref(), Some("Fix \"port in use\" – Blog"));
+        assert_eq!(
+            page.text,
+            "Port in use\nFind the process with lsof, then kill it."
+        );
+    }
+
+    #[test]
+    fn test_extract_keeps_preformatted_code() {
+        let html = "<p>Use:</p><pre><code>fn main() {\n    if a &lt; b &amp;&amp; c {}\n}</code></pre><p>Done</p>";
+
+        let page = extract(html);
+
+        assert_eq!(
+            page.text,
+            "Use:\nfn main() {\n    if a < b && c {}\n}\nDone"
+        );
+    }
+
+    #[test]
+    fn test_extract_prefers_main_content() {
+        let html = "<div>Sign up for our newsletter</div>\
+                    <main><h2>Answer</h2><p>Run <b>docker system prune</b>.</p></main>\
+                    <div>Related posts</div>";
+
+        let page = extract(html);
+
+        assert_eq!(page.title, None);
+        assert_eq!(page.text, "Answer\nRun docker system prune.");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &lt;&#62;&#x41; b"), "a <>A b");
+        assert_eq!(decode_entities("R&D &unknown; &"), "R&D &unknown; &");
+        assert_eq!(extract("if a < b then").text, "if a < b then");
+    }
+}
@@ -0,0 +1,560 @@
+//! Canonical programming-language registry and heuristic language detection.
+//!
+//! Languages are stored by their registry `id` (e.g. `javascript`); aliases, file
+//! extensions and display names typed by users are normalized to it.
+
+/// A language known to the registry
+pub struct Language {
+    /// Canonical value stored in the database
+    pub id: &'static str,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    /// File extensions without the dot, plus exact file names (e.g. `Dockerfile`)
+    pub extensions: &'static [&'static str],
+    /// Shebang interpreters, without version suffixes
+    pub interpreters: &'static [&'static str],
+    /// Code markers; a leading `^` matches only at the start of a line
+    pub markers: &'static [&'static str],
+}
+
+/// Minimum number of distinct markers before a keyword guess is trusted
+const MIN_MARKER_SCORE: usize = 2;
+
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        id: "bash",
+        name: "Bash",
+        aliases: &["shell", "sh", "zsh", "shellscript"],
+        extensions: &["sh", "bash", "zsh"],
+        interpreters: &["bash", "sh", "zsh", "dash"],
+        markers: &[
+            "^echo ", "^export ", "^fi", "^done", "$(", "${", "^if [", "^sudo ", "| grep ",
+        ],
+    },
+    Language {
+        id: "powershell",
+        name: "PowerShell",
+        aliases: &["ps1", "pwsh", "posh"],
+        extensions: &["ps1", "psm1"],
+        interpreters: &["pwsh", "powershell"],
+        markers: &[
+            "Get-",
+            "Set-",
+            "Write-Host",
+            "$PSVersionTable",
+            "-ErrorAction",
+            "New-Object",
+        ],
+    },
+    Language {
+        id: "python",
+        name: "Python",
+        aliases: &["py", "python3"],
+        extensions: &["py", "pyw"],
+        interpreters: &["python", "python3"],
+        markers: &[
+            "^def ", "^import ", "^from ", "self.", "elif ", "print(", "__init__", "^class ",
+            "None",
+        ],
+    },
+    Language {
+        id: "javascript",
+        name: "JavaScript",
+        aliases: &["js", "node", "nodejs", "jsx"],
+        extensions: &["js", "mjs", "cjs", "jsx"],
+        interpreters: &["node"],
+        markers: &[
+            "const ",
+            "let ",
+            "function ",
+            "=> ",
+            "console.log",
+            "require(",
+            "module.exports",
+            "===",
+            "document.",
+        ],
+    },
+    Language {
+        id: "typescript",
+        name: "TypeScript",
+        aliases: &["ts", "tsx"],
+        extensions: &["ts", "tsx", "mts", "cts"],
+        interpreters: &["ts-node", "deno"],
+        markers: &[
+            "const ",
+            "let ",
+            "=> ",
+            "console.log",
+            "===",
+            "^interface ",
+            "^export interface ",
+            ": string",
+            ": number",
+            ": boolean",
+            "^type ",
+            "^export type ",
+        ],
+    },
+    Language {
+        id: "rust",
+        name: "Rust",
+        aliases: &["rs"],
+        extensions: &["rs"],
+        interpreters: &[],
+        markers: &[
+            "fn ", "let mut ", "impl ", "pub ", "::", "-> ", "&str", "Vec<", "println!", "#[derive",
+        ],
+    },
+    Language {
+        id: "go",
+        name: "Go",
+        aliases: &["golang"],
+        extensions: &["go"],
+        interpreters: &[],
+        markers: &[
+            "^package ",
+            "func ",
+            ":= ",
+            "fmt.",
+            "err != nil",
+            "^import (",
+        ],
+    },
+    Language {
+        id: "java",
+        name: "Java",
+        aliases: &[],
+        extensions: &["java"],
+        interpreters: &[],
+        markers: &[
+            "public class ",
+            "public static void",
+            "System.out.",
+            "private ",
+            "import java.",
+            "@Override",
+            "new ",
+        ],
+    },
+    Language {
+        id: "kotlin",
+        name: "Kotlin",
+        aliases: &["kt", "kts"],
+        extensions: &["kt", "kts"],
+        interpreters: &[],
+        markers: &["fun ", "val ", "var ", "println(", "data class ", "?."],
+    },
+    Language {
+        id: "csharp",
+        name: "C#",
+        aliases: &["c#", "cs", "dotnet"],
+        extensions: &["cs"],
+        interpreters: &[],
+        markers: &[
+            "using System",
+            "namespace ",
+            "public class ",
+            "Console.Write",
+            "var ",
+            "async Task",
+        ],
+    },
+    Language {
+        id: "c",
+        name: "C",
+        aliases: &["h"],
+        extensions: &["c", "h"],
+        interpreters: &[],
+        markers: &[
+            "^#include ",
+            "printf(",
+            "malloc(",
+            "int main(",
+            "->",
+            "NULL",
+        ],
+    },
+    Language {
+        id: "cpp",
+        name: "C++",
+        aliases: &["c++", "cxx", "hpp"],
+        extensions: &["cpp", "cc", "cxx", "hpp", "hh"],
+        interpreters: &[],
+        markers: &[
+            "^#include ",
+            "std::",
+            "cout",
+            "template<",
+            "nullptr",
+            "::",
+            "int main(",
+        ],
+    },
+    Language {
+        id: "ruby",
+        name: "Ruby",
+        aliases: &["rb"],
+        extensions: &["rb", "rake", "Gemfile"],
+        interpreters: &["ruby"],
+        markers: &[
+            "^def ",
+            "^end",
+            "puts ",
+            "^require ",
+            ".each do",
+            "attr_accessor",
+            "nil",
+        ],
+    },
+    Language {
+        id: "php",
+        name: "PHP",
+        aliases: &[],
+        extensions: &["php"],
+        interpreters: &["php"],
+        markers: &["<?php", "$this->", "echo ", "function ", "->", "=> "],
+    },
+    Language {
+        id: "swift",
+        name: "Swift",
+        aliases: &[],
+        extensions: &["swift"],
+        interpreters: &["swift"],
+        markers: &[
+            "func ",
+            "let ",
+            "var ",
+            "import Foundation",
+            "guard ",
+            "print(",
+        ],
+    },
+    Language {
+        id: "sql",
+        name: "SQL",
+        aliases: &["postgres", "postgresql", "mysql", "sqlite", "plsql"],
+        extensions: &["sql"],
+        interpreters: &[],
+        markers: &[
+            "^SELECT ",
+            "^INSERT INTO",
+            "^UPDATE ",
+            "^CREATE TABLE",
+            "^DELETE FROM",
+            " FROM ",
+            " WHERE ",
+            "JOIN ",
+        ],
+    },
+    Language {
+        id: "dockerfile",
+        name: "Dockerfile",
+        aliases: &["docker"],
+        extensions: &["Dockerfile", "dockerfile", "Containerfile"],
+        interpreters: &[],
+        markers: &[
+            "^FROM ",
+            "^RUN ",
+            "^COPY ",
+            "^WORKDIR ",
+            "^ENTRYPOINT ",
+            "^CMD ",
+            "^EXPOSE ",
+            "^ENV ",
+        ],
+    },
+    Language {
+        id: "yaml",
+        name: "YAML",
+        aliases: &["yml"],
+        extensions: &["yaml", "yml"],
+        interpreters: &[],
+        markers: &[],
+    },
+    Language {
+        id: "json",
+        name: "JSON",
+        aliases: &["jsonc"],
+        extensions: &["json", "jsonc"],
+        interpreters: &[],
+        markers: &[],
+    },
+    Language {
+        id: "toml",
+        name: "TOML",
+        aliases: &[],
+        extensions: &["toml"],
+        interpreters: &[],
+        markers: &[],
+    },
+    Language {
+        id: "xml",
+        name: "XML",
+        aliases: &["svg", "xsd"],
+        extensions: &["xml", "svg", "xsd", "csproj"],
+        interpreters: &[],
+        markers: &["<?xml", "</", "xmlns"],
+    },
+    Language {
+        id: "html",
+        name: "HTML",
+        aliases: &["htm", "xhtml"],
+        extensions: &["html", "htm"],
+        interpreters: &[],
+        markers: &["<!DOCTYPE", "<html", "<div", "</div>", "<body", "<head"],
+    },
+    Language {
+        id: "css",
+        name: "CSS",
+        aliases: &["scss", "sass", "less"],
+        extensions: &["css", "scss", "sass", "less"],
+        interpreters: &[],
+        markers: &["{", "color:", "margin:", "padding:", "display:", "px;"],
+    },
+    Language {
+        id: "markdown",
+        name: "Markdown",
+        aliases: &["md"],
+        extensions: &["md", "markdown"],
+        interpreters: &[],
+        markers: &["^# ", "^## ", "^- [", "```", "]("],
+    },
+    Language {
+        id: "makefile",
+        name: "Makefile",
+        aliases: &["make"],
+        extensions: &["Makefile", "makefile", "mk"],
+        interpreters: &["make"],
+        markers: &[],
+    },
+];
+
+/// Canonical id for a language name, alias or extension; `None` if unknown
+pub fn normalize(value: &str) -> Option<&'static str> {
+    let value = value.trim().trim_start_matches('.').to_lowercase();
+    if value.is_empty() {
+        return None;
+    }
+    LANGUAGES
+        .iter()
+        .find(|lang| {
+            lang.id == value
+                || lang.name.to_lowercase() == value
+                || lang.aliases.contains(&value.as_str())
+                || lang
+                    .extensions
+                    .iter()
+                    .any(|ext| ext.to_lowercase() == value)
+        })
+        .map(|lang| lang.id)
+}
+
+/// Value to store for a user-supplied language: its canonical id when known,
+/// otherwise the trimmed, lowercased input. Blank values become `None`.
+pub fn canonicalize(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(
+        normalize(trimmed)
+            .map(String::from)
+            .unwrap_or_else(|| trimmed.to_lowercase()),
+    )
+}
+
+fn from_filename(filename: &str) -> Option<&'static str> {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    let by_name = LANGUAGES
+        .iter()
+        .find(|lang| lang.extensions.contains(&name))
+        .map(|lang| lang.id);
+    by_name.or_else(|| {
+        let (_, ext) = name.rsplit_once('.')?;
+        let ext = ext.to_lowercase();
+        LANGUAGES
+            .iter()
+            .find(|lang| lang.extensions.contains(&ext.as_str()))
+            .map(|lang| lang.id)
+    })
+}
+
+fn from_shebang(content: &str) -> Option<&'static str> {
+    let first = content.lines().next()?.strip_prefix("#!")?;
+    let mut parts = first.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = parts.find(|p| !p.starts_with('-'))?;
+    }
+    // python3.11 -> python3, python3 -> python3
+    let program = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    LANGUAGES
+        .iter()
+        .find(|lang| {
+            lang.interpreters
+                .iter()
+                .any(|i| i.trim_end_matches(|c: char| c.is_ascii_digit()) == program)
+        })
+        .map(|lang| lang.id)
+}
+
+fn looks_like_json(content: &str) -> bool {
+    let trimmed = content.trim();
+    (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+}
+
+/// Every non-comment line is a `key: value`, `key:` or `- item` line
+fn looks_like_yaml(content: &str) -> bool {
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && *l != "---")
+        .collect();
+    if lines.len() < 2 {
+        return false;
+    }
+    lines.iter().all(|line| {
+        line.starts_with("- ")
+            || line.split_once(':').is_some_and(|(key, rest)| {
+                !key.is_empty() && !key.contains(' ') && (rest.is_empty() || rest.starts_with(' '))
+            })
+    })
+}
+
+fn marker_score(content: &str, markers: &[&str]) -> usize {
+    markers
+        .iter()
+        .filter(|marker| match marker.strip_prefix('^') {
+            Some(prefix) => content
+                .lines()
+                .any(|line| line.trim_start().starts_with(prefix)),
+            None => content.contains(*marker),
+        })
+        .count()
+}
+
+fn from_markers(content: &str) -> Option<&'static str> {
+    let mut best: Option<(&'static str, usize)> = None;
+    for lang in LANGUAGES {
+        let score = marker_score(content, lang.markers);
+        // Ties go to the language listed first
+        if score >= MIN_MARKER_SCORE && best.is_none_or(|(_, top)| score > top) {
+            best = Some((lang.id, score));
+        }
+    }
+    best.map(|(id, _)| id)
+}
+
+/// Guess the language of a code block from its filename, shebang, structure and keywords
+pub fn detect(content: &str, filename: Option<&str>) -> Option<&'static str> {
+    if let Some(lang) = filename.and_then(from_filename) {
+        return Some(lang);
+    }
+    if content.trim().is_empty() {
+        return None;
+    }
+    from_shebang(content)
+        .or_else(|| looks_like_json(content).then_some("json"))
+        .or_else(|| from_markers(content))
+        .or_else(|| looks_like_yaml(content).then_some("yaml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_aliases_and_names() {
+        assert_eq!(normalize("js"), Some("javascript"));
+        assert_eq!(normalize("JavaScript"), Some("javascript"));
+        assert_eq!(normalize(" javascript "), Some("javascript"));
+        assert_eq!(normalize("C#"), Some("csharp"));
+        assert_eq!(normalize(".yml"), Some("yaml"));
+        assert_eq!(normalize("brainfuck"), None);
+        assert_eq!(normalize(""), None);
+    }
+
+    #[test]
+    fn test_canonicalize_keeps_unknown_values() {
+        assert_eq!(canonicalize("Golang").as_deref(), Some("go"));
+        assert_eq!(canonicalize("  Nix ").as_deref(), Some("nix"));
+        assert_eq!(canonicalize("   "), None);
+    }
+
+    #[test]
+    fn test_detect_from_filename() {
+        assert_eq!(detect("", Some("Dockerfile")), Some("dockerfile"));
+        assert_eq!(detect("x", Some("deploy/compose.YML")), Some("yaml"));
+        assert_eq!(detect("x", Some("main.rs")), Some("rust"));
+    }
+
+    #[test]
+    fn test_detect_from_shebang() {
+        assert_eq!(detect("#!/bin/bash\nls", None), Some("bash"));
+        assert_eq!(
+            detect("#!/usr/bin/env python3\nx = 1", None),
+            Some("python")
+        );
+        assert_eq!(
+            detect("#!/usr/bin/env -S node --harmony\n", None),
+            Some("javascript")
+        );
+    }
+
+    #[test]
+    fn test_detect_structured_formats() {
+        assert_eq!(detect("{\"a\": [1, 2]}", None), Some("json"));
+        assert_eq!(
+            detect(
+                "services:\n  web:\n    image: nginx\n    ports:\n      - \"80:80\"",
+                None
+            ),
+            Some("yaml")
+        );
+    }
+
+    #[test]
+    fn test_detect_from_keywords() {
+        assert_eq!(
+            detect(
+                "fn main() {\n    let mut v: Vec<i32> = vec![];\n    println!(\"{:?}\", v);\n}",
+                None
+            ),
+            Some("rust")
+        );
+        assert_eq!(
+            detect("def greet(name):\n    print(f\"hi {name}\")\n", None),
+            Some("python")
+        );
+        assert_eq!(
+            detect("FROM rust:1\nWORKDIR /app\nRUN cargo build", None),
+            Some("dockerfile")
+        );
+        assert_eq!(
+            detect(
+                "const add = (a, b) => a + b;\nconsole.log(add(1, 2));",
+                None
+            ),
+            Some("javascript")
+        );
+        assert_eq!(
+            detect(
+                "interface User { name: string }\nconst u: User = { name: 'a' };\nconsole.log(u);",
+                None
+            ),
+            Some("typescript")
+        );
+        assert_eq!(
+            detect("SELECT id FROM users WHERE active = 1", None),
+            Some("sql")
+        );
+    }
+
+    #[test]
+    fn test_detect_gives_up_on_ambiguous_text() {
+        assert_eq!(detect("docker ps", None), None);
+        assert_eq!(detect("   ", None), None);
+    }
+}
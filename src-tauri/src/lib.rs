@@ -1,20 +1,20 @@
+mod active_window;
 mod ai;
+pub mod cli;
+mod clipboard;
 mod commands;
 mod db;
 mod errors;
+mod html;
+mod language;
+mod mcp;
 mod models;
+mod placeholders;
+mod server;
 
-use db::Database;
-use std::path::PathBuf;
-
-/// Get the default database path
-fn get_db_path() -> PathBuf {
-    let base = dirs::data_local_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("recallsnippet");
-
-    base.join("recallsnippet.db")
-}
+use ai::ollama::OllamaHttp;
+use db::{Database, Journal};
+use server::ApiServer;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -23,11 +23,22 @@ fn greet(name: &str) -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let db = Database::new(get_db_path()).expect("Failed to initialize database");
+    let db = Database::new(Database::default_path()).expect("Failed to initialize database");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .manage(db)
+        .manage(Journal::default())
+        .manage(OllamaHttp::default())
+        .manage(ApiServer::default())
+        .setup(|app| {
+            commands::spawn_trash_purge_task(app.handle().clone());
+            commands::spawn_model_health_check(app.handle().clone());
+            commands::spawn_api_server(app.handle().clone());
+            commands::init_capture_shortcut(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             commands::create_snippet,
@@ -35,10 +46,14 @@ pub fn run() {
             commands::list_snippets,
             commands::update_snippet,
             commands::delete_snippet,
+            commands::archive_snippet,
+            commands::get_inbox_counts,
             commands::list_tags,
             commands::create_tag,
             commands::delete_tag,
             commands::check_ollama_connection,
+            commands::diagnose_ollama_connection,
+            commands::reindex_embeddings,
             commands::list_ollama_models,
             commands::semantic_search,
             commands::generate_solution,
@@ -49,6 +64,55 @@ pub fn run() {
             commands::toggle_favorite,
             commands::restore_snippet,
             commands::permanent_delete_snippet,
+            commands::empty_trash,
+            commands::restore_all_from_trash,
+            commands::bulk_delete_snippets,
+            commands::bulk_restore_snippets,
+            commands::bulk_permanent_delete_snippets,
+            commands::bulk_set_favorite,
+            commands::bulk_add_tags,
+            commands::bulk_remove_tags,
+            commands::bulk_set_language,
+            commands::undo,
+            commands::redo,
+            commands::get_history_state,
+            commands::list_snippet_templates,
+            commands::get_snippet_template,
+            commands::create_snippet_template,
+            commands::update_snippet_template,
+            commands::delete_snippet_template,
+            commands::render_snippet_template,
+            commands::create_snippet_from_template,
+            commands::add_snippet_file,
+            commands::update_snippet_file,
+            commands::reorder_snippet_files,
+            commands::remove_snippet_file,
+            commands::get_related_snippets,
+            commands::get_snippet_links,
+            commands::create_snippet_link,
+            commands::delete_snippet_link,
+            commands::get_snippet_references,
+            commands::fetch_snippet_reference,
+            commands::delete_snippet_reference,
+            commands::find_duplicates,
+            commands::check_snippet_duplicates,
+            commands::merge_snippets,
+            commands::list_languages,
+            commands::detect_language,
+            commands::generate_title,
+            commands::summarize_snippet,
+            commands::summarize_missing_snippets,
+            commands::list_prompt_templates,
+            commands::update_prompt_template,
+            commands::reset_prompt_template,
+            commands::preview_prompt_template,
+            commands::check_model_health,
+            commands::pull_ollama_model,
+            commands::delete_ollama_model,
+            commands::show_ollama_model,
+            commands::get_api_server_status,
+            commands::regenerate_api_token,
+            commands::capture_clipboard,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,345 @@
+//! Model Context Protocol server over stdio, so coding agents can search, read and
+//! (unless read-only) add snippets. Messages are newline-delimited JSON-RPC 2.0.
+
+mod tools;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::ai::ollama::OllamaHttp;
+use crate::db::{Database, Journal};
+
+/// Protocol revision answered when the client does not ask for one
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub struct McpServer<'a> {
+    db: &'a Database,
+    http: &'a OllamaHttp,
+    journal: Journal,
+    read_only: bool,
+}
+
+impl<'a> McpServer<'a> {
+    pub fn new(db: &'a Database, http: &'a OllamaHttp, read_only: bool) -> Self {
+        Self {
+            db,
+            http,
+            journal: Journal::default(),
+            read_only,
+        }
+    }
+
+    /// Answer one JSON-RPC message; notifications get no reply
+    pub async fn handle(&self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => return Some(error(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            let id = request.get("id").cloned().unwrap_or(Value::Null);
+            return Some(error(id, INVALID_REQUEST, "Expected a JSON-RPC request"));
+        };
+        // Requests carry an id; notifications such as `notifications/initialized` do not
+        let id = request.get("id").cloned()?;
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        Some(match method {
+            "initialize" => success(id, self.initialize(&params)),
+            "ping" => success(id, json!({})),
+            "tools/list" => success(id, self.list_tools()),
+            "tools/call" => match self.call_tool(params).await {
+                Ok(result) => success(id, result),
+                Err(message) => error(id, INVALID_PARAMS, &message),
+            },
+            other => error(id, METHOD_NOT_FOUND, &format!("Unknown method: {other}")),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let version = params
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .unwrap_or(PROTOCOL_VERSION);
+        let mode = if self.read_only {
+            "The knowledge base is read-only for agents."
+        } else {
+            "New snippets can be saved with create_snippet."
+        };
+        json!({
+            "protocolVersion": version,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "recallsnippet", "version": env!("CARGO_PKG_VERSION") },
+            "instructions": format!(
+                "Search the team's code snippets before solving a problem from scratch and \
+                 cite the snippet ids you use. {mode}"
+            ),
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = tools::TOOLS
+            .iter()
+            .filter(|tool| !(self.read_only && tool.writes))
+            .map(tools::Tool::definition)
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    /// Protocol problems are JSON-RPC errors; failures inside a tool are reported in
+    /// the result with `isError` so the agent can read them
+    async fn call_tool(&self, params: Value) -> Result<Value, String> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or("Missing tool name")?;
+        let tool = tools::find(name).ok_or_else(|| format!("Unknown tool: {name}"))?;
+        if self.read_only && tool.writes {
+            return Ok(tool_error(&format!(
+                "{name} is disabled: the knowledge base is read-only for agents"
+            )));
+        }
+
+        let arguments = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        Ok(match tools::call(self, tool, arguments).await {
+            Ok(output) => json!({
+                "content": [{ "type": "text", "text": output.text }],
+                "structuredContent": output.structured,
+                "isError": false,
+            }),
+            Err(message) => tool_error(&message),
+        })
+    }
+}
+
+fn success(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn tool_error(message: &str) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": message }],
+        "isError": true,
+    })
+}
+
+/// Serve requests from stdin until it closes. Stdout carries only protocol messages.
+pub async fn serve_stdio(server: &McpServer<'_>) -> std::io::Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle(&line).await {
+            stdout.write_all(response.to_string().as_bytes()).await?;
+            stdout.write_all(b"\n").await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_db() -> Database {
+        let db = Database::new_in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Docker prune', 'Disk full');
+                 INSERT INTO snippet_files (id, snippet_id, language, content, position)
+                     VALUES ('f1', 's1', 'bash', 'docker system prune -af', 0);
+                 INSERT INTO tags (id, name) VALUES ('t1', 'docker');
+                 INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('s1', 't1');
+                 -- Nothing listens here, so Ollama calls fail fast
+                 UPDATE settings SET ollama_base_url = 'http://127.0.0.1:9', ollama_max_retries = 0;",
+            )
+        })
+        .unwrap();
+        db
+    }
+
+    async fn request(server: &McpServer<'_>, method: &str, params: Value) -> Value {
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        server.handle(&message.to_string()).await.unwrap()
+    }
+
+    async fn call(server: &McpServer<'_>, name: &str, arguments: Value) -> Value {
+        request(
+            server,
+            "tools/call",
+            json!({ "name": name, "arguments": arguments }),
+        )
+        .await["result"]
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_notifications() {
+        // Given
+        let (db, http) = (setup_db(), OllamaHttp::default());
+        let server = McpServer::new(&db, &http, true);
+
+        // When
+        let init = request(
+            &server,
+            "initialize",
+            json!({ "protocolVersion": "2025-03-26" }),
+        )
+        .await;
+        let notification = server
+            .handle(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await;
+
+        // Then
+        assert_eq!(init["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(init["result"]["serverInfo"]["name"], "recallsnippet");
+        assert!(init["result"]["capabilities"]["tools"].is_object());
+        assert!(notification.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_only_hides_and_refuses_write_tools() {
+        // Given
+        let (db, http) = (setup_db(), OllamaHttp::default());
+        let read_only = McpServer::new(&db, &http, true);
+        let writable = McpServer::new(&db, &http, false);
+
+        // When
+        let listed = request(&read_only, "tools/list", json!({})).await;
+        let refused = call(
+            &read_only,
+            "create_snippet",
+            json!({ "title": "t", "problem": "p" }),
+        )
+        .await;
+        let all = request(&writable, "tools/list", json!({})).await;
+
+        // Then
+        let names: Vec<&str> = listed["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["search_snippets", "get_snippet", "list_tags"]);
+        assert_eq!(refused["isError"], true);
+        assert_eq!(db.snippets().counts().unwrap().drafts, 0);
+        assert_eq!(all["result"]["tools"].as_array().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_read_tools() {
+        // Given
+        let (db, http) = (setup_db(), OllamaHttp::default());
+        let server = McpServer::new(&db, &http, true);
+
+        // When
+        let search = call(&server, "search_snippets", json!({ "query": "Docker" })).await;
+        let snippet = call(&server, "get_snippet", json!({ "id": "s1" })).await;
+        let missing = call(&server, "get_snippet", json!({ "id": "nope" })).await;
+        let tags = call(&server, "list_tags", json!({})).await;
+
+        // Then: keyword matches still come back without Ollama
+        assert_eq!(
+            search["structuredContent"]["results"][0]["snippet"]["id"],
+            "s1"
+        );
+        assert!(search["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Docker prune (id: s1)"));
+        assert!(snippet["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("docker system prune -af"));
+        assert_eq!(snippet["structuredContent"]["tags"][0]["name"], "docker");
+        assert_eq!(missing["isError"], true);
+        assert_eq!(tags["content"][0]["text"], "docker");
+    }
+
+    #[tokio::test]
+    async fn test_create_snippet_when_writes_are_allowed() {
+        // Given
+        let (db, http) = (setup_db(), OllamaHttp::default());
+        let server = McpServer::new(&db, &http, false);
+
+        // When
+        let created = call(
+            &server,
+            "create_snippet",
+            json!({ "title": "Rust lifetimes", "problem": "Borrow outlives", "tags": ["rust"] }),
+        )
+        .await;
+
+        // Then
+        assert_eq!(created["isError"], false);
+        let id = created["structuredContent"]["id"].as_str().unwrap();
+        let snippet = db.snippets().get(id).unwrap();
+        assert_eq!(snippet.tags[0].name, "rust");
+    }
+
+    #[tokio::test]
+    async fn test_refused_duplicate_creates_no_tags() {
+        // Given
+        let (db, http) = (setup_db(), OllamaHttp::default());
+        let server = McpServer::new(&db, &http, false);
+
+        // When
+        let refused = call(
+            &server,
+            "create_snippet",
+            json!({
+                "title": "Prune again",
+                "problem": "Disk full",
+                "code": "docker system prune -af",
+                "tags": ["cleanup"]
+            }),
+        )
+        .await;
+
+        // Then
+        assert_eq!(refused["isError"], true);
+        let tags: Vec<String> = db
+            .tags()
+            .by_usage()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(tags, vec!["docker"]);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_errors() {
+        // Given
+        let (db, http) = (setup_db(), OllamaHttp::default());
+        let server = McpServer::new(&db, &http, true);
+
+        // When
+        let parse = server.handle("{not json").await.unwrap();
+        let unknown_method = request(&server, "resources/list", json!({})).await;
+        let unknown_tool = request(&server, "tools/call", json!({ "name": "drop_db" })).await;
+        let bad_args = call(&server, "get_snippet", json!({})).await;
+
+        // Then
+        assert_eq!(parse["error"]["code"], PARSE_ERROR);
+        assert_eq!(unknown_method["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(unknown_tool["error"]["code"], INVALID_PARAMS);
+        assert_eq!(bad_args["isError"], true);
+    }
+}
@@ -0,0 +1,235 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::McpServer;
+use crate::commands::search::hybrid_search_internal;
+use crate::commands::snippet::{create_snippet_internal, ensure_not_duplicate};
+use crate::models::{CreateSnippetInput, SearchHit, SnippetStatus};
+
+/// Results returned by `search_snippets` when the agent gives no limit
+const DEFAULT_SEARCH_LIMIT: usize = 8;
+
+pub struct Tool {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Tools that change the knowledge base; hidden and refused in read-only mode
+    pub writes: bool,
+    schema: fn() -> Value,
+}
+
+impl Tool {
+    pub fn definition(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": (self.schema)(),
+            "annotations": { "readOnlyHint": !self.writes },
+        })
+    }
+}
+
+pub const TOOLS: &[Tool] = &[
+    Tool {
+        name: "search_snippets",
+        description: "Search the team's snippet knowledge base by meaning and keywords. \
+                      Returns snippet ids, titles and problems; cite snippets by id.",
+        writes: false,
+        schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string", "description": "What you are looking for" },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": 50 },
+                    "includeInactive": {
+                        "type": "boolean",
+                        "description": "Also search drafts and archived snippets",
+                    },
+                },
+                "required": ["query"],
+            })
+        },
+    },
+    Tool {
+        name: "get_snippet",
+        description: "Read one snippet in full: problem, solution, code files and tags.",
+        writes: false,
+        schema: || {
+            json!({
+                "type": "object",
+                "properties": { "id": { "type": "string" } },
+                "required": ["id"],
+            })
+        },
+    },
+    Tool {
+        name: "list_tags",
+        description: "List all tags used in the knowledge base.",
+        writes: false,
+        schema: || json!({ "type": "object", "properties": {} }),
+    },
+    Tool {
+        name: "create_snippet",
+        description: "Save a new snippet as a draft for the user to review. Refused when it \
+                      looks like a duplicate of an existing one.",
+        writes: true,
+        schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "problem": { "type": "string", "description": "What went wrong or what was needed" },
+                    "solution": { "type": "string" },
+                    "code": { "type": "string" },
+                    "language": { "type": "string" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "referenceUrl": { "type": "string" },
+                },
+                "required": ["title", "problem"],
+            })
+        },
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Tool> {
+    TOOLS.iter().find(|tool| tool.name == name)
+}
+
+/// Outcome of a tool call: text for the model plus the same data as JSON
+pub struct ToolOutput {
+    pub text: String,
+    pub structured: Value,
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(arguments: Value) -> Result<T, String> {
+    serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {e}"))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchArgs {
+    query: String,
+    limit: Option<usize>,
+    #[serde(default)]
+    include_inactive: bool,
+}
+
+#[derive(Deserialize)]
+struct GetArgs {
+    id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateArgs {
+    title: String,
+    problem: String,
+    solution: Option<String>,
+    code: Option<String>,
+    language: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    reference_url: Option<String>,
+}
+
+pub async fn call(
+    server: &McpServer<'_>,
+    tool: &Tool,
+    arguments: Value,
+) -> Result<ToolOutput, String> {
+    let db = server.db;
+    match tool.name {
+        "search_snippets" => {
+            let args: SearchArgs = parse(arguments)?;
+            let limit = args.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, 50);
+            let search = hybrid_search_internal(
+                db,
+                server.http,
+                &args.query,
+                Some(limit),
+                false,
+                args.include_inactive,
+            )
+            .await?;
+            Ok(ToolOutput {
+                text: search_text(&search.hits),
+                structured: json!({ "results": search.hits }),
+            })
+        }
+        "get_snippet" => {
+            let args: GetArgs = parse(arguments)?;
+            let snippet = db.snippets().get(&args.id)?;
+            Ok(ToolOutput {
+                text: snippet.to_markdown(),
+                structured: serde_json::to_value(&snippet).map_err(|e| e.to_string())?,
+            })
+        }
+        "list_tags" => {
+            let tags = db.tags().by_usage()?;
+            let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+            Ok(ToolOutput {
+                text: if names.is_empty() {
+                    "No tags yet.".to_string()
+                } else {
+                    names.join(", ")
+                },
+                structured: json!({ "tags": tags }),
+            })
+        }
+        "create_snippet" => {
+            let args: CreateArgs = parse(arguments)?;
+            let mut input = CreateSnippetInput {
+                title: args.title,
+                problem: args.problem,
+                solution: args.solution,
+                code: args.code,
+                code_language: args.language,
+                reference_url: args.reference_url,
+                tag_ids: vec![],
+                files: vec![],
+                allow_duplicate: false,
+                source_app: None,
+                captured_at: None,
+                status: SnippetStatus::Draft,
+            };
+            // Tags are only created once the snippet is known not to be refused
+            let draft_embedding = ensure_not_duplicate(db, server.http, &input).await?;
+            input.tag_ids = db
+                .tags()
+                .get_or_create_all(&args.tags)?
+                .into_iter()
+                .map(|tag| tag.id)
+                .collect();
+            let snippet =
+                create_snippet_internal(db, &server.journal, server.http, input, draft_embedding)
+                    .await?;
+            Ok(ToolOutput {
+                text: format!("Saved snippet {} ({})", snippet.id, snippet.title),
+                structured: serde_json::to_value(&snippet).map_err(|e| e.to_string())?,
+            })
+        }
+        other => Err(format!("Unknown tool: {other}")),
+    }
+}
+
+fn search_text(hits: &[SearchHit]) -> String {
+    if hits.is_empty() {
+        return "No matching snippets.".to_string();
+    }
+    hits.iter()
+        .enumerate()
+        .map(|(i, hit)| {
+            let score = hit
+                .score
+                .map(|score| format!(", relevance {:.0}%", score * 100.0))
+                .unwrap_or_default();
+            format!(
+                "{}. {} (id: {}{score})\n   {}",
+                i + 1,
+                hit.snippet.title,
+                hit.snippet.id,
+                hit.snippet.problem
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
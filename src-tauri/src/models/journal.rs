@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+/// Labels of the operations that undo/redo would currently act on
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryState {
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+    /// Why the most recent operation is missing from the history, if it could not be recorded
+    pub record_error: Option<String>,
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+
+/// Registry entry exposed to the frontend for language pickers
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageInfo {
+    pub id: String,
+    pub name: String,
+    pub aliases: Vec<String>,
+}
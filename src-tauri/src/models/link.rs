@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use super::SnippetSummary;
+
+/// Kind of a user-created link between two snippets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkType {
+    Supersedes,
+    SeeAlso,
+    CausedBy,
+}
+
+impl LinkType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkType::Supersedes => "supersedes",
+            LinkType::SeeAlso => "see_also",
+            LinkType::CausedBy => "caused_by",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "supersedes" => Some(LinkType::Supersedes),
+            "see_also" => Some(LinkType::SeeAlso),
+            "caused_by" => Some(LinkType::CausedBy),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetLink {
+    pub id: String,
+    pub source_id: String,
+    pub target_id: String,
+    pub link_type: LinkType,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateLinkInput {
+    pub source_id: String,
+    pub target_id: String,
+    pub link_type: LinkType,
+}
+
+/// A link as seen from one of its ends, with the snippet on the other end
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkedSnippet {
+    pub link_id: String,
+    pub link_type: LinkType,
+    pub snippet: SnippetSummary,
+}
+
+/// Links around a snippet: `outgoing` from it, `incoming` backlinks to it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetLinks {
+    pub outgoing: Vec<LinkedSnippet>,
+    pub incoming: Vec<LinkedSnippet>,
+}
@@ -1,5 +1,19 @@
+pub mod journal;
+pub mod language;
+pub mod link;
+pub mod ollama;
+pub mod prompt;
+pub mod reference;
 pub mod settings;
 pub mod snippet;
+pub mod template;
 
+pub use journal::*;
+pub use language::*;
+pub use link::*;
+pub use ollama::*;
+pub use prompt::*;
+pub use reference::*;
 pub use settings::*;
 pub use snippet::*;
+pub use template::*;
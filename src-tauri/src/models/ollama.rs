@@ -0,0 +1,65 @@
+use serde::Serialize;
+
+/// What `/api/show` reports about an installed model
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDetails {
+    pub name: String,
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization_level: Option<String>,
+    /// Modelfile parameters, one `name value` per line
+    pub parameters: Option<String>,
+    pub context_length: Option<u64>,
+    /// Vector size for embedding models
+    pub embedding_length: Option<u64>,
+    pub capabilities: Vec<String>,
+}
+
+/// Payload of the pull progress event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullProgress {
+    pub model: String,
+    pub status: String,
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+}
+
+/// A model named in settings and the features that use it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfiguredModel {
+    pub model: String,
+    pub used_for: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelHealth {
+    pub reachable: bool,
+    pub installed: Vec<String>,
+    /// Configured models that are not installed; empty when Ollama is unreachable
+    pub missing: Vec<ConfiguredModel>,
+}
+
+/// One check in the connection diagnostics: url, dns, connect or http
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticStep {
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Why Ollama is (un)reachable. Steps stop at the first failure.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaDiagnostics {
+    pub base_url: String,
+    pub reachable: bool,
+    pub version: Option<String>,
+    pub steps: Vec<DiagnosticStep>,
+}
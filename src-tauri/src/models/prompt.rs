@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptVariableInfo {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+/// A prompt used by an AI feature, with the user's edits if any
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptTemplate {
+    pub key: String,
+    pub name: String,
+    pub description: String,
+    pub template: String,
+    pub default_template: String,
+    pub variables: Vec<PromptVariableInfo>,
+    pub is_customized: bool,
+    pub updated_at: Option<String>,
+}
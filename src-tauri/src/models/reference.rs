@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// A web page a snippet refers to, with a local copy of its readable text so the
+/// context survives the page going away
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetReference {
+    pub id: String,
+    pub snippet_id: String,
+    pub url: String,
+    pub title: Option<String>,
+    /// Readable text extracted from the page when it was last fetched
+    pub content: String,
+    pub fetched_at: String,
+    pub created_at: String,
+}
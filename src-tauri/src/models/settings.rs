@@ -9,6 +9,83 @@ pub struct Settings {
     pub embedding_model: String,
     pub search_limit: i32,
     pub data_path: Option<String>,
+    /// Days a snippet stays in the trash before it is purged (0 keeps trash forever)
+    pub trash_retention_days: i32,
+    /// Ask the LLM for a snippet's language when heuristics cannot tell
+    pub detect_language_with_llm: bool,
+    /// Minimum similarity for a snippet to be used as chat context
+    pub chat_relevance_threshold: f64,
+    /// Number of snippets retrieved for chat context
+    pub chat_top_k: i32,
+    /// Approximate token budget for snippet content in chat prompts
+    pub chat_context_tokens: i32,
+    /// Per-feature models; `None` falls back to `llm_model`
+    pub chat_model: Option<String>,
+    pub solution_model: Option<String>,
+    pub tags_model: Option<String>,
+    pub summary_model: Option<String>,
+    pub language_model: Option<String>,
+    /// Generation options passed to Ollama; `None` keeps the model's default
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub seed: Option<i64>,
+    pub num_ctx: Option<i32>,
+    /// Maximum tokens to generate
+    pub num_predict: Option<i32>,
+    /// How long Ollama keeps the model loaded, e.g. "5m", "1h" or "-1"
+    pub keep_alive: Option<String>,
+    /// Ollama connection settings, in seconds
+    pub ollama_connect_timeout_secs: i32,
+    pub ollama_request_timeout_secs: i32,
+    pub ollama_generate_timeout_secs: i32,
+    /// Retries after a connection error or a busy response from Ollama
+    pub ollama_max_retries: i32,
+    /// Texts per `/api/embed` request when indexing many snippets
+    pub embedding_batch_size: i32,
+    /// Embedding requests sent at the same time when indexing
+    pub embedding_concurrency: i32,
+    /// Storage format for embedding vectors: "f32", "f16" or "int8"
+    pub embedding_encoding: String,
+    /// Serve the HTTP API on 127.0.0.1 for editor plugins and scripts
+    pub api_server_enabled: bool,
+    pub api_server_port: i32,
+    /// Bearer token every API request must present
+    pub api_token: String,
+    /// Keep MCP agents from creating or changing snippets
+    pub mcp_read_only: bool,
+    /// Global shortcut that captures the clipboard as a new snippet; empty disables it
+    pub capture_shortcut: String,
+    /// Record the title of the window that was active when capturing
+    pub capture_window_title: bool,
+    /// Add the archived text of reference pages to snippet embeddings
+    pub embed_references: bool,
+}
+
+/// AI features that can use their own model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiFeature {
+    Chat,
+    Solution,
+    Tags,
+    Summary,
+    Language,
+}
+
+impl Settings {
+    /// Model configured for `feature`, falling back to the general `llm_model`
+    pub fn model_for(&self, feature: AiFeature) -> &str {
+        let model = match feature {
+            AiFeature::Chat => &self.chat_model,
+            AiFeature::Solution => &self.solution_model,
+            AiFeature::Tags => &self.tags_model,
+            AiFeature::Summary => &self.summary_model,
+            AiFeature::Language => &self.language_model,
+        };
+        model
+            .as_deref()
+            .filter(|m| !m.trim().is_empty())
+            .unwrap_or(&self.llm_model)
+    }
 }
 
 impl Default for Settings {
@@ -20,6 +97,36 @@ impl Default for Settings {
             embedding_model: "nomic-embed-text".to_string(),
             search_limit: 10,
             data_path: None,
+            trash_retention_days: 30,
+            detect_language_with_llm: false,
+            chat_relevance_threshold: 0.3,
+            chat_top_k: 5,
+            chat_context_tokens: 3000,
+            chat_model: None,
+            solution_model: None,
+            tags_model: None,
+            summary_model: None,
+            language_model: None,
+            temperature: None,
+            top_p: None,
+            seed: None,
+            num_ctx: None,
+            num_predict: None,
+            keep_alive: None,
+            ollama_connect_timeout_secs: 5,
+            ollama_request_timeout_secs: 30,
+            ollama_generate_timeout_secs: 120,
+            ollama_max_retries: 2,
+            embedding_batch_size: 32,
+            embedding_concurrency: 2,
+            embedding_encoding: "f32".to_string(),
+            api_server_enabled: false,
+            api_server_port: 47811,
+            api_token: String::new(),
+            mcp_read_only: true,
+            capture_shortcut: "CmdOrCtrl+Shift+Alt+S".to_string(),
+            capture_window_title: true,
+            embed_references: false,
         }
     }
 }
@@ -33,4 +140,53 @@ pub struct UpdateSettingsInput {
     pub embedding_model: Option<String>,
     pub search_limit: Option<i32>,
     pub data_path: Option<String>,
+    pub trash_retention_days: Option<i32>,
+    pub detect_language_with_llm: Option<bool>,
+    pub chat_relevance_threshold: Option<f64>,
+    pub chat_top_k: Option<i32>,
+    pub chat_context_tokens: Option<i32>,
+    /// Per-feature models; an empty string goes back to `llm_model`
+    pub chat_model: Option<String>,
+    pub solution_model: Option<String>,
+    pub tags_model: Option<String>,
+    pub summary_model: Option<String>,
+    pub language_model: Option<String>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub seed: Option<i64>,
+    pub num_ctx: Option<i32>,
+    pub num_predict: Option<i32>,
+    pub keep_alive: Option<String>,
+    pub ollama_connect_timeout_secs: Option<i32>,
+    pub ollama_request_timeout_secs: Option<i32>,
+    pub ollama_generate_timeout_secs: Option<i32>,
+    pub ollama_max_retries: Option<i32>,
+    pub embedding_batch_size: Option<i32>,
+    pub embedding_concurrency: Option<i32>,
+    /// Changing the encoding rewrites every stored vector
+    pub embedding_encoding: Option<String>,
+    /// Starts or stops the API server
+    pub api_server_enabled: Option<bool>,
+    /// Restarts a running API server on the new port
+    pub api_server_port: Option<i32>,
+    pub mcp_read_only: Option<bool>,
+    /// Re-registers the global shortcut; an empty string unregisters it
+    pub capture_shortcut: Option<String>,
+    pub capture_window_title: Option<bool>,
+    /// Takes effect for snippets embedded afterwards; reindex all to apply it everywhere
+    pub embed_references: Option<bool>,
+    /// Clear all generation options before applying the ones given here
+    #[serde(default)]
+    pub reset_generation_options: bool,
+}
+
+/// Whether the local HTTP API is listening, and where
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerStatus {
+    pub running: bool,
+    /// Base URL such as `http://127.0.0.1:47811`
+    pub url: Option<String>,
+    /// Why the server failed to start or stopped, when it did
+    pub error: Option<String>,
 }
@@ -6,6 +6,17 @@ pub struct SnippetSource {
     pub id: String,
     pub title: String,
     pub score: f64,
+    /// Number the snippet was given in the prompt, as cited in the answer (`[n]`)
+    pub citation: usize,
+    /// Where the answer cites this snippet, in UTF-16 code units
+    pub positions: Vec<CitationSpan>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationSpan {
+    pub start: usize,
+    pub end: usize,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -15,6 +26,38 @@ pub struct AiChatResponse {
     pub sources: Vec<SnippetSource>,
 }
 
+/// Where a snippet is in triage. Search, chat and the main list only see active ones
+/// unless asked otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnippetStatus {
+    /// Captured, imported or added by an agent, waiting to be reviewed
+    Draft,
+    #[default]
+    Active,
+    /// Kept for reference but out of the way; unlike the trash, never purged
+    Archived,
+}
+
+impl SnippetStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SnippetStatus::Draft => "draft",
+            SnippetStatus::Active => "active",
+            SnippetStatus::Archived => "archived",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "draft" => Some(SnippetStatus::Draft),
+            "active" => Some(SnippetStatus::Active),
+            "archived" => Some(SnippetStatus::Archived),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Snippet {
@@ -22,16 +65,85 @@ pub struct Snippet {
     pub title: String,
     pub problem: String,
     pub solution: Option<String>,
+    /// LLM-generated one-paragraph summary
+    pub summary: Option<String>,
+    /// Content of the first file, kept for single-block clients
     pub code: Option<String>,
     pub code_language: Option<String>,
     pub reference_url: Option<String>,
     pub tags: Vec<Tag>,
+    pub files: Vec<SnippetFile>,
     pub created_at: String,
     pub updated_at: String,
     pub is_favorite: bool,
     pub is_deleted: bool,
     pub deleted_at: Option<String>,
     pub last_accessed_at: Option<String>,
+    /// Application the snippet was quick-captured from
+    pub source_app: Option<String>,
+    /// When quick capture created the snippet; `None` for snippets written by hand
+    pub captured_at: Option<String>,
+    /// Missing in exports written before statuses existed
+    #[serde(default)]
+    pub status: SnippetStatus,
+}
+
+impl Snippet {
+    /// Plain markdown rendering for terminals and agents
+    pub fn to_markdown(&self) -> String {
+        let mut text = format!("# {}\n", self.title);
+        text.push_str(&format!("id: {}\n", self.id));
+        if !self.tags.is_empty() {
+            let tags: Vec<&str> = self.tags.iter().map(|t| t.name.as_str()).collect();
+            text.push_str(&format!("tags: {}\n", tags.join(", ")));
+        }
+        if let Some(url) = &self.reference_url {
+            text.push_str(&format!("reference: {url}\n"));
+        }
+        text.push_str(&format!("\n## Problem\n{}\n", self.problem));
+        if let Some(solution) = &self.solution {
+            text.push_str(&format!("\n## Solution\n{solution}\n"));
+        }
+        for file in &self.files {
+            text.push('\n');
+            if let Some(filename) = &file.filename {
+                text.push_str(&format!("{filename}:\n"));
+            }
+            let language = file.language.as_deref().unwrap_or_default();
+            text.push_str(&format!(
+                "```{language}\n{}\n```\n",
+                file.content.trim_end()
+            ));
+        }
+        text.trim_end().to_string()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetFile {
+    pub id: String,
+    pub snippet_id: String,
+    pub filename: Option<String>,
+    pub language: Option<String>,
+    pub content: String,
+    pub position: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetFileInput {
+    pub filename: Option<String>,
+    pub language: Option<String>,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSnippetFileInput {
+    pub filename: Option<String>,
+    pub language: Option<String>,
+    pub content: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,12 +152,23 @@ pub struct Tag {
     pub name: String,
 }
 
+/// Suggested tags split into ones that already exist and ones that would be created
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagSuggestions {
+    pub existing: Vec<Tag>,
+    pub new_tags: Vec<String>,
+    /// Ranked by embedding similarity because the LLM was unavailable
+    pub fallback: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SnippetSummary {
     pub id: String,
     pub title: String,
     pub problem: String,
+    pub summary: Option<String>,
     pub code_language: Option<String>,
     pub code_preview: Option<String>,
     pub tags: Vec<Tag>,
@@ -54,6 +177,7 @@ pub struct SnippetSummary {
     pub is_deleted: bool,
     pub deleted_at: Option<String>,
     pub last_accessed_at: Option<String>,
+    pub status: SnippetStatus,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,10 +186,69 @@ pub struct CreateSnippetInput {
     pub title: String,
     pub problem: String,
     pub solution: Option<String>,
+    /// Single code block, stored as the first file
     pub code: Option<String>,
     pub code_language: Option<String>,
     pub reference_url: Option<String>,
     pub tag_ids: Vec<String>,
+    /// Additional files, stored after `code`
+    #[serde(default)]
+    pub files: Vec<SnippetFileInput>,
+    /// Skip the duplicate check and insert even if similar snippets exist
+    #[serde(default)]
+    pub allow_duplicate: bool,
+    #[serde(default)]
+    pub source_app: Option<String>,
+    #[serde(default)]
+    pub captured_at: Option<String>,
+    #[serde(default)]
+    pub status: SnippetStatus,
+}
+
+impl CreateSnippetInput {
+    /// Unsaved snippet with the input's content, for prompts and embeddings before insert
+    pub fn draft(&self) -> Snippet {
+        let code = self.code.as_ref().map(|content| SnippetFileInput {
+            filename: None,
+            language: self.code_language.clone(),
+            content: content.clone(),
+        });
+        let files = code
+            .iter()
+            .chain(&self.files)
+            .enumerate()
+            .map(|(position, file)| SnippetFile {
+                id: String::new(),
+                snippet_id: String::new(),
+                filename: file.filename.clone(),
+                language: file.language.clone(),
+                content: file.content.clone(),
+                position: position as i32,
+            })
+            .collect();
+
+        Snippet {
+            id: String::new(),
+            title: self.title.clone(),
+            problem: self.problem.clone(),
+            solution: self.solution.clone(),
+            summary: None,
+            code: self.code.clone(),
+            code_language: self.code_language.clone(),
+            reference_url: self.reference_url.clone(),
+            tags: vec![],
+            files,
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_favorite: false,
+            is_deleted: false,
+            deleted_at: None,
+            last_accessed_at: None,
+            source_app: self.source_app.clone(),
+            captured_at: self.captured_at.clone(),
+            status: self.status,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,10 +257,13 @@ pub struct UpdateSnippetInput {
     pub title: Option<String>,
     pub problem: Option<String>,
     pub solution: Option<String>,
+    /// Replaces the content of the first file; an empty string removes it
     pub code: Option<String>,
     pub code_language: Option<String>,
     pub reference_url: Option<String>,
     pub tag_ids: Option<Vec<String>>,
+    /// Moves the snippet between draft, active and archived
+    pub status: Option<SnippetStatus>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -85,10 +271,26 @@ pub struct UpdateSnippetInput {
 pub struct SnippetFilter {
     pub language: Option<String>,
     pub search: Option<String>,
+    /// Only snippets carrying the tag with this name
+    pub tag: Option<String>,
     pub favorites_only: Option<bool>,
     pub trash_only: Option<bool>,
     pub include_deleted: Option<bool>,
     pub recent_first: Option<bool>,
+    /// Only snippets in this state [default: active]; the trash view lists every state
+    pub status: Option<SnippetStatus>,
+    /// Drafts, active and archived snippets together; overrides `status`
+    pub any_status: Option<bool>,
+}
+
+/// Snippet counts for the sidebar; the status counts leave out the trash
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InboxCounts {
+    pub drafts: i64,
+    pub active: i64,
+    pub archived: i64,
+    pub trash: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,3 +299,68 @@ pub struct SearchResult {
     pub snippet: SnippetSummary,
     pub score: f64,
 }
+
+/// A quick capture and the existing snippets it likely duplicates
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedSnippet {
+    pub snippet: Snippet,
+    pub duplicates: Vec<SearchResult>,
+}
+
+/// Which half of a hybrid search found a snippet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitKind {
+    Semantic,
+    Keyword,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub snippet: SnippetSummary,
+    /// Similarity for semantic hits; keyword hits have none
+    pub score: Option<f64>,
+    pub kind: HitKind,
+}
+
+/// Group of snippets that look like the same entry recorded more than once
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCluster {
+    /// Oldest first
+    pub snippets: Vec<SnippetSummary>,
+    /// Highest similarity between two members; 1.0 for identical code
+    pub similarity: f64,
+    /// Whether some members share the same normalized code
+    pub same_code: bool,
+}
+
+/// Outcome of a bulk operation for a single snippet
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkItemResult {
+    pub id: String,
+    pub success: bool,
+    /// Whether the operation actually modified the snippet
+    pub changed: bool,
+    pub error: Option<String>,
+}
+
+/// Payload of the reindex progress event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReindexProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReindexReport {
+    pub total: usize,
+    pub embedded: usize,
+    /// Snippets whose embedding could not be created or saved
+    pub failed: Vec<BulkItemResult>,
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use super::Tag;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub title: String,
+    pub problem: String,
+    pub solution: Option<String>,
+    pub code: Option<String>,
+    pub code_language: Option<String>,
+    pub reference_url: Option<String>,
+    pub tags: Vec<Tag>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateTemplateInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub title: String,
+    pub problem: String,
+    pub solution: Option<String>,
+    pub code: Option<String>,
+    pub code_language: Option<String>,
+    pub reference_url: Option<String>,
+    pub tag_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTemplateInput {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub title: Option<String>,
+    pub problem: Option<String>,
+    pub solution: Option<String>,
+    pub code: Option<String>,
+    pub code_language: Option<String>,
+    pub reference_url: Option<String>,
+    pub tag_ids: Option<Vec<String>>,
+}
@@ -0,0 +1,103 @@
+/// Placeholder names may contain letters, digits, `_`, `-` and `.`
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Call `visit` for every `{{name}}` placeholder with its byte range and trimmed name
+fn scan<F>(template: &str, mut visit: F)
+where
+    F: FnMut(std::ops::Range<usize>, &str),
+{
+    let mut offset = 0;
+    while let Some(start) = template[offset..].find("{{") {
+        let start = offset + start;
+        let Some(len) = template[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len + 2;
+        let name = template[start + 2..end - 2].trim();
+        if is_valid_name(name) {
+            visit(start..end, name);
+        }
+        offset = end;
+    }
+}
+
+/// Replace `{{name}}` placeholders using `lookup`.
+/// Placeholders the lookup doesn't know are left untouched.
+pub fn render<F>(template: &str, mut lookup: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut out = String::with_capacity(template.len());
+    let mut last = 0;
+    scan(template, |range, name| {
+        if let Some(value) = lookup(name) {
+            out.push_str(&template[last..range.start]);
+            out.push_str(&value);
+            last = range.end;
+        }
+    });
+    out.push_str(&template[last..]);
+    out
+}
+
+/// Unique placeholder names in order of first appearance
+pub fn names(template: &str) -> Vec<String> {
+    let mut found: Vec<String> = vec![];
+    scan(template, |_, name| {
+        if !found.iter().any(|n| n == name) {
+            found.push(name.to_string());
+        }
+    });
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "date" => Some("2026-02-09".to_string()),
+            "user" => Some("jeff".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_render_replaces_known_placeholders() {
+        let rendered = render("Postmortem {{date}} by {{ user }}", lookup);
+        assert_eq!(rendered, "Postmortem 2026-02-09 by jeff");
+    }
+
+    #[test]
+    fn test_render_keeps_unknown_placeholders() {
+        let rendered = render("{{date}} {{unknown}}", lookup);
+        assert_eq!(rendered, "2026-02-09 {{unknown}}");
+    }
+
+    #[test]
+    fn test_render_ignores_unclosed_and_invalid_braces() {
+        assert_eq!(render("{{date", lookup), "{{date");
+        assert_eq!(render("{{not a name}}", lookup), "{{not a name}}");
+        assert_eq!(render("fn f() {{ }}", lookup), "fn f() {{ }}");
+    }
+
+    #[test]
+    fn test_render_does_not_expand_values_recursively() {
+        let rendered = render("{{a}}", |name| (name == "a").then(|| "{{a}}".to_string()));
+        assert_eq!(rendered, "{{a}}");
+    }
+
+    #[test]
+    fn test_names_are_unique_in_order() {
+        assert_eq!(
+            names("{{date}} {{clipboard}} {{ date }}"),
+            vec!["date".to_string(), "clipboard".to_string()]
+        );
+    }
+}
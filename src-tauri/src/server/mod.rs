@@ -0,0 +1,238 @@
+//! Optional HTTP/JSON API on 127.0.0.1 so editor plugins and scripts can use the
+//! running app's snippets, tags, search and chat.
+
+mod routes;
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::models::{ApiServerStatus, Settings};
+
+pub use routes::{router, ApiBackend};
+
+struct Running {
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl Running {
+    async fn stop(self) {
+        let _ = self.shutdown.send(());
+        let _ = self.task.await;
+    }
+}
+
+/// Handle to the API server; managed by Tauri and shared by the settings commands
+#[derive(Default)]
+pub struct ApiServer {
+    running: Mutex<Option<Running>>,
+    /// Why the server last failed to start or stopped on its own, reported in the status
+    last_error: Arc<std::sync::Mutex<Option<String>>>,
+}
+
+impl ApiServer {
+    fn set_error(&self, error: Option<String>) {
+        *self.last_error.lock().unwrap() = error;
+    }
+
+    /// Listen on `127.0.0.1:port`, replacing a server that is already running
+    pub async fn start<B: ApiBackend>(&self, backend: B, port: u16) -> Result<SocketAddr, String> {
+        let mut running = self.running.lock().await;
+        if let Some(current) = running.take() {
+            current.stop().await;
+        }
+
+        let bound = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .await
+            .and_then(|listener| Ok((listener.local_addr()?, listener)))
+            .map_err(|e| format!("Cannot listen on 127.0.0.1:{port}: {e}"));
+        let (addr, listener) = match bound {
+            Ok(bound) => bound,
+            Err(e) => {
+                self.set_error(Some(e.clone()));
+                return Err(e);
+            }
+        };
+        self.set_error(None);
+
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let last_error = self.last_error.clone();
+        let task = tokio::spawn(async move {
+            let server = axum::serve(listener, router(backend)).with_graceful_shutdown(async {
+                let _ = stopped.await;
+            });
+            if let Err(e) = server.await {
+                *last_error.lock().unwrap() = Some(format!("API server stopped: {e}"));
+            }
+        });
+
+        *running = Some(Running {
+            addr,
+            shutdown,
+            task,
+        });
+        Ok(addr)
+    }
+
+    pub async fn stop(&self) {
+        if let Some(current) = self.running.lock().await.take() {
+            current.stop().await;
+        }
+        self.set_error(None);
+    }
+
+    pub async fn status(&self) -> ApiServerStatus {
+        let addr = self
+            .running
+            .lock()
+            .await
+            .as_ref()
+            .filter(|r| !r.task.is_finished())
+            .map(|r| r.addr);
+        ApiServerStatus {
+            running: addr.is_some(),
+            url: addr.map(|addr| format!("http://{addr}")),
+            error: self.last_error.lock().unwrap().clone(),
+        }
+    }
+
+    /// Start, stop or move the server so it matches the settings
+    pub async fn apply<B: ApiBackend>(
+        &self,
+        backend: B,
+        settings: &Settings,
+    ) -> Result<(), String> {
+        if !settings.api_server_enabled {
+            self.stop().await;
+            return Ok(());
+        }
+
+        let Ok(port) = u16::try_from(settings.api_server_port) else {
+            let error = format!("Invalid API port: {}", settings.api_server_port);
+            self.set_error(Some(error.clone()));
+            return Err(error);
+        };
+        let current = self.running.lock().await.as_ref().map(|r| r.addr.port());
+        if current == Some(port) {
+            return Ok(());
+        }
+        self.start(backend, port).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::ai::ollama::OllamaHttp;
+    use crate::db::{Database, Journal};
+
+    #[derive(Clone)]
+    pub(super) struct TestBackend(Arc<(Database, Journal, OllamaHttp)>);
+
+    impl ApiBackend for TestBackend {
+        fn database(&self) -> &Database {
+            &self.0 .0
+        }
+
+        fn journal(&self) -> &Journal {
+            &self.0 .1
+        }
+
+        fn http(&self) -> &OllamaHttp {
+            &self.0 .2
+        }
+    }
+
+    pub(super) fn backend() -> TestBackend {
+        TestBackend(Arc::new((
+            Database::new_in_memory().unwrap(),
+            Journal::default(),
+            OllamaHttp::default(),
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_start_serves_on_localhost_until_stopped() {
+        // Given
+        let server = ApiServer::default();
+
+        // When
+        let addr = server.start(backend(), 0).await.unwrap();
+        let health = reqwest::get(format!("http://{addr}/api/health"))
+            .await
+            .unwrap();
+
+        // Then
+        assert!(addr.ip().is_loopback());
+        assert!(health.status().is_success());
+        assert!(server.status().await.running);
+
+        server.stop().await;
+        assert!(!server.status().await.running);
+        assert!(reqwest::get(format!("http://{addr}/api/health"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_follows_enabled_flag() {
+        // Given
+        let server = ApiServer::default();
+        let backend = backend();
+        let addr = server.start(backend.clone(), 0).await.unwrap();
+        let enabled = Settings {
+            api_server_enabled: true,
+            api_server_port: addr.port() as i32,
+            ..Default::default()
+        };
+
+        // When: same port keeps the running server
+        server.apply(backend.clone(), &enabled).await.unwrap();
+
+        // Then
+        assert_eq!(
+            server.status().await.url,
+            Some(format!("http://127.0.0.1:{}", addr.port()))
+        );
+
+        // When: disabled
+        let disabled = Settings {
+            api_server_enabled: false,
+            ..enabled
+        };
+        server.apply(backend, &disabled).await.unwrap();
+
+        // Then
+        assert!(!server.status().await.running);
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_why_the_server_is_not_running() {
+        // Given
+        let server = ApiServer::default();
+        let taken = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let port = taken.local_addr().unwrap().port();
+
+        // When
+        let started = server.start(backend(), port).await;
+
+        // Then
+        let status = server.status().await;
+        assert!(started.is_err());
+        assert!(!status.running);
+        assert_eq!(status.error, started.err());
+
+        // A later successful start clears it
+        server.start(backend(), 0).await.unwrap();
+        assert_eq!(server.status().await.error, None);
+        server.stop().await;
+    }
+}
@@ -0,0 +1,489 @@
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::ai::ollama::OllamaHttp;
+use crate::commands::ai::{ai_chat_internal, SnippetContext};
+use crate::commands::search::semantic_search_internal;
+use crate::commands::snippet::{
+    create_snippet_internal, delete_snippet_internal, ensure_not_duplicate, get_snippet_internal,
+    update_snippet_internal,
+};
+use crate::commands::tag::create_tag_internal;
+use crate::db::{Database, Journal};
+use crate::errors::AppError;
+use crate::models::{
+    AiChatResponse, CreateSnippetInput, SearchResult, Snippet, SnippetFilter, SnippetSummary, Tag,
+    UpdateSnippetInput,
+};
+
+/// Shared state the handlers run against. The app serves its managed state; tests
+/// provide their own.
+pub trait ApiBackend: Clone + Send + Sync + 'static {
+    fn database(&self) -> &Database;
+    fn journal(&self) -> &Journal;
+    fn http(&self) -> &OllamaHttp;
+}
+
+impl ApiBackend for tauri::AppHandle {
+    fn database(&self) -> &Database {
+        tauri::Manager::state::<Database>(self).inner()
+    }
+
+    fn journal(&self) -> &Journal {
+        tauri::Manager::state::<Journal>(self).inner()
+    }
+
+    fn http(&self) -> &OllamaHttp {
+        tauri::Manager::state::<OllamaHttp>(self).inner()
+    }
+}
+
+/// An error response: `{"error": "..."}` with the given status
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Duplicate(_) => StatusCode::CONFLICT,
+            AppError::Ollama(_) => StatusCode::BAD_GATEWAY,
+            AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        error_response(status, self.to_string())
+    }
+}
+
+type ApiResult<T> = Result<T, AppError>;
+
+/// All endpoints under `/api`; everything except `/api/health` needs the bearer token
+pub fn router<B: ApiBackend>(backend: B) -> Router {
+    let protected = Router::new()
+        .route(
+            "/api/snippets",
+            get(list_snippets::<B>).post(create_snippet::<B>),
+        )
+        .route(
+            "/api/snippets/{id}",
+            get(get_snippet::<B>)
+                .patch(update_snippet::<B>)
+                .delete(delete_snippet::<B>),
+        )
+        .route("/api/tags", get(list_tags::<B>).post(create_tag::<B>))
+        .route("/api/search", get(search::<B>))
+        .route("/api/chat", axum::routing::post(chat::<B>))
+        .route_layer(middleware::from_fn_with_state(
+            backend.clone(),
+            require_token::<B>,
+        ));
+
+    Router::new()
+        .route("/api/health", get(health))
+        .merge(protected)
+        .with_state(backend)
+}
+
+/// Compare without short-circuiting so response timing does not leak the token
+fn token_matches(expected: &str, given: &str) -> bool {
+    !expected.is_empty()
+        && expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn require_token<B: ApiBackend>(
+    State(backend): State<B>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    // Read on every request so a regenerated token takes effect immediately
+    let expected = backend.database().settings().get_or_default().api_token;
+    if !token_matches(&expected, given) {
+        return error_response(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid API token".to_string(),
+        );
+    }
+    next.run(request).await
+}
+
+async fn health() -> Json<serde_json::Value> {
+    Json(json!({
+        "name": "recallsnippet",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+async fn list_snippets<B: ApiBackend>(
+    State(backend): State<B>,
+    Query(filter): Query<SnippetFilter>,
+) -> ApiResult<Json<Vec<SnippetSummary>>> {
+    Ok(Json(backend.database().snippets().list(&filter)?))
+}
+
+async fn create_snippet<B: ApiBackend>(
+    State(backend): State<B>,
+    Json(input): Json<CreateSnippetInput>,
+) -> ApiResult<(StatusCode, Json<Snippet>)> {
+    let (db, http) = (backend.database(), backend.http());
+    let draft_embedding = ensure_not_duplicate(db, http, &input).await?;
+    let snippet =
+        create_snippet_internal(db, backend.journal(), http, input, draft_embedding).await?;
+    Ok((StatusCode::CREATED, Json(snippet)))
+}
+
+async fn get_snippet<B: ApiBackend>(
+    State(backend): State<B>,
+    Path(id): Path<String>,
+) -> ApiResult<Json<Snippet>> {
+    Ok(Json(get_snippet_internal(backend.database(), &id)?))
+}
+
+async fn update_snippet<B: ApiBackend>(
+    State(backend): State<B>,
+    Path(id): Path<String>,
+    Json(input): Json<UpdateSnippetInput>,
+) -> ApiResult<Json<Snippet>> {
+    let snippet = update_snippet_internal(
+        backend.database(),
+        backend.journal(),
+        backend.http(),
+        id,
+        input,
+    )
+    .await?;
+    Ok(Json(snippet))
+}
+
+async fn delete_snippet<B: ApiBackend>(
+    State(backend): State<B>,
+    Path(id): Path<String>,
+) -> ApiResult<StatusCode> {
+    delete_snippet_internal(backend.database(), backend.journal(), &id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_tags<B: ApiBackend>(State(backend): State<B>) -> ApiResult<Json<Vec<Tag>>> {
+    Ok(Json(backend.database().tags().list()?))
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateTagBody {
+    name: String,
+}
+
+async fn create_tag<B: ApiBackend>(
+    State(backend): State<B>,
+    Json(body): Json<CreateTagBody>,
+) -> ApiResult<Json<Tag>> {
+    let tag = create_tag_internal(backend.database(), backend.journal(), &body.name)?;
+    Ok(Json(tag))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchParams {
+    q: String,
+    limit: Option<usize>,
+    #[serde(default)]
+    include_inactive: bool,
+}
+
+async fn search<B: ApiBackend>(
+    State(backend): State<B>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<Json<Vec<SearchResult>>> {
+    let results = semantic_search_internal(
+        backend.database(),
+        backend.http(),
+        &params.q,
+        params.limit,
+        params.include_inactive,
+        false,
+    )
+    .await?;
+    Ok(Json(results))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatBody {
+    message: String,
+    snippet_context: Option<SnippetContext>,
+    #[serde(default)]
+    include_inactive: bool,
+}
+
+async fn chat<B: ApiBackend>(
+    State(backend): State<B>,
+    Json(body): Json<ChatBody>,
+) -> ApiResult<Json<AiChatResponse>> {
+    let response = ai_chat_internal(
+        backend.database(),
+        backend.http(),
+        body.message,
+        body.snippet_context,
+        body.include_inactive,
+    )
+    .await?;
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::server::tests::{backend, TestBackend};
+
+    fn setup() -> (TestBackend, String) {
+        let backend = backend();
+        let db = backend.database();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem) VALUES ('s1', 'Docker prune', 'Disk full');
+                 INSERT INTO tags (id, name) VALUES ('t1', 'docker');
+                 INSERT INTO snippet_tags (snippet_id, tag_id) VALUES ('s1', 't1');",
+            )
+        })
+        .unwrap();
+        let token = db.settings().get().unwrap().api_token;
+        (backend, token)
+    }
+
+    async fn send(
+        backend: &TestBackend,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = match body {
+            Some(value) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(value.to_string())
+            }
+            None => Body::empty(),
+        };
+
+        let response = router(backend.clone())
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+        (status, json)
+    }
+
+    #[tokio::test]
+    async fn test_health_needs_no_token() {
+        let (backend, _) = setup();
+
+        let (status, body) = send(&backend, "GET", "/api/health", None, None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "recallsnippet");
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_valid_token_are_rejected() {
+        // Given
+        let (backend, _) = setup();
+
+        // When
+        let (missing, body) = send(&backend, "GET", "/api/snippets", None, None).await;
+        let (wrong, _) = send(&backend, "GET", "/api/snippets", Some("nope"), None).await;
+
+        // Then
+        assert_eq!(missing, StatusCode::UNAUTHORIZED);
+        assert_eq!(wrong, StatusCode::UNAUTHORIZED);
+        assert!(body["error"].as_str().unwrap().contains("token"));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_get_snippets() {
+        // Given
+        let (backend, token) = setup();
+
+        // When
+        let (list_status, list) = send(
+            &backend,
+            "GET",
+            "/api/snippets?tag=docker",
+            Some(&token),
+            None,
+        )
+        .await;
+        let (get_status, snippet) =
+            send(&backend, "GET", "/api/snippets/s1", Some(&token), None).await;
+        let (missing_status, _) =
+            send(&backend, "GET", "/api/snippets/nope", Some(&token), None).await;
+
+        // Then
+        assert_eq!(list_status, StatusCode::OK);
+        assert_eq!(list[0]["id"], "s1");
+        assert_eq!(list[0]["tags"][0]["name"], "docker");
+        assert_eq!(get_status, StatusCode::OK);
+        assert_eq!(snippet["title"], "Docker prune");
+        assert_eq!(missing_status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_create_tag_and_delete_snippet() {
+        // Given
+        let (backend, token) = setup();
+
+        // When
+        let (tag_status, tag) = send(
+            &backend,
+            "POST",
+            "/api/tags",
+            Some(&token),
+            Some(json!({ "name": " rust " })),
+        )
+        .await;
+        let (delete_status, _) =
+            send(&backend, "DELETE", "/api/snippets/s1", Some(&token), None).await;
+        let (_, remaining) = send(&backend, "GET", "/api/snippets", Some(&token), None).await;
+
+        // Then
+        assert_eq!(tag_status, StatusCode::OK);
+        assert_eq!(tag["name"], "rust");
+        assert_eq!(delete_status, StatusCode::NO_CONTENT);
+        assert_eq!(remaining, json!([]));
+    }
+
+    /// Stand-in Ollama answering every embedding request with the same vector
+    async fn serve_embedding(embedding: &str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let body = format!(r#"{{"embeddings":[{embedding}]}}"#);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_search_leaves_out_trashed_snippets() {
+        // Given
+        let (backend, token) = setup();
+        let base = serve_embedding("[1.0, 0.0]").await;
+        let db = backend.database();
+        db.with_connection(|conn| {
+            conn.execute_batch(
+                "INSERT INTO snippets (id, title, problem, is_deleted, deleted_at)
+                 VALUES ('s2', 'Old prune', 'Disk full', 1, CURRENT_TIMESTAMP);
+                 UPDATE settings SET ollama_max_retries = 0;",
+            )?;
+            conn.execute("UPDATE settings SET ollama_base_url = ?1", [&base])
+        })
+        .unwrap();
+        let model = db.settings().get_or_default().embedding_model;
+        db.embeddings().save_one("s1", &[0.6, 0.8], &model).unwrap();
+        db.embeddings().save_one("s2", &[1.0, 0.0], &model).unwrap();
+
+        // When
+        let (status, results) =
+            send(&backend, "GET", "/api/search?q=disk", Some(&token), None).await;
+
+        // Then
+        assert_eq!(status, StatusCode::OK);
+        let ids: Vec<&str> = results
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["snippet"]["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["s1"]);
+    }
+
+    #[tokio::test]
+    async fn test_regenerated_token_applies_immediately() {
+        // Given
+        let (backend, old_token) = setup();
+        let new_token = backend
+            .database()
+            .settings()
+            .regenerate_api_token()
+            .unwrap();
+
+        // When
+        let (old, _) = send(&backend, "GET", "/api/tags", Some(&old_token), None).await;
+        let (new, _) = send(&backend, "GET", "/api/tags", Some(&new_token), None).await;
+
+        // Then
+        assert_eq!(old, StatusCode::UNAUTHORIZED);
+        assert_eq!(new, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_token_matches() {
+        assert!(token_matches("abc", "abc"));
+        assert!(!token_matches("abc", "abd"));
+        assert!(!token_matches("abc", "ab"));
+        assert!(!token_matches("", ""));
+    }
+
+    #[test]
+    fn test_errors_map_to_status_codes() {
+        let status = |err: AppError| err.into_response().status();
+
+        assert_eq!(
+            status(AppError::NotFound("x".into())),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(AppError::Validation("x".into())),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            status(AppError::Duplicate("x".into())),
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            status(AppError::Ollama("x".into())),
+            StatusCode::BAD_GATEWAY
+        );
+        assert_eq!(
+            status(AppError::Database(rusqlite::Error::InvalidQuery)),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
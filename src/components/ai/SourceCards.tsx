@@ -21,6 +21,7 @@ export function SourceCards({ sources, onSelect }: SourceCardsProps) {
           className="inline-flex items-center gap-1.5 px-2.5 py-1 rounded-full text-xs font-medium bg-primary/10 text-primary hover:bg-primary/20 transition-colors cursor-pointer border border-primary/20"
         >
           <FileText className="h-3 w-3" />
+          <span className="text-primary/60">[{source.citation}]</span>
           <span className="truncate max-w-[150px]">{source.title}</span>
           <span className="text-primary/60">{Math.round(source.score * 100)}%</span>
         </button>
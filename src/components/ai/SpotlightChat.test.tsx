@@ -84,8 +84,16 @@ describe("SpotlightChat", () => {
 
   it("renders AI response with sources after sending message", async () => {
     mockInvoke.mockResolvedValue({
-      answer: "Use docker restart",
-      sources: [{ id: "s1", title: "Docker Restart", score: 0.85 }],
+      answer: "Use docker restart [1]",
+      sources: [
+        {
+          id: "s1",
+          title: "Docker Restart",
+          score: 0.85,
+          citation: 1,
+          positions: [{ start: 19, end: 22 }],
+        },
+      ],
     });
 
     render(
@@ -97,7 +105,7 @@ describe("SpotlightChat", () => {
     fireEvent.keyDown(textarea, { key: "Enter" });
 
     await waitFor(() => {
-      expect(screen.getByText("Use docker restart")).toBeInTheDocument();
+      expect(screen.getByText("Use docker restart [1]")).toBeInTheDocument();
     });
     expect(screen.getByText("Docker Restart")).toBeInTheDocument();
     expect(screen.getByText("85%")).toBeInTheDocument();
@@ -106,7 +114,9 @@ describe("SpotlightChat", () => {
   it("calls onSelectSnippet when source card clicked", async () => {
     mockInvoke.mockResolvedValue({
       answer: "Answer",
-      sources: [{ id: "s1", title: "My Snippet", score: 0.9 }],
+      sources: [
+        { id: "s1", title: "My Snippet", score: 0.9, citation: 1, positions: [] },
+      ],
     });
 
     const onSelect = vi.fn();
@@ -9,6 +9,7 @@ const mockResults: SearchResult[] = [
       id: "s1",
       title: "Docker networking fix",
       problem: "Container cannot reach host",
+      summary: null,
       codeLanguage: "bash",
       codePreview: null,
       tags: [{ id: "t1", name: "docker" }],
@@ -17,6 +18,7 @@ const mockResults: SearchResult[] = [
       isDeleted: false,
       deletedAt: null,
       lastAccessedAt: null,
+      status: "active",
     },
     score: 0.95,
   },
@@ -25,6 +27,7 @@ const mockResults: SearchResult[] = [
       id: "s2",
       title: "Rust async patterns",
       problem: "How to use tokio properly",
+      summary: null,
       codeLanguage: "rust",
       codePreview: null,
       tags: [{ id: "t2", name: "async" }],
@@ -33,6 +36,7 @@ const mockResults: SearchResult[] = [
       isDeleted: false,
       deletedAt: null,
       lastAccessedAt: null,
+      status: "active",
     },
     score: 0.72,
   },
@@ -8,6 +8,7 @@ const mockSnippet: SnippetSummary = {
   id: "s1",
   title: "Fix async/await error",
   problem: "Getting unhandled promise rejection when using async/await",
+  summary: null,
   codeLanguage: "typescript",
   codePreview: 'const result = await fetchData();',
   tags: [
@@ -19,6 +20,7 @@ const mockSnippet: SnippetSummary = {
   isDeleted: false,
   deletedAt: null,
   lastAccessedAt: null,
+  status: "active",
 };
 
 const queryClient = new QueryClient();
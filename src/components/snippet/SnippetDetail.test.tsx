@@ -10,15 +10,20 @@ const mockSnippet: Snippet = {
   problem: "Getting unhandled promise rejection",
   solution: "Wrap in try/catch block",
   code: 'try { await fetch(); } catch(e) { console.error(e); }',
+  summary: null,
   codeLanguage: "typescript",
   referenceUrl: "https://example.com/docs",
   tags: [{ id: "t1", name: "async" }],
+  files: [],
   createdAt: "2026-02-07",
   updatedAt: "2026-02-08",
   isFavorite: false,
   isDeleted: false,
   deletedAt: null,
   lastAccessedAt: null,
+  status: "active",
+  sourceApp: null,
+  capturedAt: null,
 };
 
 const createWrapper = () => {
@@ -65,15 +65,20 @@ describe("SnippetForm", () => {
       problem: "Existing Problem",
       solution: "Existing Solution",
       code: null,
+      summary: null,
       codeLanguage: null,
       referenceUrl: null,
       tags: [{ id: "t1", name: "rust" }],
+      files: [],
       createdAt: "2026-02-07",
       updatedAt: "2026-02-07",
       isFavorite: false,
       isDeleted: false,
       deletedAt: null,
       lastAccessedAt: null,
+      status: "active",
+      sourceApp: null,
+      capturedAt: null,
     };
 
     renderForm({ snippet });
@@ -151,4 +156,27 @@ describe("SnippetForm", () => {
 
     expect(screen.getByText("Saving...")).toBeInTheDocument();
   });
+
+  it("resubmits a refused duplicate when saving anyway", () => {
+    const handleSubmit = vi.fn();
+    renderForm({
+      onSubmit: handleSubmit,
+      duplicateError: "Likely duplicate of 'Fix port in use'",
+    });
+
+    fireEvent.change(screen.getByLabelText("Title *"), {
+      target: { value: "Title" },
+    });
+    fireEvent.change(screen.getByLabelText("Problem *"), {
+      target: { value: "Problem" },
+    });
+    fireEvent.click(screen.getByText("Save anyway"));
+
+    expect(screen.getByTestId("duplicate-error")).toHaveTextContent(
+      "Likely duplicate of 'Fix port in use'",
+    );
+    expect(handleSubmit).toHaveBeenCalledWith(
+      expect.objectContaining({ title: "Title", allowDuplicate: true }),
+    );
+  });
 });
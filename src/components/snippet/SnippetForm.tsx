@@ -16,6 +16,8 @@ interface SnippetFormProps {
   onCancel: () => void;
   onNavigateToSnippet?: (id: string) => void;
   isLoading?: boolean;
+  /** Error of a save refused as a likely duplicate, offering to save anyway */
+  duplicateError?: string | null;
 }
 
 export function SnippetForm({
@@ -25,6 +27,7 @@ export function SnippetForm({
   onCancel,
   onNavigateToSnippet,
   isLoading = false,
+  duplicateError = null,
 }: SnippetFormProps) {
   const [title, setTitle] = useState(snippet?.title ?? "");
   const [problem, setProblem] = useState(snippet?.problem ?? "");
@@ -84,17 +87,23 @@ export function SnippetForm({
     checkDuplicates(`${title} ${value}`);
   };
 
+  const buildInput = (): CreateSnippetInput => ({
+    title: title.trim(),
+    problem: problem.trim(),
+    solution: solution.trim() || undefined,
+    code: code.trim() || undefined,
+    codeLanguage: codeLanguage.trim().toLowerCase() || undefined,
+    referenceUrl: referenceUrl.trim() || undefined,
+    tagIds: selectedTagIds,
+  });
+
   const handleSubmit = (e: React.FormEvent) => {
     e.preventDefault();
-    onSubmit({
-      title: title.trim(),
-      problem: problem.trim(),
-      solution: solution.trim() || undefined,
-      code: code.trim() || undefined,
-      codeLanguage: codeLanguage.trim().toLowerCase() || undefined,
-      referenceUrl: referenceUrl.trim() || undefined,
-      tagIds: selectedTagIds,
-    });
+    onSubmit(buildInput());
+  };
+
+  const handleSaveAnyway = () => {
+    onSubmit({ ...buildInput(), allowDuplicate: true });
   };
 
   const toggleTag = (tagId: string) => {
@@ -111,13 +120,7 @@ export function SnippetForm({
     setAiError(null);
     suggestTags.mutate(content, {
       onSuccess: (suggested) => {
-        const matchedIds = availableTags
-          .filter((tag) =>
-            suggested.some(
-              (s) => s.toLowerCase() === tag.name.toLowerCase(),
-            ),
-          )
-          .map((tag) => tag.id);
+        const matchedIds = suggested.existing.map((tag) => tag.id);
         if (matchedIds.length > 0) {
           setSelectedTagIds((prev) => [
             ...new Set([...prev, ...matchedIds]),
@@ -169,6 +172,28 @@ export function SnippetForm({
         </div>
       )}
 
+      {duplicateError && (
+        <div
+          className="bg-amber-500/10 border border-amber-500/30 rounded-lg p-3 mb-6 flex items-center justify-between gap-3"
+          role="alert"
+          data-testid="duplicate-error"
+        >
+          <div className="flex items-center gap-2">
+            <AlertTriangle className="h-4 w-4 text-amber-500 shrink-0" />
+            <span className="text-sm text-amber-500">{duplicateError}</span>
+          </div>
+          <Button
+            type="button"
+            variant="outline"
+            size="sm"
+            onClick={handleSaveAnyway}
+            disabled={!isValid || isLoading}
+          >
+            Save anyway
+          </Button>
+        </div>
+      )}
+
       {similarSnippets.length > 0 && (
         <div className="bg-amber-500/10 border border-amber-500/30 rounded-lg p-3 mb-6" data-testid="duplicate-banner">
           <div className="flex items-center gap-2 mb-2">
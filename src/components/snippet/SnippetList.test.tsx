@@ -9,6 +9,7 @@ const mockSnippets: SnippetSummary[] = [
     id: "s1",
     title: "First Snippet",
     problem: "First problem",
+    summary: null,
     codeLanguage: "rust",
     codePreview: "fn main() {}",
     tags: [],
@@ -17,11 +18,13 @@ const mockSnippets: SnippetSummary[] = [
     isDeleted: false,
     deletedAt: null,
     lastAccessedAt: null,
+    status: "active",
   },
   {
     id: "s2",
     title: "Second Snippet",
     problem: "Second problem",
+    summary: null,
     codeLanguage: "python",
     codePreview: null,
     tags: [{ id: "t1", name: "web" }],
@@ -30,6 +33,7 @@ const mockSnippets: SnippetSummary[] = [
     isDeleted: false,
     deletedAt: null,
     lastAccessedAt: null,
+    status: "active",
   },
 ];
 
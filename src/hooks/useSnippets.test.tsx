@@ -35,14 +35,17 @@ const mockSummary: SnippetSummary = {
   id: "s1",
   title: "Test Snippet",
   problem: "Test problem",
+  summary: null,
   codeLanguage: "rust",
   codePreview: null,
   tags: [{ id: "t1", name: "rust" }],
+  files: [],
   createdAt: "2026-02-07",
   isFavorite: false,
   isDeleted: false,
   deletedAt: null,
   lastAccessedAt: null,
+  status: "active",
 };
 
 const mockSnippet: Snippet = {
@@ -51,6 +54,8 @@ const mockSnippet: Snippet = {
   code: "fn main() {}",
   referenceUrl: null,
   updatedAt: "2026-02-07",
+  sourceApp: null,
+  capturedAt: null,
 };
 
 describe("useSnippets", () => {
@@ -1,16 +1,41 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type {
   Snippet,
   SnippetSummary,
   CreateSnippetInput,
   UpdateSnippetInput,
   SnippetFilter,
+  InboxCounts,
+  BulkItemResult,
+  DuplicateCluster,
+  CapturedSnippet,
   Tag,
   SearchResult,
   Settings,
   UpdateSettingsInput,
   AiChatResponse,
   SnippetContext,
+  HistoryState,
+  SnippetTemplate,
+  CreateTemplateInput,
+  UpdateTemplateInput,
+  SnippetFileInput,
+  UpdateSnippetFileInput,
+  SnippetLink,
+  CreateLinkInput,
+  SnippetLinks,
+  SnippetReference,
+  LanguageInfo,
+  PromptTemplate,
+  TagSuggestions,
+  ModelDetails,
+  PullProgress,
+  ModelHealth,
+  OllamaDiagnostics,
+  ReindexProgress,
+  ReindexReport,
+  ApiServerStatus,
 } from "./types";
 
 export const snippetApi = {
@@ -27,27 +52,65 @@ export const snippetApi = {
     delete: (id: string) => invoke<void>("delete_snippet", { id }),
     toggleFavorite: (id: string) => invoke<Snippet>("toggle_favorite", { id }),
     restore: (id: string) => invoke<Snippet>("restore_snippet", { id }),
+    archive: (id: string) => invoke<Snippet>("archive_snippet", { id }),
+    counts: () => invoke<InboxCounts>("get_inbox_counts"),
     deletePermanent: (id: string) => invoke<void>("permanent_delete_snippet", { id }),
+    emptyTrash: () => invoke<string[]>("empty_trash"),
+    restoreAllFromTrash: () => invoke<string[]>("restore_all_from_trash"),
+
+    onTrashPurgeFailed: (handler: (error: string) => void): Promise<UnlistenFn> =>
+      listen<string>("trash-purge-failed", (event) => handler(event.payload)),
   };
 
+export const bulkApi = {
+  delete: (ids: string[]) =>
+    invoke<BulkItemResult[]>("bulk_delete_snippets", { ids }),
+  restore: (ids: string[]) =>
+    invoke<BulkItemResult[]>("bulk_restore_snippets", { ids }),
+  deletePermanent: (ids: string[]) =>
+    invoke<BulkItemResult[]>("bulk_permanent_delete_snippets", { ids }),
+  setFavorite: (ids: string[], favorite: boolean) =>
+    invoke<BulkItemResult[]>("bulk_set_favorite", { ids, favorite }),
+  addTags: (ids: string[], tagIds: string[]) =>
+    invoke<BulkItemResult[]>("bulk_add_tags", { ids, tagIds }),
+  removeTags: (ids: string[], tagIds: string[]) =>
+    invoke<BulkItemResult[]>("bulk_remove_tags", { ids, tagIds }),
+  setLanguage: (ids: string[], language: string | null) =>
+    invoke<BulkItemResult[]>("bulk_set_language", { ids, language }),
+};
+
 export const searchApi = {
-  semantic: (query: string, limit?: number) =>
-    invoke<SearchResult[]>("semantic_search", { query, limit }),
+  semantic: (query: string, limit?: number, includeInactive?: boolean) =>
+    invoke<SearchResult[]>("semantic_search", { query, limit, includeInactive }),
+
+  reindex: (all?: boolean) => invoke<ReindexReport>("reindex_embeddings", { all }),
+
+  onReindexProgress: (handler: (progress: ReindexProgress) => void): Promise<UnlistenFn> =>
+    listen<ReindexProgress>("reindex-progress", (event) => handler(event.payload)),
 };
 
 export const aiApi = {
   checkConnection: () => invoke<boolean>("check_ollama_connection"),
 
+  diagnoseConnection: () => invoke<OllamaDiagnostics>("diagnose_ollama_connection"),
+
   listModels: () => invoke<string[]>("list_ollama_models"),
 
   generateSolution: (problem: string, model?: string) =>
     invoke<string>("generate_solution", { problem, model }),
 
   suggestTags: (content: string, model?: string) =>
-    invoke<string[]>("suggest_tags", { content, model }),
+    invoke<TagSuggestions>("suggest_tags", { content, model }),
+
+  chat: (message: string, snippetContext?: SnippetContext, includeInactive?: boolean) =>
+    invoke<AiChatResponse>("ai_chat", { message, snippetContext, includeInactive }),
+
+  generateTitle: (id: string) => invoke<string>("generate_title", { id }),
 
-  chat: (message: string, snippetContext?: SnippetContext) =>
-    invoke<AiChatResponse>("ai_chat", { message, snippetContext }),
+  summarizeSnippet: (id: string) => invoke<Snippet>("summarize_snippet", { id }),
+
+  summarizeMissing: () =>
+    invoke<BulkItemResult[]>("summarize_missing_snippets"),
 };
 
 export const tagApi = {
@@ -64,3 +127,136 @@ export const settingsApi = {
   update: (input: UpdateSettingsInput) =>
     invoke<Settings>("update_settings", { input }),
 };
+
+export const captureApi = {
+  capture: () => invoke<CapturedSnippet>("capture_clipboard"),
+
+  onCaptured: (
+    handler: (captured: CapturedSnippet) => void,
+  ): Promise<UnlistenFn> =>
+    listen<CapturedSnippet>("snippet-captured", (event) =>
+      handler(event.payload),
+    ),
+
+  onCaptureFailed: (handler: (error: string) => void): Promise<UnlistenFn> =>
+    listen<string>("snippet-capture-failed", (event) => handler(event.payload)),
+
+  onShortcutFailed: (handler: (error: string) => void): Promise<UnlistenFn> =>
+    listen<string>("capture-shortcut-failed", (event) => handler(event.payload)),
+};
+
+export const apiServerApi = {
+  status: () => invoke<ApiServerStatus>("get_api_server_status"),
+
+  regenerateToken: () => invoke<string>("regenerate_api_token"),
+};
+
+export const historyApi = {
+  undo: () => invoke<string | null>("undo"),
+
+  redo: () => invoke<string | null>("redo"),
+
+  state: () => invoke<HistoryState>("get_history_state"),
+};
+
+export const templateApi = {
+  list: () => invoke<SnippetTemplate[]>("list_snippet_templates"),
+
+  get: (id: string) => invoke<SnippetTemplate>("get_snippet_template", { id }),
+
+  create: (input: CreateTemplateInput) =>
+    invoke<SnippetTemplate>("create_snippet_template", { input }),
+
+  update: (id: string, input: UpdateTemplateInput) =>
+    invoke<SnippetTemplate>("update_snippet_template", { id, input }),
+
+  delete: (id: string) => invoke<void>("delete_snippet_template", { id }),
+
+  render: (id: string, variables?: Record<string, string>) =>
+    invoke<CreateSnippetInput>("render_snippet_template", { id, variables }),
+
+  createSnippet: (id: string, variables?: Record<string, string>) =>
+    invoke<Snippet>("create_snippet_from_template", { id, variables }),
+};
+
+export const fileApi = {
+  add: (snippetId: string, input: SnippetFileInput) =>
+    invoke<Snippet>("add_snippet_file", { snippetId, input }),
+
+  update: (id: string, input: UpdateSnippetFileInput) =>
+    invoke<Snippet>("update_snippet_file", { id, input }),
+
+  reorder: (snippetId: string, fileIds: string[]) =>
+    invoke<Snippet>("reorder_snippet_files", { snippetId, fileIds }),
+
+  remove: (id: string) => invoke<Snippet>("remove_snippet_file", { id }),
+};
+
+export const linkApi = {
+  related: (id: string, limit?: number) =>
+    invoke<SearchResult[]>("get_related_snippets", { id, limit }),
+
+  list: (snippetId: string) =>
+    invoke<SnippetLinks>("get_snippet_links", { snippetId }),
+
+  create: (input: CreateLinkInput) =>
+    invoke<SnippetLink>("create_snippet_link", { input }),
+
+  delete: (id: string) => invoke<void>("delete_snippet_link", { id }),
+};
+
+export const referenceApi = {
+  list: (snippetId: string) =>
+    invoke<SnippetReference[]>("get_snippet_references", { snippetId }),
+
+  fetch: (snippetId: string, url: string) =>
+    invoke<SnippetReference>("fetch_snippet_reference", { snippetId, url }),
+
+  delete: (id: string) => invoke<void>("delete_snippet_reference", { id }),
+};
+
+export const duplicateApi = {
+  find: (threshold?: number) =>
+    invoke<DuplicateCluster[]>("find_duplicates", { threshold }),
+
+  check: (input: CreateSnippetInput) =>
+    invoke<SearchResult[]>("check_snippet_duplicates", { input }),
+
+  merge: (targetId: string, sourceIds: string[]) =>
+    invoke<Snippet>("merge_snippets", { targetId, sourceIds }),
+};
+
+export const languageApi = {
+  list: () => invoke<LanguageInfo[]>("list_languages"),
+
+  detect: (code: string, filename?: string) =>
+    invoke<string | null>("detect_language", { code, filename }),
+};
+
+export const promptApi = {
+  list: () => invoke<PromptTemplate[]>("list_prompt_templates"),
+
+  update: (key: string, template: string) =>
+    invoke<PromptTemplate>("update_prompt_template", { key, template }),
+
+  reset: (key: string) => invoke<PromptTemplate>("reset_prompt_template", { key }),
+
+  preview: (key: string, snippetId: string, template?: string, question?: string) =>
+    invoke<string>("preview_prompt_template", { key, snippetId, template, question }),
+};
+
+export const modelApi = {
+  health: () => invoke<ModelHealth>("check_model_health"),
+
+  pull: (name: string) => invoke<void>("pull_ollama_model", { name }),
+
+  delete: (name: string) => invoke<void>("delete_ollama_model", { name }),
+
+  show: (name: string) => invoke<ModelDetails>("show_ollama_model", { name }),
+
+  onPullProgress: (handler: (progress: PullProgress) => void): Promise<UnlistenFn> =>
+    listen<PullProgress>("ollama-pull-progress", (event) => handler(event.payload)),
+
+  onHealth: (handler: (health: ModelHealth) => void): Promise<UnlistenFn> =>
+    listen<ModelHealth>("ollama-model-health", (event) => handler(event.payload)),
+};
@@ -1,24 +1,53 @@
+export type SnippetStatus = "draft" | "active" | "archived";
+
 export interface Snippet {
   id: string;
   title: string;
   problem: string;
   solution: string | null;
+  summary: string | null;
   code: string | null;
   codeLanguage: string | null;
   referenceUrl: string | null;
   tags: Tag[];
+  files: SnippetFile[];
   createdAt: string;
   updatedAt: string;
   isFavorite: boolean;
   isDeleted: boolean;
   deletedAt: string | null;
   lastAccessedAt: string | null;
+  sourceApp: string | null;
+  capturedAt: string | null;
+  status: SnippetStatus;
+}
+
+export interface SnippetFile {
+  id: string;
+  snippetId: string;
+  filename: string | null;
+  language: string | null;
+  content: string;
+  position: number;
+}
+
+export interface SnippetFileInput {
+  filename?: string;
+  language?: string;
+  content: string;
+}
+
+export interface UpdateSnippetFileInput {
+  filename?: string;
+  language?: string;
+  content?: string;
 }
 
 export interface SnippetSummary {
   id: string;
   title: string;
   problem: string;
+  summary: string | null;
   codeLanguage: string | null;
   codePreview: string | null;
   tags: Tag[];
@@ -27,6 +56,7 @@ export interface SnippetSummary {
   isDeleted: boolean;
   deletedAt: string | null;
   lastAccessedAt: string | null;
+  status: SnippetStatus;
 }
 
 export interface CreateSnippetInput {
@@ -37,6 +67,9 @@ export interface CreateSnippetInput {
   codeLanguage?: string;
   referenceUrl?: string;
   tagIds: string[];
+  files?: SnippetFileInput[];
+  allowDuplicate?: boolean;
+  status?: SnippetStatus;
 }
 
 export interface UpdateSnippetInput {
@@ -47,15 +80,56 @@ export interface UpdateSnippetInput {
   codeLanguage?: string;
   referenceUrl?: string;
   tagIds?: string[];
+  status?: SnippetStatus;
+}
+
+export interface CapturedSnippet {
+  snippet: Snippet;
+  duplicates: SearchResult[];
+}
+
+export interface DuplicateCluster {
+  snippets: SnippetSummary[];
+  similarity: number;
+  sameCode: boolean;
+}
+
+export interface BulkItemResult {
+  id: string;
+  success: boolean;
+  changed: boolean;
+  error: string | null;
+}
+
+export interface ReindexProgress {
+  done: number;
+  total: number;
+}
+
+export interface ReindexReport {
+  total: number;
+  embedded: number;
+  failed: BulkItemResult[];
 }
 
 export interface SnippetFilter {
   language?: string;
   search?: string;
+  tag?: string;
   favoritesOnly?: boolean;
   trashOnly?: boolean;
   includeDeleted?: boolean;
   recentFirst?: boolean;
+  /** Defaults to active; ignored by the trash view */
+  status?: SnippetStatus;
+  anyStatus?: boolean;
+}
+
+export interface InboxCounts {
+  drafts: number;
+  active: number;
+  archived: number;
+  trash: number;
 }
 
 export interface Tag {
@@ -68,6 +142,8 @@ export interface SearchResult {
   score: number;
 }
 
+export type EmbeddingEncoding = "f32" | "f16" | "int8";
+
 export interface Settings {
   theme: string;
   ollamaBaseUrl: string;
@@ -75,12 +151,61 @@ export interface Settings {
   embeddingModel: string;
   searchLimit: number;
   dataPath: string | null;
+  trashRetentionDays: number;
+  detectLanguageWithLlm: boolean;
+  chatRelevanceThreshold: number;
+  chatTopK: number;
+  chatContextTokens: number;
+  chatModel: string | null;
+  solutionModel: string | null;
+  tagsModel: string | null;
+  summaryModel: string | null;
+  languageModel: string | null;
+  temperature: number | null;
+  topP: number | null;
+  seed: number | null;
+  numCtx: number | null;
+  numPredict: number | null;
+  keepAlive: string | null;
+  ollamaConnectTimeoutSecs: number;
+  ollamaRequestTimeoutSecs: number;
+  ollamaGenerateTimeoutSecs: number;
+  ollamaMaxRetries: number;
+  embeddingBatchSize: number;
+  embeddingConcurrency: number;
+  embeddingEncoding: EmbeddingEncoding;
+  apiServerEnabled: boolean;
+  apiServerPort: number;
+  apiToken: string;
+  mcpReadOnly: boolean;
+  captureShortcut: string;
+  captureWindowTitle: boolean;
+  embedReferences: boolean;
+}
+
+export interface ApiServerStatus {
+  running: boolean;
+  url: string | null;
+  error: string | null;
+}
+
+export interface TagSuggestions {
+  existing: Tag[];
+  newTags: string[];
+  fallback: boolean;
 }
 
 export interface SnippetSource {
   id: string;
   title: string;
   score: number;
+  citation: number;
+  positions: CitationSpan[];
+}
+
+export interface CitationSpan {
+  start: number;
+  end: number;
 }
 
 export interface AiChatResponse {
@@ -102,4 +227,183 @@ export interface UpdateSettingsInput {
   embeddingModel?: string;
   searchLimit?: number;
   dataPath?: string;
+  trashRetentionDays?: number;
+  detectLanguageWithLlm?: boolean;
+  chatRelevanceThreshold?: number;
+  chatTopK?: number;
+  chatContextTokens?: number;
+  chatModel?: string;
+  solutionModel?: string;
+  tagsModel?: string;
+  summaryModel?: string;
+  languageModel?: string;
+  temperature?: number;
+  topP?: number;
+  seed?: number;
+  numCtx?: number;
+  numPredict?: number;
+  keepAlive?: string;
+  ollamaConnectTimeoutSecs?: number;
+  ollamaRequestTimeoutSecs?: number;
+  ollamaGenerateTimeoutSecs?: number;
+  ollamaMaxRetries?: number;
+  embeddingBatchSize?: number;
+  embeddingConcurrency?: number;
+  embeddingEncoding?: EmbeddingEncoding;
+  apiServerEnabled?: boolean;
+  apiServerPort?: number;
+  mcpReadOnly?: boolean;
+  captureShortcut?: string;
+  captureWindowTitle?: boolean;
+  embedReferences?: boolean;
+  resetGenerationOptions?: boolean;
+}
+
+export interface HistoryState {
+  undoLabel: string | null;
+  redoLabel: string | null;
+  recordError: string | null;
+}
+
+export interface SnippetTemplate {
+  id: string;
+  name: string;
+  description: string | null;
+  title: string;
+  problem: string;
+  solution: string | null;
+  code: string | null;
+  codeLanguage: string | null;
+  referenceUrl: string | null;
+  tags: Tag[];
+  createdAt: string;
+  updatedAt: string;
+}
+
+export interface CreateTemplateInput {
+  name: string;
+  description?: string;
+  title: string;
+  problem: string;
+  solution?: string;
+  code?: string;
+  codeLanguage?: string;
+  referenceUrl?: string;
+  tagIds: string[];
+}
+
+export interface UpdateTemplateInput {
+  name?: string;
+  description?: string;
+  title?: string;
+  problem?: string;
+  solution?: string;
+  code?: string;
+  codeLanguage?: string;
+  referenceUrl?: string;
+  tagIds?: string[];
+}
+
+export type LinkType = "supersedes" | "see_also" | "caused_by";
+
+export interface SnippetLink {
+  id: string;
+  sourceId: string;
+  targetId: string;
+  linkType: LinkType;
+  createdAt: string;
+}
+
+export interface CreateLinkInput {
+  sourceId: string;
+  targetId: string;
+  linkType: LinkType;
+}
+
+export interface LinkedSnippet {
+  linkId: string;
+  linkType: LinkType;
+  snippet: SnippetSummary;
+}
+
+export interface SnippetLinks {
+  outgoing: LinkedSnippet[];
+  incoming: LinkedSnippet[];
+}
+
+export interface SnippetReference {
+  id: string;
+  snippetId: string;
+  url: string;
+  title: string | null;
+  content: string;
+  fetchedAt: string;
+  createdAt: string;
+}
+
+export interface LanguageInfo {
+  id: string;
+  name: string;
+  aliases: string[];
+}
+
+export interface PromptVariableInfo {
+  name: string;
+  description: string;
+  required: boolean;
+}
+
+export interface PromptTemplate {
+  key: string;
+  name: string;
+  description: string;
+  template: string;
+  defaultTemplate: string;
+  variables: PromptVariableInfo[];
+  isCustomized: boolean;
+  updatedAt: string | null;
+}
+
+export interface ModelDetails {
+  name: string;
+  family: string | null;
+  parameterSize: string | null;
+  quantizationLevel: string | null;
+  parameters: string | null;
+  contextLength: number | null;
+  embeddingLength: number | null;
+  capabilities: string[];
+}
+
+export interface PullProgress {
+  model: string;
+  status: string;
+  digest: string | null;
+  total: number | null;
+  completed: number | null;
+}
+
+export interface ConfiguredModel {
+  model: string;
+  usedFor: string[];
+}
+
+export interface ModelHealth {
+  reachable: boolean;
+  installed: string[];
+  missing: ConfiguredModel[];
+}
+
+export interface DiagnosticStep {
+  name: "url" | "dns" | "connect" | "http";
+  ok: boolean;
+  detail: string;
+  durationMs: number;
+}
+
+export interface OllamaDiagnostics {
+  baseUrl: string;
+  reachable: boolean;
+  version: string | null;
+  steps: DiagnosticStep[];
 }
@@ -28,6 +28,7 @@ const mockSummaries: SnippetSummary[] = [
     id: "s1",
     title: "Fix borrow checker",
     problem: "Cannot borrow as mutable",
+    summary: null,
     codeLanguage: "rust",
     codePreview: null,
     tags: [mockTags[0]],
@@ -36,11 +37,13 @@ const mockSummaries: SnippetSummary[] = [
     isDeleted: false,
     deletedAt: null,
     lastAccessedAt: null,
+    status: "active",
   },
   {
     id: "s2",
     title: "Async timeout",
     problem: "Future times out",
+    summary: null,
     codeLanguage: "typescript",
     codePreview: null,
     tags: [mockTags[1]],
@@ -49,6 +52,7 @@ const mockSummaries: SnippetSummary[] = [
     isDeleted: false,
     deletedAt: null,
     lastAccessedAt: null,
+    status: "active",
   },
 ];
 
@@ -58,15 +62,20 @@ const mockSnippet: Snippet = {
   problem: "Cannot borrow as mutable",
   solution: "Use Rc<RefCell<T>>",
   code: "use std::rc::Rc;",
+  summary: null,
   codeLanguage: "rust",
   referenceUrl: null,
   tags: [mockTags[0]],
+  files: [],
   createdAt: "2026-02-09",
   updatedAt: "2026-02-09",
   isFavorite: false,
   isDeleted: false,
   deletedAt: null,
   lastAccessedAt: null,
+  status: "active",
+  sourceApp: null,
+  capturedAt: null,
 };
 
 function createWrapper() {
@@ -1,4 +1,4 @@
-import { useState, useCallback } from "react";
+import { useState, useCallback, useEffect } from "react";
 import { MainLayout } from "@/components/layout/MainLayout";
 import { SnippetList } from "@/components/snippet/SnippetList";
 import { SnippetDetail } from "@/components/snippet/SnippetDetail";
@@ -26,10 +26,19 @@ import type {
   SnippetFilter,
 } from "@/lib/types";
 
+/** Start of the error a create refused as a likely duplicate comes back with */
+const DUPLICATE_ERROR_PREFIX = "Likely duplicate of ";
+
 function HomeContent() {
   const { view, selectedId, filterLanguage, activeFolder } = useAppState();
   const dispatch = useAppDispatch();
   const [spotlightOpen, setSpotlightOpen] = useState(false);
+  const [duplicateError, setDuplicateError] = useState<string | null>(null);
+
+  // A refused save only concerns the form it came from
+  useEffect(() => {
+    setDuplicateError(null);
+  }, [view]);
   const [spotlightContext, setSpotlightContext] = useState<
     SnippetContext | undefined
   >();
@@ -57,10 +66,17 @@ function HomeContent() {
   };
 
   const handleCreate = (data: CreateSnippetInput) => {
+    setDuplicateError(null);
     createMutation.mutate(data, {
       onSuccess: (created) => {
         dispatch({ type: "SELECT_SNIPPET", id: created.id });
       },
+      onError: (error) => {
+        const message = String(error);
+        if (message.startsWith(DUPLICATE_ERROR_PREFIX)) {
+          setDuplicateError(message);
+        }
+      },
     });
   };
 
@@ -148,6 +164,7 @@ function HomeContent() {
             onCancel={handleBack}
             onNavigateToSnippet={handleSelect}
             isLoading={createMutation.isPending}
+            duplicateError={duplicateError}
           />
         );
 